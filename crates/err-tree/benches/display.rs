@@ -0,0 +1,57 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use err_tree::{ErrorTree, ErrorTreeExt, ErrorTreeSource};
+use std::fmt;
+
+/// A linear chain of error trees.
+#[derive(Debug)]
+struct Chain {
+    level: usize,
+    next: Option<Box<Chain>>,
+}
+
+impl Chain {
+    fn new(depth: usize) -> Self {
+        let mut chain = Chain {
+            level: 0,
+            next: None,
+        };
+        for level in 1..depth {
+            chain = Chain {
+                level,
+                next: Some(Box::new(chain)),
+            };
+        }
+        chain
+    }
+}
+
+impl fmt::Display for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "level {}\nwith a second line", self.level)
+    }
+}
+
+impl ErrorTree for Chain {
+    fn sources(&self) -> Box<dyn Iterator<Item = ErrorTreeSource<'_>> + '_> {
+        Box::new(self.next.iter().map(|next| ErrorTreeSource::Tree(&**next)))
+    }
+}
+
+fn bench_display(c: &mut Criterion) {
+    let chain = Chain::new(1000);
+    c.bench_function("display_tree/chain-1000", |b| {
+        b.iter(|| black_box(&chain).display_tree().to_string())
+    });
+    // Setting a maximum depth disables the fast path for chains.
+    c.bench_function("display_tree/chain-1000-general", |b| {
+        b.iter(|| {
+            black_box(&chain)
+                .display_tree()
+                .max_depth(usize::MAX)
+                .to_string()
+        })
+    });
+}
+
+criterion_group!(benches, bench_display);
+criterion_main!(benches);
@@ -1,8 +1,14 @@
 use crate::{ErrorTree, ErrorTreeSource};
+use alloc::boxed::Box;
 
 impl ErrorTree for anyhow::Error {
     fn sources(&self) -> Box<dyn Iterator<Item = ErrorTreeSource<'_>> + '_> {
         // Represent a standard error as a chain of errors.
         Box::new(self.source().into_iter().map(ErrorTreeSource::Error))
     }
+
+    #[inline]
+    fn as_error(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.as_ref())
+    }
 }
@@ -1,7 +1,14 @@
-use crate::{ErrorTree, ErrorTreeSource};
+use crate::{ErrorTree, ErrorTreeExt, ErrorTreeSource};
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
 use std::{error, fmt};
 
 /// Wraps an error to implement [`ErrorTree`] on it.
+///
+/// This type is only available with the `std` feature.
 #[repr(transparent)]
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ErrorWrapper<E>(E);
@@ -57,8 +64,112 @@ impl<E: error::Error + Send + Sync> ErrorTree for ErrorWrapper<E> {
 }
 
 /// Wraps an [`ErrorTree`] to implement [`Error`](std::error::Error) on it.
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+///
+/// This is useful for passing an error tree to APIs that require a standard error, such as
+/// `Box<dyn Error>` sinks.
+///
+/// A standard error has at most one source, so [`source`](error::Error::source) returns the
+/// tree's *first* source, followed by that source's first source, and so on: each branch of the
+/// tree is collapsed into its first element, and the remaining sources aren't reachable through
+/// the `Error` chain. They're still available through [`as_inner`](Self::as_inner).
+///
+/// The chain is captured as messages when the wrapper is created, so the errors in it can't be
+/// downcast.
+///
+/// This type is only available with the `std` feature.
+///
+/// ```
+/// use err_tree::{ErrorTreeWrapper, OwnedErrorTree};
+///
+/// let tree = OwnedErrorTree::from_msg_and_sources(
+///     "outer",
+///     vec![
+///         OwnedErrorTree::from_msg_and_sources("first", vec![]),
+///         OwnedErrorTree::from_msg_and_sources("second", vec![]),
+///     ],
+/// );
+/// let error: Box<dyn std::error::Error> = Box::new(ErrorTreeWrapper::new(tree));
+/// assert_eq!(error.to_string(), "outer");
+/// assert_eq!(error.source().unwrap().to_string(), "first");
+/// ```
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ErrorTreeWrapper<E> {
     inner: E,
-    // This is a representation of
+    // This is a representation of the first-source chain of `inner`, computed when the wrapper is
+    // created, since `source` must return a reference.
+    source: Option<Box<FlattenedSource>>,
+}
+
+impl<E: ErrorTree> ErrorTreeWrapper<E> {
+    /// Create a new error tree wrapper.
+    pub fn new(tree: E) -> Self {
+        let messages: Vec<_> = tree.chain().map(|source| source.to_string()).collect();
+        let source = messages.into_iter().rev().fold(None, |source, msg| {
+            Some(Box::new(FlattenedSource { msg, source }))
+        });
+
+        ErrorTreeWrapper {
+            inner: tree,
+            source,
+        }
+    }
+
+    /// Get the wrapped error tree.
+    #[inline]
+    pub fn into_inner(self) -> E {
+        self.inner
+    }
+
+    /// Access the wrapped error tree.
+    #[inline]
+    pub fn as_inner(&self) -> &E {
+        &self.inner
+    }
+}
+
+impl<E: ErrorTree> From<E> for ErrorTreeWrapper<E> {
+    fn from(tree: E) -> Self {
+        ErrorTreeWrapper::new(tree)
+    }
+}
+
+impl<E: ErrorTree> fmt::Debug for ErrorTreeWrapper<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.inner, f)
+    }
+}
+
+impl<E: ErrorTree> fmt::Display for ErrorTreeWrapper<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.inner, f)
+    }
+}
+
+impl<E: ErrorTree> error::Error for ErrorTreeWrapper<E> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn error::Error + 'static))
+    }
+}
+
+/// One link of the chain returned by [`ErrorTreeWrapper`]'s `source`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct FlattenedSource {
+    msg: String,
+    source: Option<Box<FlattenedSource>>,
+}
+
+impl fmt::Display for FlattenedSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.msg)
+    }
+}
+
+impl error::Error for FlattenedSource {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn error::Error + 'static))
+    }
 }
@@ -1,47 +1,888 @@
-use crate::{ErrorTree, ErrorTreeSource};
+use crate::{
+    iter::{Ancestors, NodeKey},
+    ErrorTree, ErrorTreeSource, Severity,
+};
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt::{self, Write};
 use indent_write::fmt::IndentWriter;
-use std::fmt::{self, Write};
 
 /// A displayer for error trees, including their sources, in a tree-like format.
+///
+/// # Trailing newlines
+///
+/// Regardless of the shape of the tree, the output always ends with a single newline written by
+/// the displayer: a tree with no sources is displayed as its message followed by a newline, and a
+/// tree with sources ends with the newline after its last source. (The one exception is
+/// [`sources_only`](Self::sources_only) or [`hide_root_message`](Self::hide_root_message) for a
+/// tree with no sources, which display nothing.)
+///
+/// To embed the output in a larger message without the final newline, use
+/// [`trim_trailing_newline`](Self::trim_trailing_newline).
+///
+/// # Cycles
+///
+/// Trees are expected to be acyclic, but displaying a tree always terminates even if a buggy
+/// [`ErrorTree`] or [`Error`](std::error::Error) impl returns a source that loops back to one of
+/// its ancestors. Such a source is displayed as `(cycle)`, and its sources aren't displayed.
+/// Nodes are identified by their address, so the same node appearing in separate branches isn't
+/// a cycle. Zero-sized nodes don't have unique addresses, and aren't checked.
 #[derive(Clone, Copy, Debug)]
 pub struct ErrorTreeDisplay<'a, ET: ?Sized> {
     tree: &'a ET,
+    options: DisplayOptions<'static>,
 }
 
 impl<'a, ET: ErrorTree + ?Sized> ErrorTreeDisplay<'a, ET> {
     /// Create a new displayer for the given error tree.
     #[inline]
     pub fn new(tree: &'a ET) -> Self {
-        Self { tree }
+        Self {
+            tree,
+            options: DisplayOptions::default(),
+        }
+    }
+
+    /// Only display the sources of the tree.
+    ///
+    /// The root's own message and the `Caused by:` header are omitted, which is useful when the
+    /// root message is already shown elsewhere, such as in a section heading. If the tree has no
+    /// sources, nothing is displayed.
+    #[inline]
+    pub fn sources_only(mut self) -> Self {
+        self.options.sources_only = true;
+        self
+    }
+
+    /// Omit the root's own message, but keep the `Caused by:` header before the sources.
+    ///
+    /// This is useful when the root message is redundant, for example because it was set by a
+    /// wrapper outside of your control. Unlike [`sources_only`](Self::sources_only), the output
+    /// still reads as a list of causes. If the tree has no sources, nothing is displayed.
+    #[inline]
+    pub fn hide_root_message(mut self) -> Self {
+        self.options.hide_root_message = true;
+        self
+    }
+
+    /// Omit the newline that would otherwise be written at the end of the output.
+    ///
+    /// Only the final newline written by the displayer is removed. Newlines that are part of
+    /// messages, and the newlines between sources, are preserved.
+    #[inline]
+    pub fn trim_trailing_newline(mut self) -> Self {
+        self.options.trim_trailing_newline = true;
+        self
+    }
+
+    /// Show the duration recorded in each node's [`metadata`](ErrorTree::metadata), if any.
+    ///
+    /// Durations are shown in milliseconds after the node's message, for example
+    /// `request failed [123ms]`. Nodes without a recorded duration are displayed as usual.
+    #[inline]
+    pub fn show_durations(mut self) -> Self {
+        self.options.show_durations = true;
+        self
+    }
+
+    /// Limit the output to at most `bytes` bytes.
+    ///
+    /// If the output would be longer than that, it is cut off (on a character boundary) once the
+    /// limit is reached, and `... (output truncated)` is written after it. The marker itself is
+    /// not counted towards the limit. This is useful for protecting log sinks from very large
+    /// trees.
+    #[inline]
+    pub fn max_output_len(mut self, bytes: usize) -> Self {
+        self.options.max_output_len = Some(bytes);
+        self
+    }
+
+    /// Wrap lines that are longer than `cols` characters.
+    ///
+    /// Lines are broken between words. Continuation lines are indented to line up with the start
+    /// of the message they belong to, rather than with the `-` or `+` marker before it. Words
+    /// that are longer than the available width are broken across lines.
+    ///
+    /// `Caused by:` headers are never wrapped. If a line's indentation and marker alone take up
+    /// `cols` or more characters, each of its lines still holds at least one character of the
+    /// message, so such lines are longer than `cols`. A width of 0 disables wrapping.
+    #[inline]
+    pub fn wrap_width(mut self, cols: usize) -> Self {
+        self.options.wrap_width = Some(cols);
+        self
+    }
+
+    /// Only display sources with at least the given [`Severity`].
+    ///
+    /// A source is displayed if the [severity](crate::NodeMetadata::severity) in its metadata is
+    /// at least `min_severity`, or if any of its descendants is displayed. Sources without a
+    /// recorded severity, including plain [`std::error::Error`]s, are treated as being below any
+    /// threshold. Everything else, including entire subtrees with no severe enough nodes, is
+    /// omitted.
+    ///
+    /// The root of the tree is always displayed.
+    #[inline]
+    pub fn min_severity(mut self, min_severity: Severity) -> Self {
+        self.options.min_severity = Some(min_severity);
+        self
+    }
+
+    /// Display the tree as an outline, with each node's depth shown by repeating `bullet`.
+    ///
+    /// The root is prefixed with one `bullet`, its sources with two, and so on. With `'*'` as the
+    /// bullet, this is the heading format used by Emacs org-mode:
+    ///
+    /// ```text
+    /// * root
+    /// ** source 1
+    /// *** cause
+    /// ** source 2
+    /// ```
+    ///
+    /// Continuation lines of multi-line messages are indented to line up with the message.
+    /// Unlike the default format, there is no `Caused by:` header, and each depth is marked
+    /// explicitly rather than through indentation.
+    #[inline]
+    pub fn outline(mut self, bullet: char) -> Self {
+        self.options.outline = Some(bullet);
+        self
+    }
+
+    /// Only display sources up to `max_depth` levels below the root.
+    ///
+    /// The root's sources are at depth 1, their sources are at depth 2, and so on. Wherever a
+    /// node at `max_depth` has sources of its own, they're replaced with a single
+    /// `... (N more levels)` line, where `N` is the number of levels omitted below that node. With
+    /// `max_depth` set to 0, only the root is displayed. By default, there's no limit.
+    #[inline]
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.options.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Use custom markers before each source.
+    ///
+    /// `single` replaces the `-` used for sources in a chain, and `multi` replaces the `+` used
+    /// for sources that have siblings. Indentation is unchanged, so the markers should be a single
+    /// column wide.
+    #[inline]
+    pub fn with_bullets(mut self, single: char, multi: char) -> Self {
+        self.options.bullets = Some((single, multi));
+        self
+    }
+
+    /// Number sources that have siblings, as `1.`, `2.`, and so on, instead of marking them with
+    /// `+`.
+    ///
+    /// Numbering starts again at 1 for each node's sources, so that a source can be referred to
+    /// by its position within its branch. Sources in a chain keep their `-` marker. Continuation
+    /// lines and nested sources are indented to line up with the wider markers. This has no
+    /// effect on the [`outline`](Self::outline) format.
+    #[inline]
+    pub fn numbered(mut self, enable: bool) -> Self {
+        self.options.numbered = enable;
+        self
+    }
+
+    /// Color the `-` and `+` markers (or [`outline`](Self::outline) bullets) and the `Caused by:`
+    /// headers with ANSI escape codes.
+    ///
+    /// This is useful when printing to a terminal. Messages themselves are never colored. With
+    /// `enable` set to false, which is the default, no escape codes are written, so the output is
+    /// suitable for piping to other programs; callers will typically pass in whether the output
+    /// stream is a terminal.
+    ///
+    /// Escape codes are counted as regular characters by [`wrap_width`](Self::wrap_width) and
+    /// [`max_output_len`](Self::max_output_len).
+    #[inline]
+    pub fn colored(mut self, enable: bool) -> Self {
+        self.options.colored = enable;
+        self
+    }
+
+    /// Display each node's message with a custom formatter.
+    ///
+    /// `formatter` is called with each node, including the root, along with its depth: 0 for the
+    /// root, 1 for its sources, and so on. The string it returns replaces the node's message,
+    /// while the displayer still takes care of indentation, markers and headers. This allows for
+    /// arbitrary per-node decorations, such as codes, icons or colors. Options that change how
+    /// messages are written, like [`show_durations`](Self::show_durations), are ignored.
+    ///
+    /// The formatter is the last option to be set, so call this after any other options.
+    #[inline]
+    pub fn with_node_formatter<F>(self, formatter: F) -> ErrorTreeFormatterDisplay<'a, ET, F>
+    where
+        ET: Sized + 'static,
+        F: Fn(&ErrorTreeSource<'_>, usize) -> String,
+    {
+        ErrorTreeFormatterDisplay {
+            display: self,
+            formatter,
+        }
     }
 }
 
 impl<'a, ET: ErrorTree + ?Sized> fmt::Display for ErrorTreeDisplay<'a, ET> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        display_tree(f, &self.tree)
+        let mut ancestors = Ancestors::new(NodeKey::of_tree(self.tree.as_dyn_error_tree()));
+        with_options(f, &self.options, |f| {
+            display_root(
+                f,
+                &self.tree,
+                Node::Root(&self.tree),
+                &self.options,
+                &mut ancestors,
+            )
+        })
+    }
+}
+
+/// A displayer for error trees with a custom formatter for each node.
+///
+/// Returned by [`ErrorTreeDisplay::with_node_formatter`].
+#[derive(Clone, Copy)]
+pub struct ErrorTreeFormatterDisplay<'a, ET, F> {
+    display: ErrorTreeDisplay<'a, ET>,
+    formatter: F,
+}
+
+impl<'a, ET, F> fmt::Display for ErrorTreeFormatterDisplay<'a, ET, F>
+where
+    ET: ErrorTree + 'static,
+    F: Fn(&ErrorTreeSource<'_>, usize) -> String,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let options = DisplayOptions {
+            node_formatter: Some(NodeFormatter(&self.formatter)),
+            ..self.display.options
+        };
+        let tree = self.display.tree;
+        let mut ancestors = Ancestors::new(NodeKey::of_tree(tree));
+        with_options(f, &options, |f| {
+            display_root(
+                f,
+                tree,
+                Node::Source(ErrorTreeSource::Tree(tree)),
+                &options,
+                &mut ancestors,
+            )
+        })
+    }
+}
+
+impl<'a, ET: fmt::Debug, F> fmt::Debug for ErrorTreeFormatterDisplay<'a, ET, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ErrorTreeFormatterDisplay")
+            .field("display", &self.display)
+            .finish_non_exhaustive()
     }
 }
 
 /// A displayer for [`ErrorTreeSource`] in a tree-like format.
+///
+/// As with [`ErrorTreeDisplay`], the output always ends with a single newline unless
+/// [`trim_trailing_newline`](Self::trim_trailing_newline) is called.
 #[derive(Clone, Copy, Debug)]
 pub struct ErrorTreeSourceDisplay<'a> {
     source: ErrorTreeSource<'a>,
+    options: DisplayOptions<'static>,
 }
 
 impl<'a> ErrorTreeSourceDisplay<'a> {
     /// Create a new displayer for the given error tree source.
     #[inline]
     pub fn new(source: ErrorTreeSource<'a>) -> Self {
-        Self { source }
+        Self {
+            source,
+            options: DisplayOptions::default(),
+        }
+    }
+
+    /// Omit the newline that would otherwise be written at the end of the output.
+    ///
+    /// Only the final newline written by the displayer is removed. Newlines that are part of
+    /// messages, and the newlines between sources, are preserved.
+    #[inline]
+    pub fn trim_trailing_newline(mut self) -> Self {
+        self.options.trim_trailing_newline = true;
+        self
     }
 }
 
 impl<'a> fmt::Display for ErrorTreeSourceDisplay<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.source {
-            ErrorTreeSource::Error(error) => display_error(f, error),
-            ErrorTreeSource::Tree(tree) => display_tree(f, tree),
+        with_options(f, &self.options, |f| match self.source {
+            #[cfg(feature = "std")]
+            ErrorTreeSource::Error(error) => display_error(f, error, &self.options),
+            ErrorTreeSource::Tree(tree) => {
+                let mut ancestors = Ancestors::new(NodeKey::of_tree(tree));
+                display_tree(
+                    f,
+                    tree,
+                    Node::Source(self.source),
+                    &self.options,
+                    &mut ancestors,
+                )
+            }
+        })
+    }
+}
+
+/// A displayer for error trees on a single line.
+///
+/// Returned by [`ErrorTreeExt::display_tree_compact`](crate::ErrorTreeExt::display_tree_compact).
+///
+/// Each node is followed by `: ` and its sources. A single source continues the chain, while
+/// multiple sources are grouped as `{first; second}`. Newlines within messages are collapsed into
+/// a single space. For example:
+///
+/// ```text
+/// top: mishap5: mishap4: {mishap2: cause; mishap3}
+/// ```
+///
+/// Unlike [`ErrorTreeDisplay`], there is no trailing newline.
+#[derive(Clone, Copy, Debug)]
+pub struct ErrorTreeCompactDisplay<'a, ET: ?Sized> {
+    tree: &'a ET,
+}
+
+impl<'a, ET: ErrorTree + ?Sized> ErrorTreeCompactDisplay<'a, ET> {
+    /// Create a new compact displayer for the given error tree.
+    #[inline]
+    pub fn new(tree: &'a ET) -> Self {
+        Self { tree }
+    }
+}
+
+impl<'a, ET: ErrorTree + ?Sized> fmt::Display for ErrorTreeCompactDisplay<'a, ET> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_compact_message(f, &self.tree)?;
+        let mut ancestors = Ancestors::new(NodeKey::of_tree(self.tree.as_dyn_error_tree()));
+        display_compact_sources(f, self.tree.sources().collect(), &mut ancestors)
+    }
+}
+
+fn display_compact_sources<'a>(
+    f: &mut dyn fmt::Write,
+    mut sources: Vec<ErrorTreeSource<'a>>,
+    ancestors: &mut Ancestors<'a>,
+) -> fmt::Result {
+    // As with display_nested_tree, loop over chains rather than recursing for each link.
+    loop {
+        match sources.as_slice() {
+            [] => return Ok(()),
+            &[source] => {
+                f.write_str(": ")?;
+                if !ancestors.push(NodeKey::of_source(source)) {
+                    return f.write_str(CYCLE);
+                }
+                write_compact_message(f, &source)?;
+                sources = source.sources().collect();
+            }
+            _ => {
+                f.write_str(": {")?;
+                let len = ancestors.len();
+                for (i, &source) in sources.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str("; ")?;
+                    }
+                    if !ancestors.push(NodeKey::of_source(source)) {
+                        f.write_str(CYCLE)?;
+                        continue;
+                    }
+                    write_compact_message(f, &source)?;
+                    display_compact_sources(f, source.sources().collect(), ancestors)?;
+                    ancestors.truncate(len);
+                }
+                return f.write_char('}');
+            }
+        }
+    }
+}
+
+fn write_compact_message(f: &mut dyn fmt::Write, msg: &dyn fmt::Display) -> fmt::Result {
+    let mut writer = CollapseNewlines {
+        inner: f,
+        pending_space: false,
+    };
+    write!(writer, "{}", msg)
+}
+
+/// A writer that replaces each run of newlines with a single space.
+///
+/// Spaces are only written before further output, so trailing newlines are dropped.
+struct CollapseNewlines<W> {
+    inner: W,
+    pending_space: bool,
+}
+
+impl<W: fmt::Write> fmt::Write for CollapseNewlines<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for (i, line) in s.split('\n').enumerate() {
+            if i > 0 {
+                self.pending_space = true;
+            }
+            if line.is_empty() {
+                continue;
+            }
+            if core::mem::take(&mut self.pending_space) {
+                self.inner.write_char(' ')?;
+            }
+            self.inner.write_str(line)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct DisplayOptions<'f> {
+    sources_only: bool,
+    hide_root_message: bool,
+    trim_trailing_newline: bool,
+    show_durations: bool,
+    max_output_len: Option<usize>,
+    wrap_width: Option<usize>,
+    min_severity: Option<Severity>,
+    max_depth: Option<usize>,
+    outline: Option<char>,
+    bullets: Option<(char, char)>,
+    numbered: bool,
+    colored: bool,
+    node_formatter: Option<NodeFormatter<'f>>,
+}
+
+#[derive(Clone, Copy)]
+struct NodeFormatter<'f>(&'f dyn Fn(&ErrorTreeSource<'_>, usize) -> String);
+
+impl<'f> fmt::Debug for NodeFormatter<'f> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("NodeFormatter")
+    }
+}
+
+impl<'f> DisplayOptions<'f> {
+    fn bullets(&self) -> (char, char) {
+        self.bullets.unwrap_or(('-', '+'))
+    }
+
+    /// Returns the marker for a source displayed as `kind`, where `position` is the source's
+    /// position among its siblings, starting at 1.
+    fn marker(&self, kind: DisplayKind, position: usize) -> Styled<Marker> {
+        let (single, multi) = self.bullets();
+        let marker = match kind {
+            DisplayKind::Single => Marker::Bullet(single),
+            DisplayKind::Multi if self.numbered => Marker::Number(position),
+            DisplayKind::Multi => Marker::Bullet(multi),
+        };
+        Styled {
+            value: marker,
+            style: self.colored.then_some(BULLET_STYLE),
+        }
+    }
+
+    fn header(&self) -> Styled<&'static str> {
+        Styled {
+            value: "Caused by:",
+            style: self.colored.then_some(HEADER_STYLE),
+        }
+    }
+
+    fn sources<'a>(&self, tree: &'a dyn ErrorTree) -> impl Iterator<Item = ErrorTreeSource<'a>> {
+        let min_severity = self.min_severity;
+        tree.sources().filter(move |&source| match min_severity {
+            Some(min_severity) => meets_severity(source, min_severity),
+            None => true,
+        })
+    }
+
+    /// Returns true if the sources of a node at `depth` should be elided.
+    fn elide(&self, depth: usize) -> bool {
+        self.max_depth.is_some_and(|max_depth| depth >= max_depth)
+    }
+
+    /// Returns the number of levels in the subtrees rooted at `sources`.
+    fn levels<'a>(&self, sources: impl Iterator<Item = ErrorTreeSource<'a>>) -> usize {
+        let mut levels = 0;
+        let mut stack: Vec<_> = sources.map(|source| (1, source)).collect();
+        let mut ancestors = Ancestors::default();
+        while let Some((level, source)) = stack.pop() {
+            ancestors.truncate(level - 1);
+            if !ancestors.push(NodeKey::of_source(source)) {
+                continue;
+            }
+            levels = levels.max(level);
+            match source {
+                #[cfg(feature = "std")]
+                ErrorTreeSource::Error(error) => stack.extend(
+                    error
+                        .source()
+                        .map(|source| (level + 1, ErrorTreeSource::Error(source))),
+                ),
+                ErrorTreeSource::Tree(tree) => {
+                    stack.extend(self.sources(tree).map(|source| (level + 1, source)))
+                }
+            }
+        }
+
+        levels
+    }
+}
+
+// Cyan for markers, and bold for headers.
+const BULLET_STYLE: &str = "\x1b[36m";
+const HEADER_STYLE: &str = "\x1b[1m";
+const RESET_STYLE: &str = "\x1b[0m";
+
+/// A value that's optionally surrounded by ANSI escape codes when displayed.
+struct Styled<T> {
+    value: T,
+    style: Option<&'static str>,
+}
+
+impl<T: fmt::Display> fmt::Display for Styled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.style {
+            Some(style) => write!(f, "{style}{}{RESET_STYLE}", self.value),
+            None => write!(f, "{}", self.value),
+        }
+    }
+}
+
+/// The marker written before a source.
+#[derive(Clone, Copy, Debug)]
+enum Marker {
+    Bullet(char),
+    Number(usize),
+}
+
+impl Marker {
+    /// Returns the number of columns taken up by the marker.
+    fn width(self) -> usize {
+        match self {
+            Marker::Bullet(_) => 1,
+            // The digits, followed by a dot.
+            Marker::Number(n) => n.checked_ilog10().unwrap_or(0) as usize + 2,
+        }
+    }
+}
+
+impl fmt::Display for Marker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Marker::Bullet(bullet) => f.write_char(*bullet),
+            Marker::Number(n) => write!(f, "{n}."),
+        }
+    }
+}
+
+/// Returns `n` spaces, for indentation that depends on the width of a marker.
+fn spaces(n: usize) -> &'static str {
+    // Markers are at most as wide as `usize::MAX` followed by a dot, so this is long enough.
+    const SPACES: &str = "                                ";
+    &SPACES[..n]
+}
+
+/// The marker displayed in place of sources omitted by [`ErrorTreeDisplay::max_depth`].
+struct Elided {
+    levels: usize,
+}
+
+impl fmt::Display for Elided {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.levels {
+            1 => f.write_str("... (1 more level)"),
+            levels => write!(f, "... ({levels} more levels)"),
+        }
+    }
+}
+
+/// Returns true if `source` or any of its descendants has a severity of at least `min_severity`.
+fn meets_severity(source: ErrorTreeSource<'_>, min_severity: Severity) -> bool {
+    let mut stack = vec![(0, source)];
+    let mut ancestors = Ancestors::default();
+    while let Some((depth, source)) = stack.pop() {
+        ancestors.truncate(depth);
+        if !ancestors.push(NodeKey::of_source(source)) {
+            continue;
+        }
+
+        let severity = match source {
+            #[cfg(feature = "std")]
+            ErrorTreeSource::Error(_) => None,
+            ErrorTreeSource::Tree(tree) => tree.metadata().and_then(|metadata| metadata.severity()),
+        };
+        if severity.is_some_and(|severity| severity >= min_severity) {
+            return true;
+        }
+        stack.extend(source.sources().map(|source| (depth + 1, source)));
+    }
+
+    false
+}
+
+fn with_options(
+    f: &mut fmt::Formatter<'_>,
+    options: &DisplayOptions<'_>,
+    display: impl FnOnce(&mut dyn fmt::Write) -> fmt::Result,
+) -> fmt::Result {
+    let Some(max_len) = options.max_output_len else {
+        return with_trim(f, options, display);
+    };
+
+    let mut limited = LimitedWriter {
+        inner: f,
+        remaining: max_len,
+        truncated: false,
+    };
+    let res = with_trim(&mut limited, options, display);
+    if limited.truncated {
+        // The error was returned by the writer to stop displaying, not by a Display impl.
+        return limited.inner.write_str("... (output truncated)");
+    }
+    res
+}
+
+fn with_trim(
+    f: &mut dyn fmt::Write,
+    options: &DisplayOptions<'_>,
+    display: impl FnOnce(&mut dyn fmt::Write) -> fmt::Result,
+) -> fmt::Result {
+    if options.trim_trailing_newline {
+        with_wrap(
+            &mut TrimTrailingNewline {
+                inner: f,
+                pending_newline: false,
+            },
+            options,
+            display,
+        )
+    } else {
+        with_wrap(f, options, display)
+    }
+}
+
+fn with_wrap(
+    f: &mut dyn fmt::Write,
+    options: &DisplayOptions<'_>,
+    display: impl FnOnce(&mut dyn fmt::Write) -> fmt::Result,
+) -> fmt::Result {
+    let width = match options.wrap_width {
+        Some(0) | None => return display(f),
+        Some(width) => width,
+    };
+
+    let (single, multi) = options.bullets();
+    let mut wrapper = LineWrapper {
+        inner: f,
+        width,
+        header: options.header().to_string(),
+        bullets: [single, multi],
+        numbered: options.numbered,
+        line: String::new(),
+    };
+    display(&mut wrapper)?;
+    wrapper.finish()
+}
+
+/// A writer that wraps lines longer than a given width.
+///
+/// Output is buffered a line at a time. Continuation lines are indented to line up with the
+/// start of the message: past any leading whitespace and the `- ` or `+ ` marker.
+struct LineWrapper<W> {
+    inner: W,
+    width: usize,
+    // The `Caused by:` header as displayed, which is written as-is.
+    header: String,
+    // The markers that may start a line, followed by a space.
+    bullets: [char; 2],
+    // Whether lines may also start with a numbered marker, such as `1. `.
+    numbered: bool,
+    line: String,
+}
+
+impl<W: fmt::Write> LineWrapper<W> {
+    fn finish(mut self) -> fmt::Result {
+        self.write_line()
+    }
+
+    /// Returns the length of the marker at the start of `line`, including the space after it, or
+    /// 0 if the line doesn't start with a marker.
+    fn marker_len(&self, line: &str) -> usize {
+        let mut chars = line.chars();
+        if let (Some(first), Some(' ')) = (chars.next(), chars.next()) {
+            if self.bullets.contains(&first) {
+                return first.len_utf8() + 1;
+            }
+        }
+        if self.numbered {
+            let digits = line.bytes().take_while(u8::is_ascii_digit).count();
+            if digits > 0 && line[digits..].starts_with(". ") {
+                return digits + 2;
+            }
+        }
+        0
+    }
+
+    fn write_line(&mut self) -> fmt::Result {
+        let line = core::mem::take(&mut self.line);
+        let trimmed = line.trim_start_matches(' ');
+        if line.chars().count() <= self.width || trimmed == self.header {
+            return self.inner.write_str(&line);
+        }
+
+        let prefix_len = line.len() - trimmed.len() + self.marker_len(trimmed);
+        let (prefix, text) = line.split_at(prefix_len);
+        let prefix_width = prefix.chars().count();
+        let indent = " ".repeat(prefix_width);
+        // Always fit at least one character on each line.
+        let available = self.width.saturating_sub(prefix_width).max(1);
+
+        self.inner.write_str(prefix)?;
+        let mut current_len = 0;
+        for word in text.split(' ') {
+            let mut word = word;
+            let mut word_len = word.chars().count();
+            if current_len > 0 && current_len + 1 + word_len > available {
+                write!(self.inner, "\n{indent}")?;
+                current_len = 0;
+            }
+            if current_len > 0 {
+                self.inner.write_char(' ')?;
+                current_len += 1;
+            }
+
+            // Break up words that are too long to fit on a line of their own.
+            while word_len > available {
+                let (end, _) = word
+                    .char_indices()
+                    .nth(available)
+                    .expect("word is long enough");
+                write!(self.inner, "{}\n{indent}", &word[..end])?;
+                word = &word[end..];
+                word_len -= available;
+            }
+            self.inner.write_str(word)?;
+            current_len += word_len;
+        }
+
+        Ok(())
+    }
+}
+
+impl<W: fmt::Write> fmt::Write for LineWrapper<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut lines = s.split('\n');
+        // split always returns at least one item.
+        self.line.push_str(lines.next().unwrap_or_default());
+        for line in lines {
+            self.write_line()?;
+            self.inner.write_char('\n')?;
+            self.line.push_str(line);
+        }
+        Ok(())
+    }
+}
+
+/// A writer that stops once a maximum number of bytes has been written to it.
+///
+/// Once the limit is reached, as much output as fits is written, and an error is returned to stop
+/// displaying.
+struct LimitedWriter<W> {
+    inner: W,
+    remaining: usize,
+    truncated: bool,
+}
+
+impl<W: fmt::Write> fmt::Write for LimitedWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if s.len() <= self.remaining {
+            self.remaining -= s.len();
+            return self.inner.write_str(s);
+        }
+
+        // Write as much as fits without splitting a character.
+        let mut end = self.remaining;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.inner.write_str(&s[..end])?;
+        self.remaining = 0;
+        self.truncated = true;
+        Err(fmt::Error)
+    }
+}
+
+/// A writer that drops the final newline written to it.
+///
+/// A trailing newline is held back until more output arrives, so whatever is pending once
+/// displaying finishes is never written.
+struct TrimTrailingNewline<W> {
+    inner: W,
+    pending_newline: bool,
+}
+
+impl<W: fmt::Write> fmt::Write for TrimTrailingNewline<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if s.is_empty() {
+            return Ok(());
+        }
+        if self.pending_newline {
+            self.inner.write_char('\n')?;
+        }
+        let (s, pending_newline) = match s.strip_suffix('\n') {
+            Some(s) => (s, true),
+            None => (s, false),
+        };
+        self.pending_newline = pending_newline;
+        self.inner.write_str(s)
+    }
+}
+
+/// A node whose message is being displayed.
+#[derive(Clone, Copy)]
+enum Node<'a> {
+    /// The root of an [`ErrorTreeDisplay`], which may not be `'static`.
+    Root(&'a dyn ErrorTree),
+
+    /// Any other node.
+    Source(ErrorTreeSource<'a>),
+}
+
+/// Displays the message for a node, along with any metadata enabled by the options.
+struct NodeMessage<'a, 'f> {
+    node: Node<'a>,
+    depth: usize,
+    options: &'a DisplayOptions<'f>,
+}
+
+impl<'a, 'f> fmt::Display for NodeMessage<'a, 'f> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tree: &dyn ErrorTree = match self.node {
+            Node::Root(tree) => tree,
+            Node::Source(source) => {
+                if let Some(NodeFormatter(formatter)) = self.options.node_formatter {
+                    return f.write_str(&formatter(&source, self.depth));
+                }
+                match source {
+                    #[cfg(feature = "std")]
+                    ErrorTreeSource::Error(error) => return write!(f, "{}", error),
+                    ErrorTreeSource::Tree(tree) => tree,
+                }
+            }
+        };
+
+        write!(f, "{}", tree)?;
+
+        if self.options.show_durations {
+            if let Some(duration) = tree.metadata().and_then(|m| m.duration()) {
+                write!(f, " [{}ms]", duration.as_millis())?;
+            }
         }
+
+        Ok(())
     }
 }
 
@@ -51,138 +892,500 @@ pub(crate) enum DisplayKind {
     Multi,
 }
 
-fn display_tree(f: &mut dyn fmt::Write, tree: &dyn ErrorTree) -> fmt::Result {
-    write!(f, "{}", tree)?;
+/// Displays a tree in the format selected by the options.
+///
+/// `root` is `tree` itself, used to display its message.
+fn display_root<'a>(
+    f: &mut dyn fmt::Write,
+    tree: &'a dyn ErrorTree,
+    root: Node<'_>,
+    options: &DisplayOptions<'_>,
+    ancestors: &mut Ancestors<'a>,
+) -> fmt::Result {
+    match options.outline {
+        Some(bullet) => display_outline(f, tree, root, bullet, options, ancestors),
+        None => display_tree(f, tree, root, options, ancestors),
+    }
+}
+
+fn display_tree<'a>(
+    f: &mut dyn fmt::Write,
+    tree: &'a dyn ErrorTree,
+    root: Node<'_>,
+    options: &DisplayOptions<'_>,
+    ancestors: &mut Ancestors<'a>,
+) -> fmt::Result {
+    let show_root = !options.sources_only && !options.hide_root_message;
+    if show_root {
+        let msg = NodeMessage {
+            node: root,
+            depth: 0,
+            options,
+        };
+        write!(f, "{}", msg)?;
+    }
 
-    let mut sources = tree.sources().peekable();
+    let mut sources = options.sources(tree).peekable();
 
     // The behavior depends on the number of sources:
     let Some(first_source) = sources.next() else {
-        // * With zero sources, we can return early.
+        // * With zero sources, we can return early. End with a newline to match the output for
+        //   trees with sources.
+        if show_root {
+            writeln!(f)?;
+        }
         return Ok(());
     };
 
-    writeln!(f, "\n\nCaused by:\n")?;
+    if show_root {
+        writeln!(f, "\n\n{}\n", options.header())?;
+    } else if !options.sources_only {
+        writeln!(f, "{}\n", options.header())?;
+    }
+
+    if options.elide(0) {
+        let levels = options.levels(options.sources(tree));
+        return writeln!(f, "  {}", Elided { levels });
+    }
 
     if sources.peek().is_none() {
         // * With exactly one source, we can display it as a chain.
-        display_nested_source(f, first_source, DisplayKind::Single)?;
+        match chain_links(first_source, options, ancestors) {
+            Some(links) => display_chain(f, &links, options)?,
+            None => display_nested_source(
+                f,
+                first_source,
+                DisplayKind::Single,
+                1,
+                1,
+                options,
+                ancestors,
+            )?,
+        }
     } else {
         // * With more than one source, we need to display it as a tree.
-        display_nested_source(f, first_source, DisplayKind::Multi)?;
-        for source in sources {
-            display_nested_source(f, source, DisplayKind::Multi)?;
+        let sources = core::iter::once(first_source).chain(sources);
+        for (position, source) in (1..).zip(sources) {
+            display_nested_source(
+                f,
+                source,
+                DisplayKind::Multi,
+                position,
+                1,
+                options,
+                ancestors,
+            )?;
         }
     }
 
     Ok(())
 }
 
-fn display_error(f: &mut dyn fmt::Write, error: &dyn std::error::Error) -> fmt::Result {
-    write!(f, "{}", error)?;
+/// Returns the nodes of the chain starting at `source`, if every one of them has at most one
+/// source to display.
+///
+/// Chains are the most common shape of tree, and [`display_chain`] displays them in a single loop,
+/// which is faster than the general path for deep chains. Returns `None` if the general path is
+/// needed: for trees that branch or loop back, or if sources might be elided.
+fn chain_links<'a>(
+    source: ErrorTreeSource<'a>,
+    options: &DisplayOptions<'_>,
+    ancestors: &Ancestors<'a>,
+) -> Option<Vec<ErrorTreeSource<'a>>> {
+    if options.max_depth.is_some() {
+        return None;
+    }
 
-    let Some(source) = error.source() else {
+    // Rather than checking each node against all of the ones before it, loops within the chain
+    // are found with Brent's algorithm: each node is compared to a single earlier one, which
+    // moves forward to the current node whenever the number of nodes reaches a power of two.
+    let mut earlier = None;
+    let mut links = Vec::new();
+    let mut next = Some(source);
+    while let Some(source) = next {
+        let key = NodeKey::of_source(source);
+        if ancestors.contains(key) || (key.is_some() && key == earlier) {
+            return None;
+        }
+        if links.len().is_power_of_two() {
+            earlier = key;
+        }
+        links.push(source);
+
+        next = match source {
+            #[cfg(feature = "std")]
+            ErrorTreeSource::Error(error) => error.source().map(ErrorTreeSource::Error),
+            ErrorTreeSource::Tree(tree) => {
+                let mut sources = options.sources(tree);
+                let first = sources.next();
+                if sources.next().is_some() {
+                    return None;
+                }
+                first
+            }
+        };
+    }
+
+    Some(links)
+}
+
+/// Displays the nodes of a chain returned by [`chain_links`].
+///
+/// The output is the same as that of [`display_nested_source`] for the first node.
+fn display_chain(
+    mut f: &mut dyn fmt::Write,
+    links: &[ErrorTreeSource<'_>],
+    options: &DisplayOptions<'_>,
+) -> fmt::Result {
+    let marker = options.marker(DisplayKind::Single, 1);
+    for (depth, &source) in (1..).zip(links) {
+        let msg = NodeMessage {
+            node: Node::Source(source),
+            depth,
+            options,
+        };
+        let mut indent = IndentWriter::new_skip_initial(spaces(4), f);
+        writeln!(indent, "  {marker} {msg}")?;
+        f = indent.into_inner();
+    }
+    Ok(())
+}
+
+fn display_outline<'a>(
+    f: &mut dyn fmt::Write,
+    tree: &'a dyn ErrorTree,
+    root: Node<'_>,
+    bullet: char,
+    options: &DisplayOptions<'_>,
+    ancestors: &mut Ancestors<'a>,
+) -> fmt::Result {
+    let mut depth = 1;
+    // Outlines don't have a `Caused by:` header, so hiding the root is the same as only showing
+    // the sources.
+    if !options.sources_only && !options.hide_root_message {
+        let msg = NodeMessage {
+            node: root,
+            depth: 0,
+            options,
+        };
+        write_outline_node(f, bullet, depth, msg, options)?;
+        depth += 1;
+    }
+
+    if options.elide(0) {
+        let levels = options.levels(options.sources(tree));
+        if levels > 0 {
+            write_outline_node(f, bullet, depth, Elided { levels }, options)?;
+        }
         return Ok(());
+    }
+
+    let mut stack: Vec<_> = options
+        .sources(tree)
+        .map(|source| (source, depth))
+        .collect();
+    // Sources are collected in order, so reverse them to pop the first one next.
+    stack.reverse();
+    // `ancestors` starts out with just the root, which is one level above its sources.
+    let root_depth = depth - 1;
+    while let Some((source, depth)) = stack.pop() {
+        ancestors.truncate(depth - root_depth);
+        if !ancestors.push(NodeKey::of_source(source)) {
+            write_outline_node(f, bullet, depth, CYCLE, options)?;
+            continue;
+        }
+
+        let msg = NodeMessage {
+            node: Node::Source(source),
+            // Outline depths start at 1 for the root.
+            depth: depth - 1,
+            options,
+        };
+        write_outline_node(f, bullet, depth, msg, options)?;
+
+        if options.elide(depth - 1) {
+            let levels = match source {
+                #[cfg(feature = "std")]
+                ErrorTreeSource::Error(_) => options.levels(source.sources()),
+                ErrorTreeSource::Tree(tree) => options.levels(options.sources(tree)),
+            };
+            if levels > 0 {
+                write_outline_node(f, bullet, depth + 1, Elided { levels }, options)?;
+            }
+            continue;
+        }
+
+        let len = stack.len();
+        match source {
+            #[cfg(feature = "std")]
+            ErrorTreeSource::Error(error) => {
+                stack.extend(
+                    error
+                        .source()
+                        .map(|source| (ErrorTreeSource::Error(source), depth + 1)),
+                );
+            }
+            ErrorTreeSource::Tree(tree) => {
+                stack.extend(options.sources(tree).map(|source| (source, depth + 1)));
+            }
+        }
+        stack[len..].reverse();
+    }
+
+    Ok(())
+}
+
+fn write_outline_node(
+    f: &mut dyn fmt::Write,
+    bullet: char,
+    depth: usize,
+    msg: impl fmt::Display,
+    options: &DisplayOptions<'_>,
+) -> fmt::Result {
+    let prefix = Styled {
+        value: core::iter::repeat(bullet).take(depth).collect::<String>(),
+        style: options.colored.then_some(BULLET_STYLE),
     };
+    // Continuation lines line up with the start of the message.
+    let continuation = " ".repeat(depth + 1);
+    let mut indent = IndentWriter::new_skip_initial(&continuation, f);
+    writeln!(indent, "{prefix} {msg}")
+}
 
-    writeln!(f, "\n\nCaused by:")?;
+#[cfg(feature = "std")]
+fn display_error(
+    f: &mut dyn fmt::Write,
+    error: &(dyn std::error::Error + 'static),
+    options: &DisplayOptions<'_>,
+) -> fmt::Result {
+    let msg = NodeMessage {
+        node: Node::Source(ErrorTreeSource::Error(error)),
+        depth: 0,
+        options,
+    };
+    write!(f, "{}", msg)?;
 
-    display_nested_error(f, source, DisplayKind::Single)
+    let Some(source) = error.source() else {
+        // End with a newline to match the output for errors with sources.
+        return writeln!(f);
+    };
+
+    writeln!(f, "\n\n{}", options.header())?;
+
+    let mut ancestors = Ancestors::new(NodeKey::of_error(error));
+    display_nested_error(
+        f,
+        source,
+        DisplayKind::Single,
+        1,
+        1,
+        options,
+        &mut ancestors,
+    )
 }
 
-fn display_nested_source(
+/// Displays a source and its descendants, where `position` is the source's position among its
+/// siblings, starting at 1.
+fn display_nested_source<'a>(
     f: &mut dyn fmt::Write,
-    source: ErrorTreeSource<'_>,
+    source: ErrorTreeSource<'a>,
     parent_kind: DisplayKind,
+    position: usize,
+    depth: usize,
+    options: &DisplayOptions<'_>,
+    ancestors: &mut Ancestors<'a>,
 ) -> fmt::Result {
-    match source {
-        ErrorTreeSource::Error(error) => display_nested_error(f, error, parent_kind),
-        ErrorTreeSource::Tree(tree) => display_nested_tree(f, tree, parent_kind),
-    }
+    // The nested display functions add nodes to `ancestors` as they go, so restore it afterwards
+    // for this source's siblings.
+    let len = ancestors.len();
+    let res = match source {
+        #[cfg(feature = "std")]
+        ErrorTreeSource::Error(error) => {
+            display_nested_error(f, error, parent_kind, position, depth, options, ancestors)
+        }
+        ErrorTreeSource::Tree(tree) => {
+            display_nested_tree(f, tree, parent_kind, position, depth, options, ancestors)
+        }
+    };
+    ancestors.truncate(len);
+    res
 }
 
-fn display_nested_tree(
+fn display_nested_tree<'a>(
     mut f: &mut dyn fmt::Write,
-    tree: &dyn ErrorTree,
+    mut tree: &'a (dyn ErrorTree + 'static),
     parent_kind: DisplayKind,
+    position: usize,
+    mut depth: usize,
+    options: &DisplayOptions<'_>,
+    ancestors: &mut Ancestors<'a>,
 ) -> fmt::Result {
-    let mut indent = IndentWriter::new_skip_initial("    ", f);
-    match parent_kind {
-        DisplayKind::Single => {
-            writeln!(indent, "  - {}", tree)?;
-            f = indent.into_inner();
-        }
-        DisplayKind::Multi => {
-            writeln!(indent, "  + {}", tree)?;
-            f = indent.into_inner();
+    let marker = options.marker(parent_kind, position);
+    // The indentation below is laid out for single-column markers, and is widened for numbers.
+    let extra = marker.value.width() - 1;
+
+    // Long chains of trees are common, so rather than recursing for each link in a chain, this
+    // loops over single -> single displays.
+    loop {
+        if !ancestors.push(NodeKey::of_tree(tree)) {
+            return writeln!(f, "  {marker} {CYCLE}");
         }
-    }
 
-    let mut sources = tree.sources().peekable();
+        let msg = NodeMessage {
+            node: Node::Source(ErrorTreeSource::Tree(tree)),
+            depth,
+            options,
+        };
+        let mut indent = IndentWriter::new_skip_initial(spaces(4 + extra), f);
+        writeln!(indent, "  {marker} {msg}")?;
+        f = indent.into_inner();
 
-    // The behavior depends on the number of sources:
-    let Some(first_source) = sources.next() else {
-        // * With zero sources, we can return early.
-        return Ok(());
-    };
+        let mut sources = options.sources(tree).peekable();
 
-    if sources.peek().is_none() {
-        // * With exactly one source, we can display it as a chain.
-        match parent_kind {
-            DisplayKind::Single => {
-                // Single -> single displays can avoid the extra indentation.
-                display_nested_source(f, first_source, DisplayKind::Single)?;
+        // The behavior depends on the number of sources:
+        let Some(first_source) = sources.next() else {
+            // * With zero sources, we can return early.
+            return Ok(());
+        };
+
+        if options.elide(depth) {
+            // Write the marker where the first source would have been displayed.
+            let indent = match (parent_kind, sources.peek().is_none()) {
+                (DisplayKind::Single, true) => spaces(2),
+                (DisplayKind::Multi, true) => spaces(6 + extra),
+                (_, false) => spaces(4 + extra),
+            };
+            let levels = options.levels(options.sources(tree));
+            return writeln!(f, "{indent}{}", Elided { levels });
+        }
+
+        if sources.peek().is_none() {
+            // * With exactly one source, we can display it as a chain.
+            match parent_kind {
+                DisplayKind::Single => {
+                    // Single -> single displays can avoid the extra indentation.
+                    match first_source {
+                        #[cfg(feature = "std")]
+                        ErrorTreeSource::Error(error) => {
+                            return display_nested_error(
+                                f,
+                                error,
+                                DisplayKind::Single,
+                                1,
+                                depth + 1,
+                                options,
+                                ancestors,
+                            );
+                        }
+                        ErrorTreeSource::Tree(next) => {
+                            tree = next;
+                            depth += 1;
+                            continue;
+                        }
+                    }
+                }
+                DisplayKind::Multi => {
+                    // Multi -> single displays need to add an extra indent.
+                    let mut indent = IndentWriter::new(spaces(4 + extra), f);
+                    display_nested_source(
+                        &mut indent,
+                        first_source,
+                        DisplayKind::Single,
+                        1,
+                        depth + 1,
+                        options,
+                        ancestors,
+                    )?;
+                }
             }
-            DisplayKind::Multi => {
-                // Multi -> single displays need to add an extra indent.
-                let mut indent = IndentWriter::new("    ", f);
-                display_nested_source(&mut indent, first_source, DisplayKind::Single)?;
+        } else {
+            // * With more than one source, we need to display it as a tree -- this
+            //   always adds extra indentation.
+            let mut indent = IndentWriter::new(spaces(2 + extra), f);
+            let sources = core::iter::once(first_source).chain(sources);
+            for (position, source) in (1..).zip(sources) {
+                display_nested_source(
+                    &mut indent,
+                    source,
+                    DisplayKind::Multi,
+                    position,
+                    depth + 1,
+                    options,
+                    ancestors,
+                )?;
             }
         }
-    } else {
-        // * With more than one source, we need to display it as a tree -- this
-        //   always adds extra indentation.
-        let mut indent = IndentWriter::new("  ", f);
-        display_nested_source(&mut indent, first_source, DisplayKind::Multi)?;
-        for source in sources {
-            display_nested_source(&mut indent, source, DisplayKind::Multi)?;
-        }
-    }
 
-    Ok(())
+        return Ok(());
+    }
 }
 
-fn display_nested_error(
+#[cfg(feature = "std")]
+fn display_nested_error<'a>(
     mut f: &mut dyn fmt::Write,
-    error: &dyn std::error::Error,
+    error: &'a (dyn std::error::Error + 'static),
     parent_kind: DisplayKind,
+    position: usize,
+    mut depth: usize,
+    options: &DisplayOptions<'_>,
+    ancestors: &mut Ancestors<'a>,
 ) -> fmt::Result {
+    let msg = |error, depth| NodeMessage {
+        node: Node::Source(ErrorTreeSource::Error(error)),
+        depth,
+        options,
+    };
+    let bullet = options.marker(parent_kind, position);
+    if !ancestors.push(NodeKey::of_error(error)) {
+        return writeln!(f, "  {bullet} {CYCLE}");
+    }
+
     match parent_kind {
         DisplayKind::Single => {
             let mut indent = IndentWriter::new_skip_initial("    ", f);
-            writeln!(indent, "  - {}", error)?;
+            writeln!(indent, "  {} {}", bullet, msg(error, depth))?;
             f = indent.into_inner();
 
             let mut next = error.source();
 
             while let Some(source) = next {
+                if options.elide(depth) {
+                    return writeln!(f, "  {}", elided_chain(source));
+                }
+                if !ancestors.push(NodeKey::of_error(source)) {
+                    return writeln!(f, "  {bullet} {CYCLE}");
+                }
+                depth += 1;
                 let mut indent = IndentWriter::new_skip_initial("    ", f);
-                writeln!(indent, "  - {}", source)?;
+                writeln!(indent, "  {} {}", bullet, msg(source, depth))?;
                 next = source.source();
                 f = indent.into_inner();
             }
         }
         DisplayKind::Multi => {
-            let mut indent = IndentWriter::new_skip_initial("    ", f);
-            writeln!(indent, "  + {}", error)?;
+            // The indentation below is laid out for single-column markers, and is widened for
+            // numbers.
+            let extra = bullet.value.width() - 1;
+            let nested = spaces(6 + extra);
+            let single = options.marker(DisplayKind::Single, 1);
+
+            let mut indent = IndentWriter::new_skip_initial(spaces(4 + extra), f);
+            writeln!(indent, "  {} {}", bullet, msg(error, depth))?;
             f = indent.into_inner();
 
             let mut next = error.source();
 
             while let Some(source) = next {
+                if options.elide(depth) {
+                    return writeln!(f, "{nested}{}", elided_chain(source));
+                }
+                if !ancestors.push(NodeKey::of_error(source)) {
+                    return writeln!(f, "{nested}{single} {CYCLE}");
+                }
+                depth += 1;
                 // Add an extra indent to show that this is nested.
-                let mut indent = IndentWriter::new_skip_initial("        ", f);
-                writeln!(indent, "      - {}", source)?;
+                let mut indent = IndentWriter::new_skip_initial(spaces(8 + extra), f);
+                writeln!(indent, "{nested}{single} {}", msg(source, depth))?;
                 next = source.source();
                 f = indent.into_inner();
             }
@@ -191,3 +1394,17 @@ fn display_nested_error(
 
     Ok(())
 }
+
+/// Returns the marker for an elided chain of errors, starting with `error`.
+#[cfg(feature = "std")]
+fn elided_chain(error: &(dyn std::error::Error + 'static)) -> Elided {
+    let mut ancestors = Ancestors::default();
+    Elided {
+        levels: core::iter::successors(Some(error), |error| error.source())
+            .take_while(|error| ancestors.push(NodeKey::of_error(*error)))
+            .count(),
+    }
+}
+
+/// Displayed in place of a node that loops back to one of its ancestors.
+const CYCLE: &str = "(cycle)";
@@ -1,5 +1,18 @@
-use crate::{ErrorTreeDisplay, ErrorTreeSourceDisplay};
-use std::{fmt, sync::Arc};
+use crate::{
+    iter::{Ancestors, NodeKey},
+    Chain, ErrorTreeCompactDisplay, ErrorTreeDisplay, ErrorTreeSourceDisplay, IterBfs, IterDfs,
+    Leaves, NodeMetadata, OwnedErrorTree, WalkWithLayout,
+};
+use alloc::{
+    boxed::Box,
+    collections::BTreeSet,
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec,
+    vec::Vec,
+};
+use core::fmt;
 
 /// An error tree.
 ///
@@ -17,13 +30,32 @@ use std::{fmt, sync::Arc};
 /// * Is object-safe.
 ///
 /// Unlike [`std::error::Error`], this trait requires [`Send`] and [`Sync`] to be implemented.
-pub trait ErrorTree: fmt::Debug + fmt::Display + Send + Sync {
+pub trait ErrorTree: AsDynErrorTree + fmt::Debug + fmt::Display + Send + Sync {
     /// Returns all the lower-level sources of this error.
     ///
     /// This is similar to [`std::error::Error::source`], except it returns an
     /// iterator of all the causes, rather than just one.
     fn sources(&self) -> Box<dyn Iterator<Item = ErrorTreeSource<'_>> + '_>;
 
+    /// Returns the [`std::error::Error`] that this tree is backed by, if any.
+    ///
+    /// Error trees that wrap a standard error, such as [`anyhow::Error`] or a boxed error, can
+    /// return it here so that callers can inspect it, for example by downcasting it. The default
+    /// implementation returns `None`.
+    ///
+    /// This method is only available with the `std` feature.
+    #[cfg(feature = "std")]
+    fn as_error(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+
+    /// Returns metadata attached to this node, if any.
+    ///
+    /// The default implementation returns `None`.
+    fn metadata(&self) -> Option<&NodeMetadata> {
+        None
+    }
+
     /// Converts the error tree into a boxed trait object.
     fn into_boxed(self) -> Box<dyn ErrorTree>
     where
@@ -42,6 +74,17 @@ where
         (**self).sources()
     }
 
+    #[cfg(feature = "std")]
+    #[inline]
+    fn as_error(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        (**self).as_error()
+    }
+
+    #[inline]
+    fn metadata(&self) -> Option<&NodeMetadata> {
+        (**self).metadata()
+    }
+
     fn into_boxed(self) -> Box<dyn ErrorTree>
     where
         T: 'static,
@@ -56,11 +99,35 @@ impl ErrorTree for Box<dyn ErrorTree> {
         (**self).sources()
     }
 
+    #[cfg(feature = "std")]
+    #[inline]
+    fn as_error(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        (**self).as_error()
+    }
+
+    #[inline]
+    fn metadata(&self) -> Option<&NodeMetadata> {
+        (**self).metadata()
+    }
+
     fn into_boxed(self) -> Box<dyn ErrorTree> {
         self
     }
 }
 
+/// Represents a boxed standard error as a chain of errors.
+#[cfg(feature = "std")]
+impl ErrorTree for Box<dyn std::error::Error + Send + Sync + 'static> {
+    fn sources(&self) -> Box<dyn Iterator<Item = ErrorTreeSource<'_>> + '_> {
+        Box::new(self.source().into_iter().map(ErrorTreeSource::Error))
+    }
+
+    #[inline]
+    fn as_error(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&**self)
+    }
+}
+
 impl<T> ErrorTree for Arc<T>
 where
     T: ErrorTree + ?Sized,
@@ -69,8 +136,20 @@ where
     fn sources(&self) -> Box<dyn Iterator<Item = ErrorTreeSource<'_>> + '_> {
         (**self).sources()
     }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn as_error(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        (**self).as_error()
+    }
+
+    #[inline]
+    fn metadata(&self) -> Option<&NodeMetadata> {
+        (**self).metadata()
+    }
 }
 
+#[allow(clippy::needless_lifetimes)]
 impl<'a, T> ErrorTree for &'a T
 where
     T: ErrorTree + ?Sized,
@@ -79,8 +158,20 @@ where
     fn sources(&self) -> Box<dyn Iterator<Item = ErrorTreeSource<'_>> + '_> {
         (**self).sources()
     }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn as_error(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        (**self).as_error()
+    }
+
+    #[inline]
+    fn metadata(&self) -> Option<&NodeMetadata> {
+        (**self).metadata()
+    }
 }
 
+#[allow(clippy::needless_lifetimes)]
 impl<'a, T> ErrorTree for &'a mut T
 where
     T: ErrorTree + ?Sized,
@@ -89,25 +180,364 @@ where
     fn sources(&self) -> Box<dyn Iterator<Item = ErrorTreeSource<'_>> + '_> {
         (**self).sources()
     }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn as_error(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        (**self).as_error()
+    }
+
+    #[inline]
+    fn metadata(&self) -> Option<&NodeMetadata> {
+        (**self).metadata()
+    }
+}
+
+/// Converts an [`ErrorTree`] into a trait object.
+///
+/// This is implemented for every sized `ErrorTree`, and is a supertrait of `ErrorTree` so that
+/// generic code can get a trait object even for `?Sized` trees. It's an implementation detail,
+/// used to identify nodes while detecting cycles.
+#[doc(hidden)]
+pub trait AsDynErrorTree {
+    fn as_dyn_error_tree(&self) -> &(dyn ErrorTree + '_);
+}
+
+impl<T: ErrorTree> AsDynErrorTree for T {
+    #[inline]
+    fn as_dyn_error_tree(&self) -> &(dyn ErrorTree + '_) {
+        self
+    }
 }
 
 /// Extension trait for [`ErrorTree`] to provide additional methods.
+///
+/// Every method of this trait that walks a tree terminates, even if a buggy [`ErrorTree`] impl
+/// returns a source that loops back to one of its ancestors: such a source is visited, but its own
+/// sources aren't. Displays show it as `(cycle)`, iterators yield it without descending into it,
+/// and owned copies and comparisons treat it as a leaf. See
+/// [`ErrorTreeDisplay`'s cycle handling](ErrorTreeDisplay#cycles) for how nodes are identified.
 pub trait ErrorTreeExt: ErrorTree {
     /// Displays the error tree in a tree-like format.
     #[inline]
     fn display_tree(&self) -> ErrorTreeDisplay<'_, Self> {
         ErrorTreeDisplay::new(self)
     }
+
+    /// Displays the error tree on a single line, such as for a structured log field.
+    ///
+    /// See [`ErrorTreeCompactDisplay`] for the format.
+    #[inline]
+    fn display_tree_compact(&self) -> ErrorTreeCompactDisplay<'_, Self> {
+        ErrorTreeCompactDisplay::new(self)
+    }
+
+    /// Returns an estimate of the size, in bytes, of this tree serialized as compact JSON.
+    ///
+    /// This walks the tree, adding up the lengths of messages along with the overhead of each
+    /// node's `{"msg":...,"sources":[...]}` representation (where leaves omit `"sources"`),
+    /// without actually serializing anything. The result is only an estimate: for example, it
+    /// doesn't account for characters that need to be escaped.
+    fn estimated_json_size(&self) -> usize {
+        // The size of a node with an empty message and no sources, which is serialized without
+        // a "sources" field.
+        const NODE_OVERHEAD: usize = r#"{"msg":""}"#.len();
+        // The size of the "sources" field of a node that has sources, excluding the sources
+        // themselves.
+        const SOURCES_OVERHEAD: usize = r#","sources":[]"#.len();
+
+        // Sources are separated by commas.
+        let sources_size = |count: usize| match count {
+            0 => 0,
+            n => SOURCES_OVERHEAD + n - 1,
+        };
+
+        let mut ancestors = Ancestors::new(NodeKey::of_tree(self.as_dyn_error_tree()));
+        let mut stack: Vec<_> = self.sources().map(|source| (1, source)).collect();
+        let mut size = NODE_OVERHEAD + display_len(self) + sources_size(stack.len());
+        while let Some((depth, source)) = stack.pop() {
+            size += NODE_OVERHEAD + display_len(&source);
+            ancestors.truncate(depth);
+            // A source that loops back to an ancestor is counted as a leaf.
+            if ancestors.push(NodeKey::of_source(source)) {
+                let len_before = stack.len();
+                stack.extend(source.sources().map(|source| (depth + 1, source)));
+                size += sources_size(stack.len() - len_before);
+            }
+        }
+
+        size
+    }
+
+    /// Returns the messages shared by this tree and `other` along their root paths.
+    ///
+    /// Starting at the roots, the messages of the two trees are compared level by level, following
+    /// the first source of each node. The result contains each message that was equal in both
+    /// trees, stopping at the first level where the messages differ or where either tree has no
+    /// more sources.
+    ///
+    /// This is useful as a heuristic for grouping errors that only diverge deep down.
+    fn longest_common_root_prefix(&self, other: &dyn ErrorTree) -> Vec<String> {
+        let msg = self.to_string();
+        if msg != other.to_string() {
+            return Vec::new();
+        }
+
+        let mut prefix = vec![msg];
+        for (a, b) in self.chain().zip(other.chain()) {
+            let msg = a.to_string();
+            if msg != b.to_string() {
+                break;
+            }
+            prefix.push(msg);
+        }
+
+        prefix
+    }
+
+    /// Produces an owned copy of this tree with the same structure, but with each message
+    /// computed by `f`.
+    ///
+    /// `f` is called once for each node in depth-first pre-order, with the node and its depth
+    /// (the root has depth 0), and returns the new message for that node.
+    ///
+    /// Sources that are plain [`std::error::Error`]s are stringified into owned nodes before
+    /// being passed to `f`.
+    fn map_tree(&self, mut f: impl FnMut(&dyn ErrorTree, usize) -> String) -> OwnedErrorTree {
+        OwnedErrorTree::build(self.as_dyn_error_tree(), 0, &mut |tree, depth| {
+            (f(tree, depth), None)
+        })
+    }
+
+    /// Produces an owned copy of this tree, with nodes deeper than `max_depth` removed.
+    ///
+    /// The root has depth 0. Each node at depth `max_depth` that has sources keeps its message,
+    /// but its sources are replaced with a single leaf noting how many descendants were dropped,
+    /// for example `... 4 descendants omitted`. All other nodes are copied as-is.
+    fn prune_to_depth(&self, max_depth: usize) -> OwnedErrorTree {
+        OwnedErrorTree::build(self.as_dyn_error_tree(), 0, &mut |tree, depth| {
+            (
+                tree.to_string(),
+                (depth == max_depth).then(|| omitted(tree)),
+            )
+        })
+    }
+
+    /// Returns the distinct messages of the leaves of this tree.
+    ///
+    /// The leaves of a tree are the nodes without any sources, which usually describe root
+    /// causes. Each message is included once, regardless of how many leaves have it. If this tree
+    /// has no sources, the root itself is the only leaf.
+    fn distinct_leaf_messages(&self) -> BTreeSet<String> {
+        if self.sources().next().is_none() {
+            return BTreeSet::from([self.to_string()]);
+        }
+
+        // A source that loops back to an ancestor has sources of its own, so it isn't a leaf.
+        self.iter_dfs()
+            .filter(|(_, source)| source.sources().next().is_none())
+            .map(|(_, source)| source.to_string())
+            .collect()
+    }
+
+    /// Returns true if this tree has the same structure as `other`.
+    ///
+    /// Two trees are structurally equal if their roots display the same message, and they have
+    /// the same number of sources, with each pair of sources (in order) being structurally equal
+    /// in turn. [`Error`](ErrorTreeSource::Error) and [`Tree`](ErrorTreeSource::Tree) sources
+    /// are compared in the same way, by their displayed messages and their sources. The
+    /// [`Debug`](fmt::Debug) output, concrete types and metadata of nodes are ignored. A source
+    /// that loops back to one of its ancestors is compared as a leaf.
+    fn structural_eq(&self, other: &dyn ErrorTree) -> bool {
+        self.to_string() == other.to_string()
+            && sources_eq(
+                (NodeKey::of_tree(self.as_dyn_error_tree()), self.sources()),
+                (NodeKey::of_tree(other), other.sources()),
+            )
+    }
+
+    /// Returns the first source in this tree that matches `pred`.
+    ///
+    /// Sources are visited in depth-first pre-order, which is the order in which they're
+    /// displayed by [`display_tree`](Self::display_tree). The root itself is not a source, so it
+    /// isn't checked. The walk stops as soon as a match is found.
+    fn find(
+        &self,
+        mut pred: impl FnMut(&ErrorTreeSource<'_>) -> bool,
+    ) -> Option<ErrorTreeSource<'_>> {
+        self.iter_dfs()
+            .map(|(_, source)| source)
+            .find(|source| pred(source))
+    }
+
+    /// Applies `f` to each source in this tree, returning the first non-`None` result.
+    ///
+    /// Sources are visited in the same order as [`find`](Self::find): depth-first pre-order,
+    /// as yielded by [`iter_dfs`](Self::iter_dfs), so the result is deterministic. The root
+    /// itself isn't visited, and the walk stops as soon as `f` returns `Some`. Since `f` is
+    /// passed sources that borrow from the tree, it can return references into it, such as a
+    /// downcast error.
+    fn find_map<'a, T, F>(&'a self, mut f: F) -> Option<T>
+    where
+        F: FnMut(ErrorTreeSource<'a>) -> Option<T>,
+    {
+        self.iter_dfs().find_map(|(_, source)| f(source))
+    }
+
+    /// Returns the number of nodes at each depth of this tree.
+    ///
+    /// Index `i` of the returned vector holds the number of nodes at depth `i`. The root is at
+    /// depth 0, so the first element is always 1, and the length of the vector is one more than
+    /// the depth of the deepest node.
+    fn depth_histogram(&self) -> Vec<usize> {
+        let mut histogram = vec![1];
+        // The root's sources are yielded at depth 0, which is depth 1 here.
+        for (depth, _) in self.iter_bfs() {
+            if histogram.len() == depth + 1 {
+                histogram.push(0);
+            }
+            histogram[depth + 1] += 1;
+        }
+
+        histogram
+    }
+
+    /// Returns the depth of this tree: the number of edges on the longest path from the root to
+    /// a leaf.
+    ///
+    /// A tree without any sources has depth 0. The tree is walked with an explicit stack, so
+    /// arbitrarily deep trees don't overflow the call stack.
+    fn depth(&self) -> usize {
+        // The root's sources are yielded at depth 0, one edge away from the root.
+        self.iter_dfs()
+            .map(|(depth, _)| depth + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the total number of nodes in this tree, including the root.
+    ///
+    /// Both error chains and tree branches are counted. Like [`depth`](Self::depth), this walks
+    /// the tree with an explicit stack.
+    fn node_count(&self) -> usize {
+        1 + self.iter_dfs().count()
+    }
+
+    /// Returns an iterator over the sources of this tree in depth-first pre-order, along with
+    /// the layout information needed to draw them.
+    ///
+    /// The root itself isn't yielded, and its direct sources have [depth](crate::LayoutNode::depth) 1.
+    /// This is useful for custom renderers that draw guides between nodes, without having to
+    /// reimplement the traversal.
+    #[inline]
+    fn walk_with_layout(&self) -> WalkWithLayout<'_> {
+        WalkWithLayout::new(self)
+    }
+
+    /// Returns an iterator over the leaves of this tree: the nodes without any sources.
+    ///
+    /// Leaves usually describe the underlying causes of an error. They're yielded in depth-first
+    /// order, which is the order in which they're displayed by
+    /// [`display_tree`](Self::display_tree). If this tree has no sources, the root itself is the
+    /// only leaf.
+    ///
+    /// Since the root may be yielded as an [`ErrorTreeSource`], this requires `Self: 'static`.
+    ///
+    /// Like the other iterators below, this always terminates, even for a tree with a source
+    /// that loops back to one of its ancestors: such sources aren't descended into, so they
+    /// aren't leaves. See [`ErrorTreeDisplay`'s cycle handling](ErrorTreeDisplay#cycles) for
+    /// how nodes are identified.
+    #[inline]
+    fn leaves(&self) -> Leaves<'_>
+    where
+        Self: Sized + 'static,
+    {
+        Leaves::new(ErrorTreeSource::Tree(self))
+    }
+
+    /// Returns an iterator over the sources of this tree in breadth-first order, along with the
+    /// depth of each source.
+    ///
+    /// All sources at one depth are visited before any sources at the next depth. The root
+    /// itself isn't yielded, and its direct sources have depth 0, as with
+    /// [`iter_dfs`](Self::iter_dfs). A source that loops back to one of its ancestors is yielded,
+    /// but its sources aren't.
+    #[inline]
+    fn iter_bfs(&self) -> IterBfs<'_> {
+        IterBfs::new(self)
+    }
+
+    /// Returns an iterator over the sources of this tree in depth-first pre-order, along with
+    /// the depth of each source.
+    ///
+    /// Each source is yielded before its own sources, in the order in which they're displayed by
+    /// [`display_tree`](Self::display_tree). The root itself isn't yielded, and its direct
+    /// sources have depth 0. The iterator is lazy: sources are only requested from each node as
+    /// the walk reaches it. A source that loops back to one of its ancestors is yielded, but its
+    /// sources aren't.
+    ///
+    /// Since the root isn't yielded, depths are counted from its direct sources. This is one less
+    /// than the depths used elsewhere, such as by [`walk_with_layout`](Self::walk_with_layout)
+    /// and [`ErrorTreeDisplay::max_depth`], where the root has depth 0 and its direct sources
+    /// have depth 1.
+    #[inline]
+    fn iter_dfs(&self) -> IterDfs<'_> {
+        IterDfs::new(self)
+    }
+
+    /// Returns an iterator over the chain of first sources of this tree.
+    ///
+    /// This yields the root's first source, followed by that source's first source, and so on,
+    /// in the same way as `anyhow::Error::chain` for a tree with a single chain of sources. The
+    /// root itself isn't yielded. Where a node has multiple sources, only the first one is
+    /// followed, and the others are skipped along with all of their descendants. If the chain
+    /// loops back to an earlier source, the iterator stops after yielding it again.
+    #[inline]
+    fn chain(&self) -> Chain<'_> {
+        Chain::new(self)
+    }
+
+    /// Returns the root cause of this tree: the last source in its [`chain`](Self::chain).
+    ///
+    /// This is like `anyhow::Error::root_cause`. For trees that branch, it's found by following
+    /// the first source of each node until a node without any sources is reached, so other
+    /// branches aren't considered. If this tree has no sources, the root itself is returned,
+    /// which requires `Self: 'static`.
+    fn root_cause(&self) -> ErrorTreeSource<'_>
+    where
+        Self: Sized + 'static,
+    {
+        self.chain().last().unwrap_or(ErrorTreeSource::Tree(self))
+    }
 }
 
 impl<T: ErrorTree + ?Sized> ErrorTreeExt for T {}
 
+/// Returns the sources that replace those of `tree` when it's pruned: a single leaf noting how
+/// many descendants were dropped, if any.
+fn omitted(tree: &dyn ErrorTree) -> Vec<OwnedErrorTree> {
+    match tree.node_count() - 1 {
+        0 => Vec::new(),
+        1 => vec![OwnedErrorTree::from_msg_and_sources(
+            "... 1 descendant omitted",
+            Vec::new(),
+        )],
+        n => vec![OwnedErrorTree::from_msg_and_sources(
+            format!("... {n} descendants omitted"),
+            Vec::new(),
+        )],
+    }
+}
+
 /// The source of an error in an error tree.
 ///
 /// Returned by [`ErrorTree::sources`].
 #[derive(Clone, Copy, Debug)]
 pub enum ErrorTreeSource<'a> {
     /// A [`std::error::Error`] source, representing a chain of errors.
+    ///
+    /// This variant is only available with the `std` feature.
+    #[cfg(feature = "std")]
     Error(&'a (dyn std::error::Error + 'static)),
 
     /// An error tree source.
@@ -115,9 +545,46 @@ pub enum ErrorTreeSource<'a> {
 }
 
 impl<'a> ErrorTreeSource<'a> {
+    /// Returns true if this is an [`Error`](Self::Error) source.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn is_error(self) -> bool {
+        matches!(self, ErrorTreeSource::Error(_))
+    }
+
+    /// Returns true if this is a [`Tree`](Self::Tree) source.
+    #[inline]
+    pub fn is_tree(self) -> bool {
+        matches!(self, ErrorTreeSource::Tree(_))
+    }
+
+    /// Returns the error if this is an [`Error`](Self::Error) source, and `None` otherwise.
+    ///
+    /// This doesn't call [`ErrorTree::as_error`] for [`Tree`](Self::Tree) sources, even if the
+    /// tree is also an error.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn as_error(self) -> Option<&'a (dyn std::error::Error + 'static)> {
+        match self {
+            ErrorTreeSource::Error(error) => Some(error),
+            ErrorTreeSource::Tree(_) => None,
+        }
+    }
+
+    /// Returns the tree if this is a [`Tree`](Self::Tree) source, and `None` otherwise.
+    #[inline]
+    pub fn as_tree(self) -> Option<&'a (dyn ErrorTree + 'static)> {
+        match self {
+            #[cfg(feature = "std")]
+            ErrorTreeSource::Error(_) => None,
+            ErrorTreeSource::Tree(tree) => Some(tree),
+        }
+    }
+
     /// Returns an iterator of the underlying sources.
     pub fn sources(self) -> Box<dyn Iterator<Item = ErrorTreeSource<'a>> + 'a> {
         match self {
+            #[cfg(feature = "std")]
             ErrorTreeSource::Error(error) => {
                 Box::new(error.source().into_iter().map(ErrorTreeSource::Error))
             }
@@ -129,13 +596,103 @@ impl<'a> ErrorTreeSource<'a> {
     pub fn display_tree(self) -> ErrorTreeSourceDisplay<'a> {
         ErrorTreeSourceDisplay::new(self)
     }
+
+    /// Creates an owned copy of this source and all of its descendants by stringifying them.
+    ///
+    /// For an [`Error`](Self::Error) source, the resulting tree is a chain following
+    /// [`std::error::Error::source`].
+    #[inline]
+    pub fn to_owned_tree(self) -> OwnedErrorTree {
+        OwnedErrorTree::from_source(self)
+    }
 }
 
+/// Compares sources by their displayed messages and the structure of their sources.
+///
+/// This is the same comparison as [`ErrorTreeExt::structural_eq`]:
+/// [`Error`](ErrorTreeSource::Error) and [`Tree`](ErrorTreeSource::Tree) sources are equal if they
+/// display the same message and their sources are equal in turn, regardless of their variants or
+/// concrete types. This is mostly useful for assertions in tests.
+impl<'a, 'b> PartialEq<ErrorTreeSource<'b>> for ErrorTreeSource<'a> {
+    fn eq(&self, other: &ErrorTreeSource<'b>) -> bool {
+        self.to_string() == other.to_string()
+            && sources_eq(
+                (NodeKey::of_source(*self), self.sources()),
+                (NodeKey::of_source(*other), other.sources()),
+            )
+    }
+}
+
+impl<'a> Eq for ErrorTreeSource<'a> {}
+
 impl<'a> fmt::Display for ErrorTreeSource<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            #[cfg(feature = "std")]
             ErrorTreeSource::Error(error) => error.fmt(f),
             ErrorTreeSource::Tree(tree) => tree.fmt(f),
         }
     }
 }
+
+type Sources<'a> = Box<dyn Iterator<Item = ErrorTreeSource<'a>> + 'a>;
+
+/// Returns true if the sources of the nodes `left` and `right` are pairwise structurally equal.
+///
+/// Each node is passed as its key along with its sources. A source that loops back to one of its
+/// ancestors is compared as if it had no sources.
+fn sources_eq<'a, 'b>(
+    (left, left_sources): (Option<NodeKey<'a>>, Sources<'a>),
+    (right, right_sources): (Option<NodeKey<'b>>, Sources<'b>),
+) -> bool {
+    fn descend<'a>(ancestors: &mut Ancestors<'a>, source: ErrorTreeSource<'a>) -> Sources<'a> {
+        if ancestors.push(NodeKey::of_source(source)) {
+            source.sources()
+        } else {
+            Box::new(core::iter::empty())
+        }
+    }
+
+    let mut left_ancestors = Ancestors::new(left);
+    let mut right_ancestors = Ancestors::new(right);
+    let mut stack = vec![(left_sources, right_sources)];
+    while let Some((left, right)) = stack.last_mut() {
+        match (left.next(), right.next()) {
+            (Some(left), Some(right)) => {
+                if left.to_string() != right.to_string() {
+                    return false;
+                }
+                // The ancestors of these sources are the nodes whose sources are on the stack.
+                left_ancestors.truncate(stack.len());
+                right_ancestors.truncate(stack.len());
+                stack.push((
+                    descend(&mut left_ancestors, left),
+                    descend(&mut right_ancestors, right),
+                ));
+            }
+            (None, None) => {
+                stack.pop();
+            }
+            // One of the nodes has more sources than the other.
+            _ => return false,
+        }
+    }
+
+    true
+}
+
+/// Returns the length of the `Display` representation of `value`, without allocating.
+fn display_len<D: fmt::Display + ?Sized>(value: &D) -> usize {
+    struct Counter(usize);
+
+    impl fmt::Write for Counter {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0 += s.len();
+            Ok(())
+        }
+    }
+
+    let mut counter = Counter(0);
+    fmt::write(&mut counter, format_args!("{}", value)).expect("Counter never fails");
+    counter.0
+}
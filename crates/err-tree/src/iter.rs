@@ -0,0 +1,269 @@
+use crate::{ErrorTree, ErrorTreeSource};
+use alloc::{boxed::Box, collections::VecDeque, vec, vec::Vec};
+use core::fmt;
+
+// Trees are usually acyclic, but a buggy `ErrorTree` impl could return a source that loops back
+// to one of its ancestors. Each iterator tracks the nodes on the path from the root to the node
+// it's visiting, and doesn't descend into a node that's already on the path, so that iteration
+// always terminates.
+
+/// An iterator over the leaves of an error tree, in depth-first order.
+///
+/// Returned by [`ErrorTreeExt::leaves`](crate::ErrorTreeExt::leaves).
+#[derive(Debug)]
+pub struct Leaves<'a> {
+    // Each node to visit, along with its depth.
+    stack: Vec<(usize, ErrorTreeSource<'a>)>,
+    ancestors: Ancestors<'a>,
+}
+
+impl<'a> Leaves<'a> {
+    pub(crate) fn new(root: ErrorTreeSource<'a>) -> Self {
+        Self {
+            stack: vec![(0, root)],
+            ancestors: Ancestors::default(),
+        }
+    }
+}
+
+impl<'a> Iterator for Leaves<'a> {
+    type Item = ErrorTreeSource<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((depth, node)) = self.stack.pop() {
+            self.ancestors.truncate(depth);
+            if !self.ancestors.push(NodeKey::of_source(node)) {
+                // This node loops back to an ancestor, so it isn't a leaf.
+                continue;
+            }
+
+            let len = self.stack.len();
+            self.stack
+                .extend(node.sources().map(|source| (depth + 1, source)));
+            if self.stack.len() == len {
+                return Some(node);
+            }
+            // Sources were pushed in order, so reverse them to pop the first one next.
+            self.stack[len..].reverse();
+        }
+
+        None
+    }
+}
+
+/// An iterator over the sources of an error tree in breadth-first order, along with their
+/// depths.
+///
+/// Returned by [`ErrorTreeExt::iter_bfs`](crate::ErrorTreeExt::iter_bfs).
+#[derive(Debug)]
+pub struct IterBfs<'a> {
+    // Each source to visit, along with its depth and the index of its parent in `nodes`.
+    queue: VecDeque<(usize, ErrorTreeSource<'a>, usize)>,
+    // Each node whose sources have been queued, along with the index of its parent, so that the
+    // ancestors of a source can be found by walking up from its parent.
+    nodes: Vec<(Option<NodeKey<'a>>, Option<usize>)>,
+}
+
+impl<'a> IterBfs<'a> {
+    pub(crate) fn new<ET: ErrorTree + ?Sized>(tree: &'a ET) -> Self {
+        Self {
+            queue: tree.sources().map(|source| (0, source, 0)).collect(),
+            nodes: vec![(NodeKey::of_tree(tree.as_dyn_error_tree()), None)],
+        }
+    }
+
+    fn is_ancestor(&self, key: Option<NodeKey<'a>>, parent: usize) -> bool {
+        key.is_some()
+            && core::iter::successors(Some(parent), |&index| self.nodes[index].1)
+                .any(|index| self.nodes[index].0 == key)
+    }
+}
+
+impl<'a> Iterator for IterBfs<'a> {
+    type Item = (usize, ErrorTreeSource<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (depth, source, parent) = self.queue.pop_front()?;
+        let key = NodeKey::of_source(source);
+        if !self.is_ancestor(key, parent) {
+            let index = self.nodes.len();
+            self.nodes.push((key, Some(parent)));
+            self.queue
+                .extend(source.sources().map(|source| (depth + 1, source, index)));
+        }
+        Some((depth, source))
+    }
+}
+
+/// An iterator over the sources of an error tree in depth-first pre-order, along with their
+/// depths.
+///
+/// Returned by [`ErrorTreeExt::iter_dfs`](crate::ErrorTreeExt::iter_dfs).
+pub struct IterDfs<'a> {
+    // The sources that are still to be visited at each level, along with their depth.
+    stack: Vec<(usize, Box<dyn Iterator<Item = ErrorTreeSource<'a>> + 'a>)>,
+    // The node whose sources are at each level of `stack`.
+    ancestors: Ancestors<'a>,
+}
+
+impl<'a> IterDfs<'a> {
+    pub(crate) fn new<ET: ErrorTree + ?Sized>(tree: &'a ET) -> Self {
+        Self {
+            stack: vec![(0, tree.sources())],
+            ancestors: Ancestors::new(NodeKey::of_tree(tree.as_dyn_error_tree())),
+        }
+    }
+}
+
+impl<'a> Iterator for IterDfs<'a> {
+    type Item = (usize, ErrorTreeSource<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (depth, sources) = self.stack.last_mut()?;
+            let depth = *depth;
+            match sources.next() {
+                Some(source) => {
+                    // Visit this source's own sources before its siblings, unless it loops back
+                    // to an ancestor.
+                    if self.ancestors.push(NodeKey::of_source(source)) {
+                        self.stack.push((depth + 1, source.sources()));
+                    }
+                    return Some((depth, source));
+                }
+                None => {
+                    self.stack.pop();
+                    self.ancestors.truncate(self.stack.len());
+                }
+            }
+        }
+    }
+}
+
+impl<'a> fmt::Debug for IterDfs<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IterDfs")
+            .field("levels", &self.stack.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// An iterator over the first source of each level of an error tree.
+///
+/// Returned by [`ErrorTreeExt::chain`](crate::ErrorTreeExt::chain).
+#[derive(Debug)]
+pub struct Chain<'a> {
+    next: Option<ErrorTreeSource<'a>>,
+    ancestors: Ancestors<'a>,
+}
+
+impl<'a> Chain<'a> {
+    pub(crate) fn new<ET: ErrorTree + ?Sized>(tree: &'a ET) -> Self {
+        Self {
+            next: tree.sources().next(),
+            ancestors: Ancestors::new(NodeKey::of_tree(tree.as_dyn_error_tree())),
+        }
+    }
+}
+
+impl<'a> Iterator for Chain<'a> {
+    type Item = ErrorTreeSource<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let source = self.next.take()?;
+        // Stop after a source that loops back to an earlier one.
+        if self.ancestors.push(NodeKey::of_source(source)) {
+            self.next = source.sources().next();
+        }
+        Some(source)
+    }
+}
+
+/// Identifies a node of a tree, to detect cycles.
+///
+/// Nodes are compared by their full trait object pointers: both the address of the value and its
+/// vtable. A node and its first field share an address, so comparing addresses alone would treat
+/// an error whose source is its only field as a cycle.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum NodeKey<'a> {
+    Tree(&'a (dyn ErrorTree + 'a)),
+    #[cfg(feature = "std")]
+    Error(&'a (dyn std::error::Error + 'a)),
+}
+
+impl<'a> NodeKey<'a> {
+    pub(crate) fn of_tree(tree: &'a (dyn ErrorTree + 'a)) -> Option<Self> {
+        // The same type can have several vtables, one per place where a reference to it was
+        // turned into a trait object. Going through `as_dyn_error_tree` picks a single one.
+        let tree = tree.as_dyn_error_tree();
+        Self::new(tree, NodeKey::Tree(tree))
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn of_error(error: &'a (dyn std::error::Error + 'a)) -> Option<Self> {
+        Self::new(error, NodeKey::Error(error))
+    }
+
+    pub(crate) fn of_source(source: ErrorTreeSource<'a>) -> Option<Self> {
+        match source {
+            #[cfg(feature = "std")]
+            ErrorTreeSource::Error(error) => Self::of_error(error),
+            ErrorTreeSource::Tree(tree) => Self::of_tree(tree),
+        }
+    }
+
+    fn new<T: ?Sized>(node: &T, key: Self) -> Option<Self> {
+        // Zero-sized values don't have unique addresses, so they can't be identified.
+        (core::mem::size_of_val(node) > 0).then_some(key)
+    }
+}
+
+impl<'a> PartialEq for NodeKey<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (NodeKey::Tree(a), NodeKey::Tree(b)) => core::ptr::eq(*a, *b),
+            #[cfg(feature = "std")]
+            (NodeKey::Error(a), NodeKey::Error(b)) => core::ptr::eq(*a, *b),
+            #[cfg(feature = "std")]
+            _ => false,
+        }
+    }
+}
+
+/// The nodes on the path from the root of a tree to the node that's currently being visited.
+#[derive(Debug, Default)]
+pub(crate) struct Ancestors<'a> {
+    keys: Vec<Option<NodeKey<'a>>>,
+}
+
+impl<'a> Ancestors<'a> {
+    pub(crate) fn new(root: Option<NodeKey<'a>>) -> Self {
+        Self { keys: vec![root] }
+    }
+
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    #[inline]
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.keys.truncate(len);
+    }
+
+    /// Returns true if the node is on the path.
+    pub(crate) fn contains(&self, key: Option<NodeKey<'a>>) -> bool {
+        key.is_some() && self.keys.contains(&key)
+    }
+
+    /// Adds a node to the end of the path.
+    ///
+    /// Returns false, without adding it, if the node is already on the path.
+    pub(crate) fn push(&mut self, key: Option<NodeKey<'a>>) -> bool {
+        if self.contains(key) {
+            return false;
+        }
+        self.keys.push(key);
+        true
+    }
+}
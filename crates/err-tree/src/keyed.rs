@@ -0,0 +1,125 @@
+use crate::{ErrorTree, ErrorTreeSource};
+use alloc::{boxed::Box, collections::BTreeMap, string::String, vec::Vec};
+use core::fmt;
+
+/// An [`ErrorTree`] of errors keyed by, for example, the name of the field they apply to.
+///
+/// This is a common shape for validation errors. Each entry in the map becomes a source with the
+/// message `"{key}: {error}"`, and sources are yielded in key order so that the output is
+/// deterministic.
+///
+/// This type is only available with the `std` feature.
+///
+/// ```
+/// use err_tree::{ErrorTreeExt, KeyedErrors};
+/// use std::collections::BTreeMap;
+///
+/// let errors = BTreeMap::from([
+///     ("port", "invalid digit found in string".parse::<u16>().unwrap_err()),
+///     ("count", "".parse::<u16>().unwrap_err()),
+/// ]);
+/// let tree = KeyedErrors::new("invalid request", errors);
+/// assert_eq!(
+///     tree.display_tree().to_string(),
+///     "invalid request\n\
+///      \n\
+///      Caused by:\n\n  \
+///        + count: cannot parse integer from empty string\n  \
+///        + port: invalid digit found in string\n",
+/// );
+/// ```
+#[derive(Debug)]
+pub struct KeyedErrors<K, E> {
+    msg: String,
+    entries: Vec<KeyedError<K, E>>,
+}
+
+impl<K, E> KeyedErrors<K, E> {
+    /// Creates a new tree with the given message, and each entry of `errors` as a source.
+    pub fn new(msg: impl Into<String>, errors: BTreeMap<K, E>) -> Self {
+        Self {
+            msg: msg.into(),
+            entries: errors
+                .into_iter()
+                .map(|(key, error)| KeyedError { key, error })
+                .collect(),
+        }
+    }
+
+    /// Returns the number of keyed errors.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if there are no keyed errors.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Consumes this tree, returning the keyed errors.
+    pub fn into_inner(self) -> BTreeMap<K, E>
+    where
+        K: Ord,
+    {
+        self.entries
+            .into_iter()
+            .map(|entry| (entry.key, entry.error))
+            .collect()
+    }
+}
+
+impl<K, E> fmt::Display for KeyedErrors<K, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.msg)
+    }
+}
+
+impl<K, E> ErrorTree for KeyedErrors<K, E>
+where
+    K: fmt::Debug + fmt::Display + Send + Sync + 'static,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    fn sources(&self) -> Box<dyn Iterator<Item = ErrorTreeSource<'_>> + '_> {
+        Box::new(
+            self.entries
+                .iter()
+                .map(|entry| ErrorTreeSource::Tree(entry)),
+        )
+    }
+}
+
+/// A single source of a [`KeyedErrors`] tree.
+#[derive(Debug)]
+struct KeyedError<K, E> {
+    key: K,
+    error: E,
+}
+
+impl<K, E> fmt::Display for KeyedError<K, E>
+where
+    K: fmt::Display,
+    E: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.key, self.error)
+    }
+}
+
+impl<K, E> ErrorTree for KeyedError<K, E>
+where
+    K: fmt::Debug + fmt::Display + Send + Sync + 'static,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    fn sources(&self) -> Box<dyn Iterator<Item = ErrorTreeSource<'_>> + '_> {
+        // The error's own message is already part of this node's message, so continue with its
+        // source.
+        Box::new(self.error.source().into_iter().map(ErrorTreeSource::Error))
+    }
+
+    #[inline]
+    fn as_error(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{
+    iter::{Ancestors, NodeKey},
+    ErrorTree, ErrorTreeSource,
+};
+use alloc::vec::Vec;
+
+/// A node yielded by [`ErrorTreeExt::walk_with_layout`](crate::ErrorTreeExt::walk_with_layout),
+/// along with the information needed to draw it as part of a tree.
+#[derive(Clone, Debug)]
+pub struct LayoutNode<'a> {
+    /// The source at this position in the tree.
+    pub source: ErrorTreeSource<'a>,
+
+    /// The depth of this node. The sources of the root have depth 1.
+    pub depth: usize,
+
+    /// True if this node is the last of its parent's sources.
+    pub is_last_sibling: bool,
+
+    /// For each ancestor of this node, excluding the root, whether that ancestor is the last of
+    /// its parent's sources.
+    ///
+    /// The first element corresponds to the ancestor at depth 1, and the vector has `depth - 1`
+    /// elements. A renderer should draw a continuation line (such as `│`) at each level where the
+    /// value is false, since more siblings follow at that level.
+    pub ancestors_last: Vec<bool>,
+}
+
+/// An iterator over the sources of an error tree in depth-first pre-order, with layout
+/// information.
+///
+/// Returned by [`ErrorTreeExt::walk_with_layout`](crate::ErrorTreeExt::walk_with_layout).
+#[derive(Debug)]
+pub struct WalkWithLayout<'a> {
+    stack: Vec<LayoutNode<'a>>,
+    // The nodes on the path to the node that was last yielded, used to stop at cycles.
+    ancestors: Ancestors<'a>,
+}
+
+impl<'a> WalkWithLayout<'a> {
+    pub(crate) fn new<ET: ErrorTree + ?Sized>(tree: &'a ET) -> Self {
+        let mut walk = Self {
+            stack: Vec::new(),
+            ancestors: Ancestors::new(NodeKey::of_tree(tree.as_dyn_error_tree())),
+        };
+        walk.push_sources(tree.sources(), 1, Vec::new());
+        walk
+    }
+
+    fn push_sources(
+        &mut self,
+        sources: impl Iterator<Item = ErrorTreeSource<'a>>,
+        depth: usize,
+        ancestors_last: Vec<bool>,
+    ) {
+        let sources: Vec<_> = sources.collect();
+        let len = sources.len();
+        // Push sources in reverse order so that they're popped in order.
+        for (i, source) in sources.into_iter().enumerate().rev() {
+            self.stack.push(LayoutNode {
+                source,
+                depth,
+                is_last_sibling: i + 1 == len,
+                ancestors_last: ancestors_last.clone(),
+            });
+        }
+    }
+}
+
+impl<'a> Iterator for WalkWithLayout<'a> {
+    type Item = LayoutNode<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.ancestors.truncate(node.depth);
+        // A source that loops back to an ancestor is yielded, but not descended into.
+        if self.ancestors.push(NodeKey::of_source(node.source)) {
+            let mut ancestors_last = node.ancestors_last.clone();
+            ancestors_last.push(node.is_last_sibling);
+            self.push_sources(node.source.sources(), node.depth + 1, ancestors_last);
+        }
+        Some(node)
+    }
+}
@@ -1,9 +1,58 @@
+//! Error trees: errors with any number of sources.
+//!
+//! # Optional features
+//!
+//! * `std`: integrates with [`std::error::Error`], adding the `Error` variant of
+//!   [`ErrorTreeSource`] along with the types and impls that are built on it. Enabled by default.
+//!   Without it, this crate is `no_std` and only requires `alloc`, and trees only have
+//!   other trees as sources.
+//! * `anyhow-compat`: implements [`ErrorTree`] for `anyhow::Error`. Implies `std`.
+//! * `miette`: adds `MietteErrorTree`, which implements `miette::Diagnostic` for error trees.
+//!   Implies `std`.
+
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
 #[cfg(feature = "anyhow-compat")]
 mod anyhow_impl;
+#[cfg(feature = "std")]
 mod compat;
 mod display;
 mod error_tree;
+mod iter;
+#[cfg(feature = "std")]
+mod keyed;
+mod layout;
+mod macros;
+mod metadata;
+#[cfg(feature = "miette")]
+mod miette_impl;
+mod owned;
+#[cfg(feature = "std")]
+mod poison;
+pub mod testing;
 
+#[cfg(feature = "std")]
 pub use compat::*;
 pub use display::*;
 pub use error_tree::*;
+pub use iter::*;
+#[cfg(feature = "std")]
+pub use keyed::*;
+pub use layout::*;
+pub use metadata::*;
+#[cfg(feature = "miette")]
+pub use miette_impl::*;
+pub use owned::*;
+#[cfg(feature = "std")]
+pub use poison::*;
+
+// Re-exports used by macros, so that they work in `no_std` crates.
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::{boxed::Box, vec, vec::Vec};
+    pub use core::{iter, panic};
+}
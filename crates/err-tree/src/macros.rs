@@ -0,0 +1,50 @@
+/// Builds the return value of [`ErrorTree::sources`](crate::ErrorTree::sources) from a list of
+/// fields.
+///
+/// Each source is written as `tree: <expr>` or `error: <expr>`, where the expression is a
+/// reference to an [`ErrorTree`](crate::ErrorTree) or a [`std::error::Error`] respectively
+/// (`error:` sources require the `std` feature).
+/// Sources are yielded in the order they're listed. This removes most of the boilerplate from
+/// hand-written `ErrorTree` impls, without needing a derive macro:
+///
+/// ```
+/// use err_tree::{sources_from, ErrorTree, ErrorTreeSource, OwnedErrorTree};
+/// use std::fmt;
+///
+/// #[derive(Debug)]
+/// struct DeployError {
+///     build: OwnedErrorTree,
+///     upload: std::io::Error,
+/// }
+///
+/// impl fmt::Display for DeployError {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         f.write_str("deploy failed")
+///     }
+/// }
+///
+/// impl ErrorTree for DeployError {
+///     fn sources(&self) -> Box<dyn Iterator<Item = ErrorTreeSource<'_>> + '_> {
+///         sources_from!(tree: &self.build, error: &self.upload)
+///     }
+/// }
+/// ```
+///
+/// With no sources, `sources_from!()` returns an empty iterator.
+#[macro_export]
+macro_rules! sources_from {
+    (@source tree $source:expr) => {
+        $crate::ErrorTreeSource::Tree($source)
+    };
+    (@source error $source:expr) => {
+        $crate::ErrorTreeSource::Error($source)
+    };
+    () => {
+        $crate::__private::Box::new($crate::__private::iter::empty())
+    };
+    ($($kind:ident: $source:expr),+ $(,)?) => {
+        $crate::__private::Box::new(
+            [$($crate::sources_from!(@source $kind $source)),+].into_iter(),
+        )
+    };
+}
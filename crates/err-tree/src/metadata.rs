@@ -0,0 +1,88 @@
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::time::SystemTime;
+
+/// Metadata attached to a single node in an error tree.
+///
+/// Returned by [`ErrorTree::metadata`](crate::ErrorTree::metadata). Unlike the sources of a
+/// node, metadata isn't part of the tree's structure; it is extra information that displayers and
+/// serializers can opt into showing.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct NodeMetadata {
+    duration: Option<Duration>,
+    #[cfg(feature = "std")]
+    timestamp: Option<SystemTime>,
+    severity: Option<Severity>,
+}
+
+impl NodeMetadata {
+    /// Creates new, empty metadata.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how long the operation represented by this node took before it failed.
+    #[inline]
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Returns how long the operation represented by this node took before it failed, if
+    /// recorded.
+    #[inline]
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    /// Sets the time at which the operation represented by this node failed.
+    ///
+    /// This method is only available with the `std` feature.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn with_timestamp(mut self, timestamp: SystemTime) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Returns the time at which the operation represented by this node failed, if recorded.
+    ///
+    /// This method is only available with the `std` feature.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn timestamp(&self) -> Option<SystemTime> {
+        self.timestamp
+    }
+
+    /// Sets how severe the error represented by this node is.
+    #[inline]
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = Some(severity);
+        self
+    }
+
+    /// Returns how severe the error represented by this node is, if recorded.
+    #[inline]
+    pub fn severity(&self) -> Option<Severity> {
+        self.severity
+    }
+}
+
+/// How severe an error is.
+///
+/// Severities are ordered from least to most severe, so `Severity::Info < Severity::Fatal`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Severity {
+    /// Informational: the failure is expected or harmless.
+    Info,
+
+    /// The failure was recovered from, but may indicate a problem.
+    Warning,
+
+    /// The operation failed.
+    Error,
+
+    /// The failure is unrecoverable.
+    Fatal,
+}
@@ -0,0 +1,150 @@
+use crate::{
+    iter::{Ancestors, NodeKey},
+    ErrorTree, ErrorTreeSource,
+};
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+use miette::Diagnostic;
+use std::{error, fmt};
+
+/// Wraps an [`ErrorTree`] to implement [`miette::Diagnostic`] on it.
+///
+/// Each source of the tree becomes a [related](Diagnostic::related) diagnostic, recursively, so a
+/// branch with several sources has several related diagnostics. The sources are captured as
+/// messages when the wrapper is created, so the errors in them can't be downcast. A source that
+/// loops back to one of its ancestors is captured without its sources.
+///
+/// This type is only available with the `miette` feature.
+///
+/// ```
+/// use err_tree::{MietteErrorTree, OwnedErrorTree};
+/// use miette::Diagnostic;
+///
+/// let tree = OwnedErrorTree::from_msg_and_sources(
+///     "outer",
+///     vec![
+///         OwnedErrorTree::from_msg_and_sources("first", vec![]),
+///         OwnedErrorTree::from_msg_and_sources("second", vec![]),
+///     ],
+/// );
+/// let diagnostic = MietteErrorTree::new(tree);
+/// let related: Vec<_> = diagnostic.related().unwrap().map(|d| d.to_string()).collect();
+/// assert_eq!(related, ["first", "second"]);
+/// ```
+pub struct MietteErrorTree<E> {
+    inner: E,
+    // Diagnostic::related must return references, so the sources are computed when the wrapper
+    // is created.
+    related: Vec<RelatedNode>,
+}
+
+impl<E: ErrorTree> MietteErrorTree<E> {
+    /// Create a new diagnostic wrapper.
+    pub fn new(tree: E) -> Self {
+        let mut ancestors = Ancestors::new(NodeKey::of_tree(&tree));
+        let related = RelatedNode::new_all(tree.sources(), &mut ancestors);
+        MietteErrorTree {
+            inner: tree,
+            related,
+        }
+    }
+
+    /// Get the wrapped error tree.
+    #[inline]
+    pub fn into_inner(self) -> E {
+        self.inner
+    }
+
+    /// Access the wrapped error tree.
+    #[inline]
+    pub fn as_inner(&self) -> &E {
+        &self.inner
+    }
+}
+
+impl<E: ErrorTree> From<E> for MietteErrorTree<E> {
+    fn from(tree: E) -> Self {
+        MietteErrorTree::new(tree)
+    }
+}
+
+impl<E: ErrorTree> fmt::Debug for MietteErrorTree<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.inner, f)
+    }
+}
+
+impl<E: ErrorTree> fmt::Display for MietteErrorTree<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.inner, f)
+    }
+}
+
+// The sources are exposed through `related` rather than `source`, so that miette shows every
+// branch rather than just the first one.
+impl<E: ErrorTree> error::Error for MietteErrorTree<E> {}
+
+impl<E: ErrorTree> Diagnostic for MietteErrorTree<E> {
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        related(&self.related)
+    }
+}
+
+/// A source of a [`MietteErrorTree`], along with its own sources.
+#[derive(Debug)]
+struct RelatedNode {
+    msg: String,
+    related: Vec<RelatedNode>,
+}
+
+impl RelatedNode {
+    /// Captures each of `sources` along with their own sources.
+    ///
+    /// A source that loops back to one of its ancestors is captured without its sources.
+    fn new_all<'a>(
+        sources: impl Iterator<Item = ErrorTreeSource<'a>>,
+        ancestors: &mut Ancestors<'a>,
+    ) -> Vec<Self> {
+        let len = ancestors.len();
+        sources
+            .map(|source| {
+                let related = if ancestors.push(NodeKey::of_source(source)) {
+                    let related = Self::new_all(source.sources(), ancestors);
+                    ancestors.truncate(len);
+                    related
+                } else {
+                    Vec::new()
+                };
+                RelatedNode {
+                    msg: source.to_string(),
+                    related,
+                }
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for RelatedNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.msg)
+    }
+}
+
+impl error::Error for RelatedNode {}
+
+impl Diagnostic for RelatedNode {
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        related(&self.related)
+    }
+}
+
+fn related(nodes: &[RelatedNode]) -> Option<Box<dyn Iterator<Item = &dyn Diagnostic> + '_>> {
+    if nodes.is_empty() {
+        None
+    } else {
+        Some(Box::new(nodes.iter().map(|node| node as &dyn Diagnostic)))
+    }
+}
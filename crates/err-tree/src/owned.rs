@@ -0,0 +1,170 @@
+use crate::{
+    iter::{Ancestors, NodeKey},
+    ErrorTree, ErrorTreeSource,
+};
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt;
+
+/// An error tree that owns its messages and sources.
+///
+/// This is a simple, fully stringified representation of an error tree, produced by
+/// transformations such as [`ErrorTreeExt::map_tree`](crate::ErrorTreeExt::map_tree).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct OwnedErrorTree {
+    /// The message for this node in the error tree.
+    pub msg: String,
+
+    /// The sources of this node.
+    pub sources: Vec<OwnedErrorTree>,
+}
+
+impl OwnedErrorTree {
+    /// Creates a new [`OwnedErrorTree`] from an arbitrary error tree, by stringifying each of its
+    /// messages.
+    ///
+    /// A source that loops back to one of its ancestors is copied as a leaf.
+    pub fn new<ET: ErrorTree + ?Sized>(tree: &ET) -> Self {
+        Self::build(tree.as_dyn_error_tree(), 0, &mut |tree, _| {
+            (tree.to_string(), None)
+        })
+    }
+
+    /// Creates a new error tree with the given message and sources.
+    pub fn from_msg_and_sources(msg: impl Into<String>, sources: Vec<OwnedErrorTree>) -> Self {
+        Self {
+            msg: msg.into(),
+            sources,
+        }
+    }
+
+    /// Creates a new [`OwnedErrorTree`] from an error and its chain of sources.
+    ///
+    /// If the chain loops back to an earlier error, the copy ends with that error as a leaf.
+    #[cfg(feature = "std")]
+    pub fn from_error(error: &dyn std::error::Error) -> Self {
+        let mut messages = Vec::new();
+        let mut ancestors = Ancestors::default();
+        let mut next = Some(error);
+        while let Some(error) = next {
+            messages.push(error.to_string());
+            next = match ancestors.push(NodeKey::of_error(error)) {
+                true => error.source(),
+                false => None,
+            };
+        }
+
+        // Build the chain from its end, so that each error is built before the one it causes.
+        let mut tree = Self::from_msg_and_sources(messages.pop().unwrap_or_default(), Vec::new());
+        while let Some(msg) = messages.pop() {
+            tree = Self::from_msg_and_sources(msg, vec![tree]);
+        }
+        tree
+    }
+
+    pub(crate) fn from_source(source: ErrorTreeSource<'_>) -> Self {
+        match source {
+            #[cfg(feature = "std")]
+            ErrorTreeSource::Error(error) => Self::from_error(error),
+            ErrorTreeSource::Tree(tree) => Self::new(tree),
+        }
+    }
+
+    /// Builds an owned copy of `tree`, whose root is at `depth`.
+    ///
+    /// `visit` is called for each node in depth-first pre-order, with the node and its depth. It
+    /// returns the message for the copy of the node, along with the sources to replace the
+    /// node's own sources with, or `None` to copy its sources in turn. Sources that are plain
+    /// errors are stringified with [`Self::from_error`] before being visited, and a source that
+    /// loops back to one of its ancestors is copied as a leaf.
+    ///
+    /// The tree is walked with an explicit stack, so arbitrarily deep trees don't overflow the
+    /// call stack.
+    pub(crate) fn build(tree: &dyn ErrorTree, depth: usize, visit: &mut VisitFn<'_>) -> Self {
+        struct Level<'a> {
+            msg: String,
+            depth: usize,
+            // The sources that have been copied so far, and those that are still to be copied.
+            copied: Vec<OwnedErrorTree>,
+            remaining: Box<dyn Iterator<Item = ErrorTreeSource<'a>> + 'a>,
+        }
+
+        let (msg, sources) = visit(tree, depth);
+        if let Some(sources) = sources {
+            return Self::from_msg_and_sources(msg, sources);
+        }
+
+        let mut stack = vec![Level {
+            msg,
+            depth,
+            copied: Vec::new(),
+            remaining: tree.sources(),
+        }];
+        let mut ancestors = Ancestors::new(NodeKey::of_tree(tree));
+        loop {
+            let level = stack
+                .last_mut()
+                .expect("the root is returned before the stack empties");
+            let depth = level.depth + 1;
+            let copy = match level.remaining.next() {
+                #[cfg(feature = "std")]
+                Some(ErrorTreeSource::Error(error)) => {
+                    // Errors don't have any error trees as sources, so this only nests once.
+                    Self::build(&Self::from_error(error), depth, visit)
+                }
+                Some(ErrorTreeSource::Tree(tree)) => match visit(tree, depth) {
+                    (msg, Some(sources)) => Self::from_msg_and_sources(msg, sources),
+                    (msg, None) if ancestors.push(NodeKey::of_tree(tree)) => {
+                        stack.push(Level {
+                            msg,
+                            depth,
+                            copied: Vec::new(),
+                            remaining: tree.sources(),
+                        });
+                        continue;
+                    }
+                    (msg, None) => Self::from_msg_and_sources(msg, Vec::new()),
+                },
+                None => {
+                    let level = stack.pop().expect("stack is non-empty");
+                    ancestors.truncate(stack.len());
+                    let copy = Self::from_msg_and_sources(level.msg, level.copied);
+                    if stack.is_empty() {
+                        return copy;
+                    }
+                    copy
+                }
+            };
+            stack
+                .last_mut()
+                .expect("stack is non-empty")
+                .copied
+                .push(copy);
+        }
+    }
+}
+
+/// Called by [`OwnedErrorTree::build`] for each node, returning its message and the sources to
+/// replace its own with, if any.
+pub(crate) type VisitFn<'v> =
+    dyn FnMut(&dyn ErrorTree, usize) -> (String, Option<Vec<OwnedErrorTree>>) + 'v;
+
+impl fmt::Display for OwnedErrorTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.msg)
+    }
+}
+
+impl ErrorTree for OwnedErrorTree {
+    fn sources(&self) -> Box<dyn Iterator<Item = ErrorTreeSource<'_>> + '_> {
+        Box::new(
+            self.sources
+                .iter()
+                .map(|source| ErrorTreeSource::Tree(source)),
+        )
+    }
+}
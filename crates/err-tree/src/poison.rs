@@ -0,0 +1,75 @@
+use crate::{ErrorTree, ErrorTreeSource};
+use alloc::{boxed::Box, string::String};
+use std::{
+    fmt,
+    sync::{PoisonError, TryLockError},
+};
+
+/// An owned, leaf [`ErrorTree`] representing an error from acquiring a lock.
+///
+/// [`PoisonError`] and [`TryLockError`] hold on to the lock guard, which borrows from the lock
+/// and so isn't `'static`. `PoisonTree` captures just a message, making it easy to pass lock
+/// errors into other error trees (for example, with `Mishap::from_error_tree`).
+///
+/// The data protected by the lock, and the guard itself, are not preserved.
+///
+/// This type is only available with the `std` feature.
+///
+/// ```
+/// use err_tree::PoisonTree;
+/// use std::sync::Mutex;
+///
+/// let mutex = Mutex::new(0);
+/// std::thread::scope(|s| {
+///     let handle = s.spawn(|| {
+///         let _guard = mutex.lock().unwrap();
+///         panic!("poisoning the mutex");
+///     });
+///     assert!(handle.join().is_err());
+/// });
+///
+/// let error = mutex.lock().map_err(PoisonTree::from).unwrap_err();
+/// assert_eq!(error.to_string(), "lock poisoned");
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct PoisonTree {
+    msg: String,
+}
+
+impl PoisonTree {
+    /// Creates a new `PoisonTree` with a custom message, such as `"mutex poisoned"`.
+    #[inline]
+    pub fn new(msg: impl Into<String>) -> Self {
+        Self { msg: msg.into() }
+    }
+}
+
+/// Converts a `PoisonError` into a tree with the message `"lock poisoned"`.
+impl<T> From<PoisonError<T>> for PoisonTree {
+    fn from(_error: PoisonError<T>) -> Self {
+        Self::new("lock poisoned")
+    }
+}
+
+/// Converts a `TryLockError` into a tree with the message `"lock poisoned"` or
+/// `"lock would block"`.
+impl<T> From<TryLockError<T>> for PoisonTree {
+    fn from(error: TryLockError<T>) -> Self {
+        match error {
+            TryLockError::Poisoned(error) => error.into(),
+            TryLockError::WouldBlock => Self::new("lock would block"),
+        }
+    }
+}
+
+impl fmt::Display for PoisonTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.msg)
+    }
+}
+
+impl ErrorTree for PoisonTree {
+    fn sources(&self) -> Box<dyn Iterator<Item = ErrorTreeSource<'_>> + '_> {
+        Box::new(std::iter::empty())
+    }
+}
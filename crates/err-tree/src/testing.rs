@@ -0,0 +1,82 @@
+//! Helpers for testing code that produces error trees.
+
+pub use crate::__assert_tree as assert_tree;
+
+/// Asserts that an error tree has the given messages and structure.
+///
+/// The expected tree is written as a message, optionally followed by `=>` and a braced,
+/// comma-separated list of sources, each of which is written the same way:
+///
+/// ```
+/// use err_tree::{testing::assert_tree, OwnedErrorTree};
+///
+/// let tree = OwnedErrorTree::from_msg_and_sources(
+///     "outer",
+///     vec![
+///         OwnedErrorTree::from_msg_and_sources("child 1", vec![]),
+///         OwnedErrorTree::from_msg_and_sources(
+///             "child 2",
+///             vec![OwnedErrorTree::from_msg_and_sources("grandchild", vec![])],
+///         ),
+///     ],
+/// );
+///
+/// assert_tree!(tree, "outer" => { "child 1", "child 2" => { "grandchild" } });
+/// ```
+///
+/// Messages can be any expression that converts into a `String`. Each message is compared
+/// against the [`Display`](std::fmt::Display) output of the corresponding node.
+///
+/// # Panics
+///
+/// Panics if the tree doesn't match, showing both trees as displayed by
+/// [`display_tree`](crate::ErrorTreeExt::display_tree).
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __assert_tree {
+    ($actual:expr, $($expected:tt)+) => {{
+        let actual = $crate::OwnedErrorTree::new(&$actual);
+        let expected = $crate::__assert_tree_node!($($expected)+);
+        if actual != expected {
+            $crate::__private::panic!(
+                "error tree does not match\n\nexpected:\n{}\nactual:\n{}",
+                $crate::ErrorTreeExt::display_tree(&expected),
+                $crate::ErrorTreeExt::display_tree(&actual),
+            );
+        }
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __assert_tree_node {
+    ($msg:expr => { $($sources:tt)* }) => {
+        $crate::OwnedErrorTree::from_msg_and_sources(
+            $msg,
+            $crate::__assert_tree_sources!([] $($sources)*),
+        )
+    };
+    ($msg:expr) => {
+        $crate::OwnedErrorTree::from_msg_and_sources($msg, $crate::__private::Vec::new())
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __assert_tree_sources {
+    ([$($out:expr),*]) => {
+        $crate::__private::vec![$($out),*]
+    };
+    ([$($out:expr),*] $msg:expr => { $($sources:tt)* } $(, $($rest:tt)*)?) => {
+        $crate::__assert_tree_sources!(
+            [$($out,)* $crate::__assert_tree_node!($msg => { $($sources)* })]
+            $($($rest)*)?
+        )
+    };
+    ([$($out:expr),*] $msg:expr $(, $($rest:tt)*)?) => {
+        $crate::__assert_tree_sources!(
+            [$($out,)* $crate::__assert_tree_node!($msg)]
+            $($($rest)*)?
+        )
+    };
+}
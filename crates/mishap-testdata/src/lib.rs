@@ -28,9 +28,13 @@ pub fn complex() -> Mishap {
 }
 
 pub fn single_source() -> Mishap {
+    Mishap::from_msg_and_error_tree("mishap2 line1\nmishap2 line2", single_source_inner())
+}
+
+/// The only source of [`single_source`], which is backed by an anyhow chain.
+pub fn single_source_inner() -> Mishap {
     let error = anyhow!("anyhow error");
     let error2 = error.context("anyhow error2");
     let error3 = error2.context("anyhow error3");
-    let mishap1 = Mishap::from_msg_and_anyhow("mishap1 line1\nmishap1 line2", error3);
-    Mishap::from_msg_and_error_tree("mishap2 line1\nmishap2 line2", mishap1)
+    Mishap::from_msg_and_anyhow("mishap1 line1\nmishap1 line2", error3)
 }
@@ -0,0 +1,147 @@
+use crate::Mishap;
+use std::fmt;
+
+/// Collects errors over the course of an operation, and turns them into a single [`Mishap`] at the
+/// end.
+///
+/// A `Mishap` always represents an error, so an accumulator starts out empty and only produces a
+/// `Mishap` once at least one error has been pushed into it.
+///
+/// ```
+/// use mishap::{ErrorAccumulator, Mishap};
+///
+/// let mut acc = ErrorAccumulator::new("failed to process items");
+/// for item in ["1", "2", "three"] {
+///     acc.push_result(item.parse::<u32>().map_err(Mishap::from_error));
+/// }
+///
+/// let error = acc.into_result().unwrap_err();
+/// assert_eq!(error.to_string(), "failed to process items");
+/// ```
+#[must_use = "an `ErrorAccumulator` should be finalized with `into_result` or `into_mishap`"]
+pub struct ErrorAccumulator<D> {
+    msg: D,
+    errors: Vec<Mishap>,
+}
+
+impl<D> ErrorAccumulator<D>
+where
+    D: fmt::Display + Send + Sync + 'static,
+{
+    /// Creates a new, empty accumulator.
+    ///
+    /// If any errors are pushed, `msg` becomes the message of the resulting `Mishap`, with each
+    /// error as a source.
+    pub fn new(msg: D) -> Self {
+        Self {
+            msg,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Adds an error to the accumulator.
+    pub fn push(&mut self, error: Mishap) {
+        self.errors.push(error);
+    }
+
+    /// Adds the error to the accumulator if `result` is an `Err`, and returns the value
+    /// otherwise.
+    pub fn push_result<T>(&mut self, result: Result<T, Mishap>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.push(error);
+                None
+            }
+        }
+    }
+
+    /// Returns true if no errors have been pushed.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Returns the number of errors pushed so far.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Finalizes the accumulator, returning `None` if no errors were pushed.
+    pub fn into_mishap(self) -> Option<Mishap> {
+        if self.errors.is_empty() {
+            None
+        } else {
+            Some(Mishap::from_msg_and_error_trees(self.msg, self.errors))
+        }
+    }
+
+    /// Finalizes the accumulator, returning `Ok(())` if no errors were pushed.
+    pub fn into_result(self) -> Result<(), Mishap> {
+        match self.into_mishap() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<D> fmt::Debug for ErrorAccumulator<D>
+where
+    D: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ErrorAccumulator")
+            .field("msg", &self.msg.to_string())
+            .field("errors", &self.errors)
+            .finish()
+    }
+}
+
+/// Collects every result from `iter`, gathering all the errors rather than stopping at the first
+/// one.
+///
+/// If there were no errors, returns all the successful values in order. Otherwise, returns a
+/// `Mishap` with the message `"N errors occurred"` and each error as a source, and the values are
+/// discarded.
+///
+/// ```
+/// use mishap::{try_collect_errors, Mishap};
+///
+/// let results = ["1", "2", "three", "four"]
+///     .into_iter()
+///     .map(|item| item.parse::<u32>().map_err(Mishap::from_error));
+/// let error = try_collect_errors(results).unwrap_err();
+/// assert_eq!(error.to_string(), "2 errors occurred");
+/// ```
+pub fn try_collect_errors<T, I>(iter: I) -> Result<Vec<T>, Mishap>
+where
+    I: IntoIterator<Item = Result<T, Mishap>>,
+{
+    let mut values = Vec::new();
+    let mut errors = Vec::new();
+    for result in iter {
+        match result {
+            Ok(value) => values.push(value),
+            Err(error) => errors.push(error),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(values)
+    } else {
+        Err(Mishap::from_msg_and_error_trees(
+            errors_occurred(errors.len()),
+            errors,
+        ))
+    }
+}
+
+/// Returns the message used when multiple errors are collected together.
+pub(crate) fn errors_occurred(n: usize) -> String {
+    if n == 1 {
+        "1 error occurred".to_owned()
+    } else {
+        format!("{n} errors occurred")
+    }
+}
@@ -0,0 +1,103 @@
+use std::{
+    cell::Cell,
+    fmt,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+// usize::MAX means that there is no limit.
+static DEBUG_NODE_LIMIT: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+thread_local! {
+    // The number of nodes that can still be written by the alternate Debug output currently being
+    // formatted on this thread, or None if no such output is being formatted.
+    static REMAINING_NODES: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// Sets the maximum number of nodes written by the alternate `Debug` output (`{:#?}`) of a
+/// [`Mishap`](crate::Mishap).
+///
+/// Once the limit is reached, any remaining sources are replaced with a single
+/// `... (truncated)` marker. This keeps the output for very large trees, for example in panic
+/// messages, to a manageable size. Passing `None` removes the limit, which is the default.
+///
+/// `Debug` formatting doesn't accept any configuration, so this limit is global to the process.
+/// The regular `Debug` output (`{:?}`) and `Display` output are not affected.
+pub fn set_debug_node_limit(limit: Option<usize>) {
+    DEBUG_NODE_LIMIT.store(limit.unwrap_or(usize::MAX), Ordering::Relaxed);
+}
+
+/// Returns the limit set by [`set_debug_node_limit`], if any.
+pub fn debug_node_limit() -> Option<usize> {
+    match DEBUG_NODE_LIMIT.load(Ordering::Relaxed) {
+        usize::MAX => None,
+        limit => Some(limit),
+    }
+}
+
+/// Tracks the number of nodes written by alternate Debug output.
+///
+/// The outermost `Mishap` being formatted enters a budget, and nested `Mishap`s share it.
+pub(crate) struct DebugBudget {
+    entered: bool,
+}
+
+impl DebugBudget {
+    pub(crate) fn enter() -> Self {
+        let entered = REMAINING_NODES.with(|remaining| {
+            if remaining.get().is_some() {
+                return false;
+            }
+            // The root node is always written.
+            let limit = DEBUG_NODE_LIMIT.load(Ordering::Relaxed);
+            remaining.set(Some(limit.saturating_sub(1)));
+            true
+        });
+        Self { entered }
+    }
+}
+
+impl Drop for DebugBudget {
+    fn drop(&mut self) {
+        if self.entered {
+            REMAINING_NODES.with(|remaining| remaining.set(None));
+        }
+    }
+}
+
+/// Takes one node from the current budget, returning false if the budget is exhausted.
+fn take_node() -> bool {
+    REMAINING_NODES.with(|remaining| match remaining.get() {
+        Some(0) => false,
+        Some(n) => {
+            remaining.set(Some(n - 1));
+            true
+        }
+        // Not within alternate Debug output.
+        None => true,
+    })
+}
+
+/// Writes a list of sources, stopping once the budget is exhausted.
+pub(crate) struct DebugSources<'a, E>(pub(crate) &'a [E]);
+
+impl<'a, E: fmt::Debug> fmt::Debug for DebugSources<'a, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut list = f.debug_list();
+        for source in self.0 {
+            if !take_node() {
+                list.entry(&Truncated);
+                break;
+            }
+            list.entry(source);
+        }
+        list.finish()
+    }
+}
+
+struct Truncated;
+
+impl fmt::Debug for Truncated {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("... (truncated)")
+    }
+}
@@ -0,0 +1,35 @@
+use crate::Mishap;
+use serde_json::error::Category;
+
+impl Mishap {
+    /// Creates a `Mishap` from a [`serde_json::Error`], with a message that includes where the
+    /// error occurred.
+    ///
+    /// For errors that occurred while parsing input, the message includes the line, the column
+    /// and the [category](serde_json::Error::classify) of the error, for example
+    /// `JSON parse error at 4:12 (data)`. Other errors don't have a position, so only the category
+    /// is included.
+    ///
+    /// The original error is kept as the source of the returned `Mishap`, so it can still be
+    /// retrieved (for example, through [`Mishap::chain_errors`]) and inspected.
+    pub fn from_serde_json(error: serde_json::Error) -> Self {
+        let category = match error.classify() {
+            Category::Io => "io",
+            Category::Syntax => "syntax",
+            Category::Data => "data",
+            Category::Eof => "eof",
+        };
+
+        // serde_json uses a line of 0 for errors that don't have a position.
+        let msg = if error.line() == 0 {
+            format!("JSON error ({category})")
+        } else {
+            format!(
+                "JSON parse error at {}:{} ({category})",
+                error.line(),
+                error.column(),
+            )
+        };
+        Self::from_msg_and_error(msg, error)
+    }
+}
@@ -29,10 +29,25 @@
 //!
 //! TODO: continue this documentation.
 
+mod accumulator;
+mod debug;
+#[cfg(feature = "serde_json")]
+mod json;
+mod macros;
 mod mishap;
+mod retry;
+#[cfg(feature = "futures")]
+mod stream;
+#[cfg(feature = "tracing")]
+mod trace;
 mod wrapped;
 
+pub use accumulator::*;
+pub use debug::{debug_node_limit, set_debug_node_limit};
 pub use mishap::*;
+pub use retry::*;
+#[cfg(feature = "futures")]
+pub use stream::*;
 pub use wrapped::*;
 
 /// A type alias for `Result<T, Mishap>`.
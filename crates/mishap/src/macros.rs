@@ -0,0 +1,102 @@
+/// Constructs a [`Mishap`](crate::Mishap), in the same way as [`anyhow::anyhow!`].
+///
+/// The constructor that runs is chosen from the form of the arguments:
+///
+/// * A string literal, optionally followed by format arguments, is formatted with
+///   [`format!`] and passed to [`Mishap::from_msg`](crate::Mishap::from_msg). This includes
+///   literals with implicit named arguments, such as `mishap!("{path} not found")`.
+/// * Any other single expression is passed to
+///   [`Mishap::from_error`](crate::Mishap::from_error), so it must implement
+///   [`std::error::Error`]. To use a `String` or other displayable value as the message, write
+///   `mishap!("{}", value)` instead.
+///
+/// ```
+/// use mishap::mishap;
+///
+/// let path = "config.toml";
+/// let error = mishap!("{path} not found");
+/// assert_eq!(error.to_string(), "config.toml not found");
+///
+/// let error = mishap!("expected {} items, found {}", 3, 2);
+/// assert_eq!(error.to_string(), "expected 3 items, found 2");
+/// ```
+///
+/// ```
+/// use mishap::mishap;
+///
+/// let parse_error = "x".parse::<u32>().unwrap_err();
+/// let error = mishap!(parse_error);
+/// assert_eq!(error.to_string(), "invalid digit found in string");
+/// assert!(error.downcast_ref::<std::num::ParseIntError>().is_some());
+/// ```
+#[macro_export]
+macro_rules! mishap {
+    ($msg:literal $(,)?) => {
+        $crate::Mishap::from_msg(::std::format!($msg))
+    };
+    ($error:expr $(,)?) => {
+        $crate::Mishap::from_error($error)
+    };
+    ($fmt:expr, $($arg:tt)*) => {
+        $crate::Mishap::from_msg(::std::format!($fmt, $($arg)*))
+    };
+}
+
+/// Returns early with a [`Mishap`](crate::Mishap) built from a format string.
+///
+/// `bail!(...)` is equivalent to `return Err(Mishap::from_msg(format!(...)))`.
+///
+/// ```
+/// use mishap::{bail, Result};
+///
+/// fn check_port(port: u16) -> Result<u16> {
+///     if port < 1024 {
+///         bail!("port {port} is reserved");
+///     }
+///     Ok(port)
+/// }
+///
+/// assert_eq!(check_port(8080).unwrap(), 8080);
+/// assert_eq!(check_port(80).unwrap_err().to_string(), "port 80 is reserved");
+/// ```
+#[macro_export]
+macro_rules! bail {
+    ($($arg:tt)+) => {
+        return ::std::result::Result::Err($crate::Mishap::from_msg(::std::format!($($arg)+)))
+    };
+}
+
+/// Returns early with a [`Mishap`](crate::Mishap) if a condition isn't satisfied.
+///
+/// `ensure!(cond, ...)` is equivalent to `if !cond { bail!(...) }`. The message is only formatted
+/// if the condition is false.
+///
+/// ```
+/// use mishap::{ensure, Result};
+/// use std::cell::Cell;
+///
+/// fn check_len(items: &[u32], formatted: &Cell<usize>) -> Result<()> {
+///     let describe = || {
+///         formatted.set(formatted.get() + 1);
+///         format!("{:?}", items)
+///     };
+///     ensure!(items.len() <= 2, "too many items: {}", describe());
+///     Ok(())
+/// }
+///
+/// let formatted = Cell::new(0);
+/// check_len(&[1, 2], &formatted).unwrap();
+/// assert_eq!(formatted.get(), 0, "message isn't formatted on success");
+///
+/// let error = check_len(&[1, 2, 3], &formatted).unwrap_err();
+/// assert_eq!(error.to_string(), "too many items: [1, 2, 3]");
+/// assert_eq!(formatted.get(), 1);
+/// ```
+#[macro_export]
+macro_rules! ensure {
+    ($cond:expr, $($arg:tt)+) => {
+        if !$cond {
+            $crate::bail!($($arg)+);
+        }
+    };
+}
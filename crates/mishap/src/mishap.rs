@@ -1,7 +1,18 @@
-use crate::WrappedTree;
+use crate::{debug::DebugBudget, WrappedTree};
 use anyhow::anyhow;
-use err_tree::{ErrorTree, ErrorTreeExt, ErrorTreeSource};
-use std::fmt;
+use err_tree::{
+    ErrorTree, ErrorTreeDisplay, ErrorTreeExt, ErrorTreeSource, NodeMetadata, Severity,
+};
+use indent_write::fmt::IndentWriter;
+#[cfg(feature = "location")]
+use std::panic::Location;
+use std::{
+    any::Any,
+    fmt::{self, Write},
+    time::{Duration, SystemTime},
+};
+#[cfg(feature = "uuid")]
+use uuid::Uuid;
 
 /// A generic tree of errors, where each error can have any number of sources.
 ///
@@ -17,107 +28,220 @@ pub struct Mishap {
     //
     // TODO: it would be nice to use something like anyhow's custom vtables for
     // less pointer-chasing.
-    kind: Box<TreeImpl>,
+    inner: Box<MishapInner>,
 }
 
 impl Mishap {
+    #[cfg_attr(feature = "location", track_caller)]
+    fn new(kind: TreeImpl) -> Self {
+        Self::with_meta(kind, Meta::capture())
+    }
+
+    #[cfg_attr(feature = "location", track_caller)]
+    fn with_meta(kind: TreeImpl, meta: Meta) -> Self {
+        Self {
+            inner: Box::new(MishapInner {
+                kind,
+                #[cfg(all(feature = "serde-err-tree", feature = "uuid"))]
+                extra: [("error_id".to_owned(), meta.id.to_string().into())].into(),
+                meta,
+                node: NodeMetadata::default(),
+                #[cfg(feature = "location")]
+                location: Location::caller(),
+            }),
+        }
+    }
+
+    #[cfg_attr(feature = "location", track_caller)]
     pub fn from_msg<D>(msg: D) -> Self
     where
         D: fmt::Debug + fmt::Display + Send + Sync + 'static,
     {
-        Self {
-            kind: TreeImpl::new_chain(anyhow!(msg)),
-        }
+        Self::new(TreeImpl::new_chain(anyhow!(msg)))
     }
 
+    #[cfg_attr(feature = "location", track_caller)]
     pub fn from_anyhow(error: anyhow::Error) -> Self {
-        Self {
-            kind: TreeImpl::new_chain(error),
-        }
+        Self::new(TreeImpl::new_chain(error))
     }
 
+    #[cfg_attr(feature = "location", track_caller)]
     pub fn from_msg_and_anyhow<D>(msg: D, error: anyhow::Error) -> Self
     where
         D: fmt::Display + Send + Sync + 'static,
     {
-        Self {
-            kind: TreeImpl::new_chain(error.context(msg)),
-        }
+        Self::new(TreeImpl::new_chain(error.context(msg)))
     }
 
+    #[cfg_attr(feature = "location", track_caller)]
     pub fn from_msg_and_anyhows<D, I>(msg: D, sources: I) -> Self
     where
         D: fmt::Display + Send + Sync + 'static,
         I: IntoIterator<Item = anyhow::Error>,
     {
-        Self {
-            kind: TreeImpl::new_wrapped_tree(msg, sources),
-        }
+        Self::new(TreeImpl::new_wrapped_tree(msg, sources))
     }
 
+    #[cfg_attr(feature = "location", track_caller)]
     pub fn from_error<E>(error: E) -> Self
     where
         E: std::error::Error + Send + Sync + 'static,
     {
-        Self {
-            kind: TreeImpl::new_chain(anyhow!(error)),
-        }
+        Self::new(TreeImpl::new_chain(anyhow!(error)))
     }
 
+    #[cfg_attr(feature = "location", track_caller)]
     pub fn from_msg_and_error<D, E>(msg: D, error: E) -> Self
     where
         D: fmt::Display + Send + Sync + 'static,
         E: std::error::Error + Send + Sync + 'static,
     {
-        Self {
-            kind: TreeImpl::new_chain(anyhow!(error).context(msg)),
-        }
+        Self::new(TreeImpl::new_chain(anyhow!(error).context(msg)))
     }
 
+    #[cfg_attr(feature = "location", track_caller)]
     pub fn from_msg_and_errors<D, I, E>(msg: D, sources: I) -> Self
     where
         D: fmt::Display + Send + Sync + 'static,
         I: IntoIterator<Item = E>,
         E: std::error::Error + Send + Sync + 'static,
     {
-        Self {
-            kind: TreeImpl::new_wrapped_tree(msg, sources.into_iter().map(|e| anyhow!(e))),
-        }
+        Self::new(TreeImpl::new_wrapped_tree(
+            msg,
+            sources.into_iter().map(|e| anyhow!(e)),
+        ))
     }
 
+    #[cfg_attr(feature = "location", track_caller)]
     pub fn from_error_tree<ET>(tree: ET) -> Self
     where
         ET: ErrorTree + 'static,
     {
-        Self {
-            kind: TreeImpl::new_tree(tree),
-        }
+        let meta = Meta::of(&tree);
+        // The tree becomes this node, so its metadata is carried over as well.
+        let node = tree.metadata().cloned().unwrap_or_default();
+        let mut mishap = Self::with_meta(TreeImpl::new_tree(tree), meta);
+        mishap.inner.node = node;
+        mishap
     }
 
+    #[cfg_attr(feature = "location", track_caller)]
     pub fn from_msg_and_error_tree<D, ET>(msg: D, tree: ET) -> Self
     where
         D: fmt::Display + Send + Sync + 'static,
         ET: ErrorTree + 'static,
     {
-        Self {
-            kind: TreeImpl::new_wrapped_tree(msg, [tree]),
-        }
+        let meta = Meta::of(&tree);
+        Self::with_meta(TreeImpl::new_wrapped_tree(msg, [tree]), meta)
     }
 
+    #[cfg_attr(feature = "location", track_caller)]
     pub fn from_msg_and_error_trees<D, I, ET>(msg: D, sources: I) -> Self
     where
         D: fmt::Display + Send + Sync + 'static,
         I: IntoIterator<Item = ET>,
         ET: ErrorTree + 'static,
     {
-        Self {
-            kind: TreeImpl::new_wrapped_tree(msg, sources),
+        Self::new(TreeImpl::new_wrapped_tree(msg, sources))
+    }
+
+    /// Creates a tree with a message and a list of `Mishap`s as sources.
+    ///
+    /// This is like [`Self::from_msg_and_error_trees`], but specialized to `Mishap` sources so
+    /// that the source type doesn't need to be inferred.
+    ///
+    /// * With no sources, this creates a leaf with just the message.
+    /// * With exactly one source, this is equivalent to [`Self::wrap_mishap`], and the source's
+    ///   help text and id are preserved.
+    #[cfg_attr(feature = "location", track_caller)]
+    pub fn from_mishaps<D, I>(msg: D, mishaps: I) -> Self
+    where
+        D: fmt::Display + Send + Sync + 'static,
+        I: IntoIterator<Item = Mishap>,
+    {
+        let mut mishaps: Vec<_> = mishaps.into_iter().collect();
+        if mishaps.len() == 1 {
+            let mishap = mishaps.pop().expect("exactly one mishap");
+            return mishap.wrap_mishap(msg);
         }
+        Self::from_msg_and_error_trees(msg, mishaps)
+    }
+
+    /// Merges independent `Mishap`s, such as the errors from a set of parallel tasks, under a new
+    /// root with the given message.
+    ///
+    /// Each `Mishap` becomes a source of the root, with its full subtree preserved. If `mishaps` is
+    /// empty, the result is a leaf with just the message.
+    ///
+    /// A single `Mishap` still produces a new root with it as the only source. This differs from
+    /// [`Self::from_mishaps`], which treats a single source as [`Self::wrap_mishap`] and carries
+    /// its help text and id over to the root: here, the root never inherits them from its
+    /// sources.
+    #[cfg_attr(feature = "location", track_caller)]
+    pub fn collect<D, I>(msg: D, mishaps: I) -> Self
+    where
+        D: fmt::Display + Send + Sync + 'static,
+        I: IntoIterator<Item = Mishap>,
+    {
+        Self::from_msg_and_error_trees(msg, mishaps)
+    }
+
+    /// Creates a node with a message and the given `Mishap`s as its children.
+    ///
+    /// This is a single entry point for building trees programmatically. The result is always a
+    /// new node with `msg` as its message:
+    ///
+    /// * With no children, it's a leaf.
+    /// * With one child, it's a new node wrapping that child, and the child's help text and id
+    ///   aren't carried over to it. This differs from the single-child collapse in
+    ///   `new_wrapped_tree`, which [`Self::from_mishaps`] and [`Self::wrap_mishap`] rely on: there,
+    ///   the wrapping node takes over the child's help text and id, as if it were the same error.
+    /// * With several children, each of them is a source of the new node, in order.
+    #[cfg_attr(feature = "location", track_caller)]
+    pub fn from_parts<D>(msg: D, children: Vec<Mishap>) -> Self
+    where
+        D: fmt::Display + Send + Sync + 'static,
+    {
+        Self::from_msg_and_error_trees(msg, children)
+    }
+
+    /// Creates a tree with both error chains and error trees as sources.
+    ///
+    /// The sources are, in order, each of the `anyhows` followed by each of the `trees`.
+    #[cfg_attr(feature = "location", track_caller)]
+    pub fn from_msg_with_mixed_sources<D, I, J>(msg: D, anyhows: I, trees: J) -> Self
+    where
+        D: fmt::Display + Send + Sync + 'static,
+        I: IntoIterator<Item = anyhow::Error>,
+        J: IntoIterator<Item = Box<dyn ErrorTree>>,
+    {
+        let sources = anyhows.into_iter().map(ErrorTree::into_boxed).chain(trees);
+        Self::new(TreeImpl::new_wrapped_tree(msg, sources))
+    }
+
+    /// Creates a tree from a list of `(field, message)` pairs, such as the errors from validating a
+    /// form.
+    ///
+    /// The root has the message `"validation failed"`, and each pair becomes a leaf with the
+    /// message `"{field}: {message}"`, in the order they're yielded. If `errors` is empty, the
+    /// result is a leaf with just the root message, as with [`Self::from_msg_and_error_trees`].
+    #[cfg_attr(feature = "location", track_caller)]
+    pub fn from_field_errors<I>(errors: I) -> Self
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        Self::from_msg_and_error_trees(
+            "validation failed",
+            errors
+                .into_iter()
+                .map(|(field, message)| Self::from_msg(format!("{field}: {message}"))),
+        )
     }
 
     /// Constructs a tree from a borrowed error, effectively cloning it by stringifying it.
     ///
     /// This doesn't currently preserve `Debug` information.
+    #[cfg_attr(feature = "location", track_caller)]
     pub fn from_borrowed_error(error: &dyn std::error::Error) -> Self {
         let mut chain = vec![error];
 
@@ -133,28 +257,79 @@ impl Mishap {
             next = error;
         }
 
-        Self {
-            kind: TreeImpl::new_chain(next),
-        }
+        Self::new(TreeImpl::new_chain(next))
     }
 
     /// Constructs a tree from a borrowed tree, effectively cloning it by stringifying it.
     ///
     /// This doesn't currently preserve `Debug` information.
+    #[cfg_attr(feature = "location", track_caller)]
     pub fn from_borrowed_tree(tree: &dyn ErrorTree) -> Self {
         // Construct a tree by stringifying the tree of errors.
         let sources = tree.sources().map(|source| match source {
             ErrorTreeSource::Error(error) => Self::from_borrowed_error(error),
             ErrorTreeSource::Tree(tree) => Self::from_borrowed_tree(tree),
         });
-        Self {
-            kind: TreeImpl::new_wrapped_tree(tree.to_string(), sources),
+        Self::new(TreeImpl::new_wrapped_tree(tree.to_string(), sources))
+    }
+
+    /// Creates a deep copy of this `Mishap` by stringifying it.
+    ///
+    /// `Mishap` isn't `Clone`, since the errors it wraps might not be. This rebuilds the tree with
+    /// [`Self::from_borrowed_tree`] instead, so the copy has the same messages and structure and
+    /// displays identically, but `Debug` information is lost and the errors in it can't be
+    /// downcast. The copy keeps this `Mishap`'s help text, id, location and
+    /// [`metadata`](ErrorTree::metadata), though the metadata of nested `Mishap`s isn't preserved.
+    pub fn clone_stringified(&self) -> Mishap {
+        let mut clone = Self::from_borrowed_tree(self);
+        clone.inner.meta = self.inner.meta.clone();
+        clone.inner.node = self.inner.node.clone();
+        #[cfg(feature = "location")]
+        {
+            clone.inner.location = self.inner.location;
         }
+        clone
+    }
+
+    /// Splits off the first source of this tree.
+    ///
+    /// Returns the root, with all of its sources except the first, along with the first source
+    /// if there is one. This is useful for handling one cause at a time, for example in a loop
+    /// that calls this method until no sources are left.
+    ///
+    /// The remaining sources keep their original order. Like [`Self::from_borrowed_tree`], both
+    /// the root and the sources are rebuilt by stringifying them, so `Debug` information isn't
+    /// preserved. The root keeps this `Mishap`'s help text, id, location and
+    /// [`metadata`](ErrorTree::metadata).
+    ///
+    /// If this tree has no sources, it's returned unchanged along with `None`.
+    pub fn pop_first_source(self) -> (Mishap, Option<Mishap>) {
+        let mut sources = self.sources().map(|source| match source {
+            ErrorTreeSource::Error(error) => Self::from_borrowed_error(error),
+            ErrorTreeSource::Tree(tree) => Self::from_borrowed_tree(tree),
+        });
+        let Some(first) = sources.next() else {
+            drop(sources);
+            return (self, None);
+        };
+        let rest: Vec<_> = sources.collect();
+
+        let mut root = Self::with_meta(
+            TreeImpl::new_wrapped_tree(self.to_string(), rest),
+            self.inner.meta.clone(),
+        );
+        root.inner.node = self.inner.node.clone();
+        #[cfg(feature = "location")]
+        {
+            root.inner.location = self.inner.location;
+        }
+        (root, Some(first))
     }
 
     /// Create a tree from a message and a chain of causes.
     ///
     /// The iterator represents a chain of causes rather than siblings.
+    #[cfg_attr(feature = "location", track_caller)]
     pub fn from_msg_and_cause_chain<I, D>(msg: D, cause_chain: I) -> Self
     where
         I: DoubleEndedIterator<Item = D>,
@@ -169,19 +344,282 @@ impl Mishap {
             next = Some(error);
         }
 
-        Self {
-            kind: TreeImpl::new_chain(next.unwrap()),
-        }
+        Self::new(TreeImpl::new_chain(next.unwrap()))
     }
 
+    /// Create a chain from a slice of messages.
+    ///
+    /// `messages[0]` is the outermost context, and the last message is the root cause. Each
+    /// message is stringified.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `messages` is empty, since a `Mishap` must have at least one message.
+    #[cfg_attr(feature = "location", track_caller)]
+    pub fn from_messages<D>(messages: &[D]) -> Self
+    where
+        D: fmt::Display,
+    {
+        let (msg, cause_chain) = messages
+            .split_first()
+            .expect("Mishap::from_messages requires at least one message");
+        Self::from_msg_and_cause_chain(
+            msg.to_string(),
+            cause_chain.iter().map(|cause| cause.to_string()),
+        )
+    }
+
+    #[cfg_attr(feature = "location", track_caller)]
     pub fn wrap_mishap<D>(self, msg: D) -> Self
     where
         D: fmt::Display + Send + Sync + 'static,
     {
-        Self {
-            kind: TreeImpl::new_wrapped_tree(msg, [self]),
+        let meta = self.inner.meta.clone();
+        Self::with_meta(TreeImpl::new_wrapped_tree(msg, [self]), meta)
+    }
+
+    /// Attaches help text to this `Mishap`, describing how the error might be fixed.
+    ///
+    /// Help text is not part of the tree itself, and isn't shown by the `Display` impl or by
+    /// [`display_tree`](ErrorTreeExt::display_tree). Use [`Self::display_report`] to show it.
+    ///
+    /// Help text is preserved when this `Mishap` is wrapped with another message. Calling this
+    /// method again replaces any existing help text.
+    pub fn with_help<D>(mut self, help: D) -> Self
+    where
+        D: fmt::Display + Send + Sync + 'static,
+    {
+        self.inner.meta.help = Some(help.to_string());
+        self
+    }
+
+    /// Records how long the operation represented by this `Mishap` took before it failed.
+    ///
+    /// The duration is part of this node's [`metadata`](ErrorTree::metadata), and can be shown
+    /// with [`ErrorTreeDisplay::show_durations`]. Unlike help text, the duration is not carried
+    /// over when this `Mishap` is wrapped with another message, since each layer has a duration of
+    /// its own.
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.inner.node = self.inner.node.with_duration(duration);
+        self
+    }
+
+    /// Records the time at which the operation represented by this `Mishap` failed.
+    ///
+    /// Like the [duration](Self::with_duration), the timestamp is part of this node's
+    /// [`metadata`](ErrorTree::metadata) and is not carried over when wrapping.
+    pub fn with_timestamp(mut self, timestamp: SystemTime) -> Self {
+        self.inner.node = self.inner.node.with_timestamp(timestamp);
+        self
+    }
+
+    /// Records how severe this error is.
+    ///
+    /// The severity is part of this node's [`metadata`](ErrorTree::metadata) and is not carried
+    /// over when wrapping. See [`ErrorTreeDisplay::min_severity`] for a way to use it.
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.inner.node = self.inner.node.with_severity(severity);
+        self
+    }
+
+    /// Returns the help text attached to this `Mishap`, if any.
+    #[inline]
+    pub fn help(&self) -> Option<&str> {
+        self.inner.meta.help.as_deref()
+    }
+
+    /// Attaches a list of suggestions to this `Mishap`, describing possible ways to fix the error.
+    ///
+    /// Like help text, suggestions are not part of the tree itself. They're shown by
+    /// [`display_report`](Self::display_report) once
+    /// [`show_suggestions`](MishapReportDisplay::show_suggestions) is called.
+    ///
+    /// Suggestions accumulate: they're preserved when this `Mishap` is wrapped with another
+    /// message, and calling this method appends to the existing suggestions rather than replacing
+    /// them. Suggestions from inner layers, which are usually the most specific, come first.
+    pub fn with_suggestions(mut self, suggestions: Vec<String>) -> Self {
+        self.inner.meta.suggestions.extend(suggestions);
+        self
+    }
+
+    /// Returns the suggestions attached to this `Mishap` and the `Mishap`s it wraps.
+    #[inline]
+    pub fn suggestions(&self) -> &[String] {
+        &self.inner.meta.suggestions
+    }
+
+    /// Displays this `Mishap` as a report for end users.
+    ///
+    /// A report consists of the tree as displayed by [`display_tree`](ErrorTreeExt::display_tree),
+    /// followed by a `help:` section if help text is attached. Suggestions can be included with
+    /// [`show_suggestions`](MishapReportDisplay::show_suggestions).
+    #[inline]
+    pub fn display_report(&self) -> MishapReportDisplay<'_> {
+        MishapReportDisplay {
+            mishap: self,
+            show_suggestions: false,
+        }
+    }
+
+    /// Displays only the sources of this `Mishap` in a tree-like format.
+    ///
+    /// Unlike [`display_tree`](ErrorTreeExt::display_tree), this omits this `Mishap`'s own
+    /// message and the `Caused by:` header. This is handy for showing sources under a custom
+    /// heading.
+    #[inline]
+    pub fn display_sources(&self) -> ErrorTreeDisplay<'_, Self> {
+        self.display_tree().sources_only()
+    }
+
+    /// Returns an iterator over the chain of errors, if this `Mishap` represents a single chain of
+    /// [`std::error::Error`]s.
+    ///
+    /// The first item is the outermost error, followed by each of its sources in turn. For
+    /// `Mishap`s that are represented as a tree (for example, ones with multiple sources), this
+    /// returns an empty iterator.
+    pub fn chain_errors(&self) -> impl Iterator<Item = &(dyn std::error::Error + 'static)> {
+        let chain = match &self.inner.kind {
+            TreeImpl::Error(error) => Some(error.chain()),
+            TreeImpl::Tree(_) => None,
+        };
+        chain.into_iter().flatten()
+    }
+
+    /// Attempts to downcast the error chain represented by this `Mishap` to a concrete type.
+    ///
+    /// For `Mishap`s created from an [`anyhow::Error`] (or any other single chain of errors),
+    /// this delegates to [`anyhow::Error::downcast_ref`], so it has exactly the same semantics:
+    /// it finds errors anywhere in the chain, as well as context values attached with
+    /// [`anyhow::Context`]. For `Mishap`s that are represented as a tree, such as ones with
+    /// multiple sources or ones created with [`wrap_mishap`](Self::wrap_mishap), this returns
+    /// `None`.
+    pub fn downcast_ref<T>(&self) -> Option<&T>
+    where
+        T: fmt::Display + fmt::Debug + Send + Sync + 'static,
+    {
+        match &self.inner.kind {
+            TreeImpl::Error(error) => error.downcast_ref(),
+            TreeImpl::Tree(_) => None,
         }
     }
+
+    /// Attempts to downcast the error chain represented by this `Mishap` to a concrete type,
+    /// taking ownership of it.
+    ///
+    /// This is the owned counterpart to [`downcast_ref`](Self::downcast_ref), and delegates to
+    /// [`anyhow::Error::downcast`] in the same way. If the downcast fails, or if this `Mishap` is
+    /// represented as a tree, the `Mishap` is returned unchanged.
+    pub fn downcast<T>(self) -> Result<T, Self>
+    where
+        T: fmt::Display + fmt::Debug + Send + Sync + 'static,
+    {
+        let inner = *self.inner;
+        let kind = match inner.kind {
+            TreeImpl::Error(error) => match error.downcast() {
+                Ok(value) => return Ok(value),
+                Err(error) => TreeImpl::Error(error),
+            },
+            kind @ TreeImpl::Tree(_) => kind,
+        };
+        Err(Self {
+            inner: Box::new(MishapInner { kind, ..inner }),
+        })
+    }
+
+    /// Returns the first raw OS error code found in this tree.
+    ///
+    /// This walks the tree in depth-first pre-order, starting with this `Mishap`, and returns the
+    /// [`raw_os_error`](std::io::Error::raw_os_error) of the first [`std::io::Error`] that has
+    /// one, no matter how deeply it's wrapped. `io::Error`s without an OS error code are skipped.
+    pub fn first_os_error(&self) -> Option<i32> {
+        self.io_errors().find_map(|error| error.raw_os_error())
+    }
+
+    /// Returns the [kind](std::io::Error::kind) of the first [`std::io::Error`] found in this
+    /// tree.
+    ///
+    /// Errors passed to constructors like [`Self::from_error`] and [`WrapError::wrap_error`] are
+    /// kept as-is rather than being stringified, so this walks the tree in depth-first pre-order,
+    /// starting with this `Mishap`, and finds the first `io::Error` no matter how deeply it's
+    /// wrapped.
+    ///
+    /// ```
+    /// use mishap::{Mishap, WrapError};
+    /// use std::io;
+    ///
+    /// let result: Result<(), _> = Err(io::Error::from(io::ErrorKind::NotFound));
+    /// let error = result
+    ///     .wrap_error("failed to read config")
+    ///     .wrap_error("failed to start")
+    ///     .unwrap_err();
+    /// assert_eq!(error.io_kind(), Some(io::ErrorKind::NotFound));
+    /// ```
+    ///
+    /// [`WrapError::wrap_error`]: crate::WrapError::wrap_error
+    pub fn io_kind(&self) -> Option<std::io::ErrorKind> {
+        self.io_errors().next().map(|error| error.kind())
+    }
+
+    /// Returns an iterator over the [`std::io::Error`]s in this tree, in depth-first pre-order.
+    fn io_errors(&self) -> impl Iterator<Item = &std::io::Error> {
+        let sources = self.iter_dfs().map(|(_, source)| source);
+        std::iter::once(ErrorTreeSource::Tree(self))
+            .chain(sources)
+            .filter_map(|node| match node {
+                ErrorTreeSource::Error(error) => Some(error),
+                ErrorTreeSource::Tree(tree) => tree.as_error(),
+            })
+            .filter_map(|error| error.downcast_ref::<std::io::Error>())
+    }
+
+    /// Returns the message of each node along the primary path of this tree, from the top down.
+    ///
+    /// The primary path starts at this `Mishap` and follows the first source of each node. At
+    /// branch points, nodes with more than one source, the other sources are ignored. This is
+    /// useful for showing a short trail of context, such as
+    /// `["loading config", "reading file", "permission denied"]`.
+    pub fn context_messages(&self) -> Vec<String> {
+        let messages = self.chain().map(|source| source.to_string());
+        std::iter::once(self.to_string()).chain(messages).collect()
+    }
+
+    /// Returns the unique id generated when this error was first created.
+    ///
+    /// The id is generated when the innermost `Mishap` is constructed. Wrapping a `Mishap` with
+    /// another message preserves the original id, so the same id can be used to correlate an error
+    /// across logs and the responses it ends up in.
+    ///
+    /// With the `serde-err-tree` feature, the id can be serialized as `"error_id"`; see the
+    /// [`ErrorTreeMetadata` impl](#impl-ErrorTreeMetadata-for-Mishap).
+    #[cfg(feature = "uuid")]
+    #[inline]
+    pub fn id(&self) -> Option<Uuid> {
+        Some(self.inner.meta.id)
+    }
+
+    /// Returns the name of the thread on which this error was first created, if that thread was
+    /// named.
+    ///
+    /// Like the [id](Self::id), the thread name is captured when the innermost `Mishap` is
+    /// constructed and preserved when it's wrapped, so it identifies the thread that produced the
+    /// error even after the error has been sent to and wrapped on another thread.
+    #[cfg(feature = "thread-name")]
+    #[inline]
+    pub fn thread_name(&self) -> Option<&str> {
+        self.inner.meta.thread_name.as_deref()
+    }
+
+    /// Returns the location in the source code where this `Mishap` was created.
+    ///
+    /// This is the call site of the constructor, such as [`Self::from_msg`], or of the `wrap_`
+    /// method that created this `Mishap`. Unlike the [id](Self::id), the location isn't
+    /// preserved when a `Mishap` is wrapped: each wrapping records its own call site, so the
+    /// location of the outermost `Mishap` is where its message was added.
+    #[cfg(feature = "location")]
+    #[inline]
+    pub fn location(&self) -> Option<&'static Location<'static>> {
+        Some(self.inner.location)
+    }
 }
 
 impl fmt::Debug for Mishap {
@@ -189,7 +627,12 @@ impl fmt::Debug for Mishap {
         if f.alternate() {
             // Similar to anyhow, in this case use the underlying Debug
             // impl.
-            return self.kind.fmt(f);
+            let _budget = DebugBudget::enter();
+            #[cfg(feature = "thread-name")]
+            if let Some(thread_name) = self.thread_name() {
+                writeln!(f, "thread: {thread_name:?}")?;
+            }
+            return self.inner.kind.fmt(f);
         }
 
         fmt::Display::fmt(&self.display_tree(), f)
@@ -198,22 +641,185 @@ impl fmt::Debug for Mishap {
 
 impl fmt::Display for Mishap {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match &*self.kind {
+        match &self.inner.kind {
             TreeImpl::Error(error) => error.fmt(f),
             TreeImpl::Tree(tree) => tree.fmt(f),
         }
     }
 }
 
+/// Creates a `Mishap` from a message and an error, equivalent to [`Mishap::from_msg_and_error`].
+///
+/// ```
+/// use mishap::Mishap;
+///
+/// let error = std::io::Error::new(std::io::ErrorKind::NotFound, "file not found");
+/// let mishap = Mishap::from(("failed to read config", error));
+/// assert_eq!(mishap.to_string(), "failed to read config");
+/// ```
+impl<D, E> From<(D, E)> for Mishap
+where
+    D: fmt::Display + Send + Sync + 'static,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    fn from((msg, error): (D, E)) -> Self {
+        Self::from_msg_and_error(msg, error)
+    }
+}
+
+/// Creates a `Mishap` from a message and another `Mishap`, equivalent to
+/// [`Mishap::wrap_mishap`].
+///
+/// A blanket impl for all error trees would overlap with the impl for errors, since a type can
+/// implement both traits. For other error trees, use [`Mishap::from_msg_and_error_tree`].
+impl<D> From<(D, Mishap)> for Mishap
+where
+    D: fmt::Display + Send + Sync + 'static,
+{
+    fn from((msg, mishap): (D, Mishap)) -> Self {
+        mishap.wrap_mishap(msg)
+    }
+}
+
 impl ErrorTree for Mishap {
     fn sources(&self) -> Box<dyn Iterator<Item = ErrorTreeSource<'_>> + '_> {
-        match &*self.kind {
+        match &self.inner.kind {
             TreeImpl::Error(error) => {
                 Box::new(error.source().into_iter().map(ErrorTreeSource::Error))
             }
             TreeImpl::Tree(tree) => tree.sources(),
         }
     }
+
+    fn as_error(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.inner.kind {
+            TreeImpl::Error(error) => Some(error.as_ref()),
+            TreeImpl::Tree(tree) => tree.as_error(),
+        }
+    }
+
+    #[inline]
+    fn metadata(&self) -> Option<&NodeMetadata> {
+        Some(&self.inner.node)
+    }
+}
+
+/// Provides the [id](Mishap::id) of a `Mishap` as extra metadata for serialization.
+///
+/// With the `uuid` feature, [`Ser::with_metadata`](serde_err_tree::Ser::with_metadata) adds
+/// `"extra": {"error_id": "<uuid>"}` to the root node. The id isn't stored under `"id"`, since
+/// [`Ser::dedupe_subtrees`](serde_err_tree::Ser::dedupe_subtrees) already uses that key for the
+/// positions of nodes. Only the root is given an id: the sources of a `Mishap` are type-erased, so
+/// nested `Mishap`s can't be found.
+///
+/// This impl is only available with the `serde-err-tree` feature.
+#[cfg(feature = "serde-err-tree")]
+impl serde_err_tree::ErrorTreeMetadata for Mishap {
+    #[inline]
+    fn extra(&self) -> Option<&std::collections::BTreeMap<String, serde_json::Value>> {
+        #[cfg(feature = "uuid")]
+        return Some(&self.inner.extra);
+        #[cfg(not(feature = "uuid"))]
+        return None;
+    }
+}
+
+struct MishapInner {
+    kind: TreeImpl,
+    meta: Meta,
+    // Metadata for this node only, which is not carried over when wrapping.
+    node: NodeMetadata,
+    // The extra metadata serialized by serde-err-tree, built from `meta`.
+    #[cfg(all(feature = "serde-err-tree", feature = "uuid"))]
+    extra: std::collections::BTreeMap<String, serde_json::Value>,
+    // Where this node was created. Like `node`, this is not carried over when wrapping.
+    #[cfg(feature = "location")]
+    location: &'static Location<'static>,
+}
+
+/// Information attached to a `Mishap` that isn't part of the tree itself.
+///
+/// This is captured when a `Mishap` is first created, and carried over when the `Mishap` is wrapped
+/// with another message.
+#[derive(Clone)]
+struct Meta {
+    #[cfg(feature = "uuid")]
+    id: Uuid,
+    help: Option<String>,
+    suggestions: Vec<String>,
+    #[cfg(feature = "thread-name")]
+    thread_name: Option<String>,
+}
+
+impl Meta {
+    fn capture() -> Self {
+        Self {
+            #[cfg(feature = "uuid")]
+            id: Uuid::new_v4(),
+            help: None,
+            suggestions: Vec::new(),
+            #[cfg(feature = "thread-name")]
+            thread_name: std::thread::current().name().map(str::to_owned),
+        }
+    }
+
+    /// Returns the metadata of `tree` if it is a `Mishap`, or captures new metadata otherwise.
+    fn of<ET: 'static>(tree: &ET) -> Self {
+        match (tree as &dyn Any).downcast_ref::<Mishap>() {
+            Some(mishap) => mishap.inner.meta.clone(),
+            None => Self::capture(),
+        }
+    }
+}
+
+/// A displayer for a [`Mishap`] as a report, including any help text.
+///
+/// Returned by [`Mishap::display_report`].
+#[derive(Clone, Copy, Debug)]
+pub struct MishapReportDisplay<'a> {
+    mishap: &'a Mishap,
+    show_suggestions: bool,
+}
+
+impl<'a> MishapReportDisplay<'a> {
+    /// Also show the [suggestions](Mishap::suggestions) attached to the `Mishap`, if there are
+    /// any.
+    ///
+    /// Suggestions are shown as a numbered list in a `Suggestions:` section after the tree, and
+    /// before any help text.
+    #[inline]
+    pub fn show_suggestions(mut self) -> Self {
+        self.show_suggestions = true;
+        self
+    }
+}
+
+impl<'a> fmt::Display for MishapReportDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // display_tree always ends with a newline.
+        write!(f, "{}", self.mishap.display_tree())?;
+
+        let suggestions = self.mishap.suggestions();
+        if self.show_suggestions && !suggestions.is_empty() {
+            writeln!(f, "\nSuggestions:")?;
+            for (index, suggestion) in suggestions.iter().enumerate() {
+                let prefix = format!("  {}. ", index + 1);
+                // Align continuation lines with the start of the suggestion.
+                let continuation = " ".repeat(prefix.len());
+                let mut indent = IndentWriter::new_skip_initial(&continuation, &mut *f);
+                writeln!(indent, "{prefix}{suggestion}")?;
+            }
+        }
+
+        if let Some(help) = self.mishap.help() {
+            writeln!(f)?;
+            // Align continuation lines with the start of the help text.
+            let mut indent = IndentWriter::new_skip_initial("      ", &mut *f);
+            writeln!(indent, "help: {}", help)?;
+        }
+
+        Ok(())
+    }
 }
 
 enum TreeImpl {
@@ -225,15 +831,15 @@ enum TreeImpl {
 }
 
 impl TreeImpl {
-    fn new_chain(error: anyhow::Error) -> Box<Self> {
-        Box::new(TreeImpl::Error(error))
+    fn new_chain(error: anyhow::Error) -> Self {
+        TreeImpl::Error(error)
     }
 
-    fn new_tree(tree: impl ErrorTree + 'static) -> Box<Self> {
-        Box::new(TreeImpl::Tree(tree.into_boxed()))
+    fn new_tree(tree: impl ErrorTree + 'static) -> Self {
+        TreeImpl::Tree(tree.into_boxed())
     }
 
-    fn new_wrapped_tree<D, ET>(msg: D, sources: impl IntoIterator<Item = ET>) -> Box<Self>
+    fn new_wrapped_tree<D, ET>(msg: D, sources: impl IntoIterator<Item = ET>) -> Self
     where
         D: fmt::Display + Send + Sync + 'static,
         ET: ErrorTree + 'static,
@@ -243,7 +849,7 @@ impl TreeImpl {
             // If there are no sources, this can be simplified to an anyhow error.
             return TreeImpl::new_chain(anyhow!(msg.to_string()));
         }
-        Box::new(TreeImpl::Tree(Box::new(WrappedTree::new(msg, sources))))
+        TreeImpl::Tree(Box::new(WrappedTree::new(msg, sources)))
     }
 }
 
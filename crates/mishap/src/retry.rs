@@ -0,0 +1,81 @@
+use crate::Mishap;
+use err_tree::{ErrorTreeExt, ErrorTreeSource};
+use std::{error::Error, io};
+
+/// A policy for deciding whether an error is transient, and so whether the operation that
+/// produced it can be retried.
+///
+/// Used by [`Mishap::is_retryable_with`].
+pub trait RetryPolicy {
+    /// Returns true if `error` is transient.
+    fn is_retryable(&self, error: &(dyn Error + 'static)) -> bool;
+}
+
+impl<F> RetryPolicy for F
+where
+    F: Fn(&(dyn Error + 'static)) -> bool,
+{
+    #[inline]
+    fn is_retryable(&self, error: &(dyn Error + 'static)) -> bool {
+        self(error)
+    }
+}
+
+/// The default [`RetryPolicy`], used by [`Mishap::is_retryable`].
+///
+/// This policy treats an error as retryable if it is an [`io::Error`] with one of these kinds:
+///
+/// * [`TimedOut`](io::ErrorKind::TimedOut)
+/// * [`Interrupted`](io::ErrorKind::Interrupted)
+/// * [`WouldBlock`](io::ErrorKind::WouldBlock)
+/// * [`ConnectionRefused`](io::ErrorKind::ConnectionRefused)
+/// * [`ConnectionReset`](io::ErrorKind::ConnectionReset)
+/// * [`ConnectionAborted`](io::ErrorKind::ConnectionAborted)
+/// * [`BrokenPipe`](io::ErrorKind::BrokenPipe)
+///
+/// All other errors are treated as permanent.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultRetryPolicy;
+
+impl RetryPolicy for DefaultRetryPolicy {
+    fn is_retryable(&self, error: &(dyn Error + 'static)) -> bool {
+        let Some(error) = error.downcast_ref::<io::Error>() else {
+            return false;
+        };
+        matches!(
+            error.kind(),
+            io::ErrorKind::TimedOut
+                | io::ErrorKind::Interrupted
+                | io::ErrorKind::WouldBlock
+                | io::ErrorKind::ConnectionRefused
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::BrokenPipe
+        )
+    }
+}
+
+impl Mishap {
+    /// Returns true if every leaf of this tree is retryable according to [`DefaultRetryPolicy`].
+    ///
+    /// See [`Self::is_retryable_with`] for details.
+    #[inline]
+    pub fn is_retryable(&self) -> bool {
+        self.is_retryable_with(&DefaultRetryPolicy)
+    }
+
+    /// Returns true if every leaf of this tree is retryable according to `policy`.
+    ///
+    /// The leaves of a tree are its root causes, so a tree is considered retryable only if all of
+    /// its root causes are transient. Leaves that aren't backed by a [`std::error::Error`] (see
+    /// [`ErrorTree::as_error`](err_tree::ErrorTree::as_error)) are never retryable.
+    pub fn is_retryable_with(&self, policy: &dyn RetryPolicy) -> bool {
+        self.leaves().all(|leaf| {
+            let error = match leaf {
+                ErrorTreeSource::Error(error) => Some(error),
+                ErrorTreeSource::Tree(tree) => tree.as_error(),
+            };
+            error.is_some_and(|error| policy.is_retryable(error))
+        })
+    }
+}
@@ -0,0 +1,33 @@
+use crate::{accumulator::errors_occurred, Mishap};
+use futures::{Stream, StreamExt};
+use std::pin::pin;
+
+/// Drives `stream` to completion, collecting every successful item and every error.
+///
+/// Unlike stopping at the first error, this gathers all the errors produced by the stream. If
+/// there were no errors, returns all the items in order. Otherwise, returns a `Mishap` with each
+/// error as a source, and the items are discarded.
+pub async fn collect_stream_errors<S, T, E>(stream: S) -> Result<Vec<T>, Mishap>
+where
+    S: Stream<Item = Result<T, E>>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let mut stream = pin!(stream);
+    let mut items = Vec::new();
+    let mut errors = Vec::new();
+    while let Some(item) = stream.next().await {
+        match item {
+            Ok(item) => items.push(item),
+            Err(error) => errors.push(error),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(items)
+    } else {
+        Err(Mishap::from_msg_and_errors(
+            errors_occurred(errors.len()),
+            errors,
+        ))
+    }
+}
@@ -0,0 +1,32 @@
+use crate::Mishap;
+use err_tree::ErrorTreeExt;
+use std::fmt;
+use tracing::Level;
+
+impl Mishap {
+    /// Emits this tree as [`tracing`] events at the given level, one event per node.
+    ///
+    /// Nodes are emitted in depth-first pre-order, starting with the root. Each event has the
+    /// node's message as its message, along with a `depth` field: the root has depth 0, its
+    /// direct sources have depth 1, and so on. This lets structured subscribers, such as those
+    /// that emit JSON, index each node of the tree separately.
+    ///
+    /// This method is only available with the `tracing` feature.
+    pub fn emit_tracing(&self, level: Level) {
+        emit(level, 0, self);
+        for (depth, source) in self.iter_dfs() {
+            emit(level, depth + 1, &source);
+        }
+    }
+}
+
+fn emit(level: Level, depth: usize, msg: &dyn fmt::Display) {
+    // The level passed to tracing's macros must be a constant, so match on it here.
+    match level {
+        Level::ERROR => tracing::event!(Level::ERROR, depth, "{msg}"),
+        Level::WARN => tracing::event!(Level::WARN, depth, "{msg}"),
+        Level::INFO => tracing::event!(Level::INFO, depth, "{msg}"),
+        Level::DEBUG => tracing::event!(Level::DEBUG, depth, "{msg}"),
+        Level::TRACE => tracing::event!(Level::TRACE, depth, "{msg}"),
+    }
+}
@@ -1,4 +1,4 @@
-use crate::Mishap;
+use crate::{debug::DebugSources, Mishap};
 use err_tree::ErrorTree;
 use std::fmt::{self, Write};
 
@@ -51,10 +51,73 @@ pub trait WrapError<T, E>: private::Sealed {
         D: fmt::Display + Send + Sync + 'static,
         F: FnOnce() -> D;
 
+    /// Wrap the error value with the [`Debug`](fmt::Debug) representation of `value` as the
+    /// message.
+    ///
+    /// `value` is only formatted, with `{:?}`, once an error does occur.
+    fn wrap_error_debug<V>(self, value: V) -> Result<T, Mishap>
+    where
+        V: fmt::Debug;
+
     /// Convert the error value into a [`Mishap`] without attaching another message.
     ///
     /// This is equivalent to `From<E: Error> for Mishap`.
     fn wrap_error_no_msg(self) -> Result<T, Mishap>;
+
+    /// An alias for [`wrap_error`](Self::wrap_error), named after [`anyhow::Context::context`].
+    ///
+    /// Both spellings produce identical trees:
+    ///
+    /// ```
+    /// use err_tree::ErrorTreeExt;
+    /// use mishap::WrapError;
+    ///
+    /// let read = || std::fs::read_to_string("does-not-exist.json");
+    /// let wrapped = read().wrap_error("failed to read config").unwrap_err();
+    /// let context = read().context("failed to read config").unwrap_err();
+    /// assert_eq!(
+    ///     context.display_tree().to_string(),
+    ///     wrapped.display_tree().to_string(),
+    /// );
+    /// ```
+    ///
+    /// Since the method names are the same, this trait and `anyhow::Context` shouldn't both be
+    /// imported in the same scope.
+    #[inline]
+    #[cfg_attr(feature = "location", track_caller)]
+    fn context<D>(self, msg: D) -> Result<T, Mishap>
+    where
+        D: fmt::Display + Send + Sync + 'static,
+        Self: Sized,
+    {
+        self.wrap_error(msg)
+    }
+
+    /// An alias for [`wrap_error_with`](Self::wrap_error_with), named after
+    /// [`anyhow::Context::with_context`].
+    ///
+    /// ```
+    /// use err_tree::ErrorTreeExt;
+    /// use mishap::{Mishap, WrapError};
+    ///
+    /// let fail = || Err::<(), _>(Mishap::from_msg("connection refused"));
+    /// let wrapped = fail().wrap_error_with(|| format!("request {} failed", 1)).unwrap_err();
+    /// let context = fail().with_context(|| format!("request {} failed", 1)).unwrap_err();
+    /// assert_eq!(
+    ///     context.display_tree().to_string(),
+    ///     wrapped.display_tree().to_string(),
+    /// );
+    /// ```
+    #[inline]
+    #[cfg_attr(feature = "location", track_caller)]
+    fn with_context<D, F>(self, f: F) -> Result<T, Mishap>
+    where
+        D: fmt::Display + Send + Sync + 'static,
+        F: FnOnce() -> D,
+        Self: Sized,
+    {
+        self.wrap_error_with(f)
+    }
 }
 
 /// Extension trait for wrapping lists or other iterators of errors with ad-hoc messages.
@@ -102,27 +165,98 @@ pub trait WrapAnyhows<T>: private::Sealed {
         F: FnOnce() -> D;
 }
 
+// The impls below use `match` rather than `Result::map_err`, so that with the `location` feature,
+// `#[track_caller]` reports the caller's location rather than a closure's.
 impl<T, E> WrapError<T, E> for Result<T, E>
 where
     E: std::error::Error + Send + Sync + 'static,
 {
+    #[cfg_attr(feature = "location", track_caller)]
     fn wrap_error<D>(self, msg: D) -> Result<T, Mishap>
     where
         D: fmt::Display + Send + Sync + 'static,
     {
-        self.map_err(|error| Mishap::from_msg_and_error(msg, error))
+        match self {
+            Ok(t) => Ok(t),
+            Err(error) => Err(Mishap::from_msg_and_error(msg, error)),
+        }
     }
 
+    #[cfg_attr(feature = "location", track_caller)]
     fn wrap_error_with<D, F>(self, f: F) -> Result<T, Mishap>
     where
         D: fmt::Display + Send + Sync + 'static,
         F: FnOnce() -> D,
     {
-        self.map_err(|error| Mishap::from_msg_and_error(f(), error))
+        match self {
+            Ok(t) => Ok(t),
+            Err(error) => Err(Mishap::from_msg_and_error(f(), error)),
+        }
+    }
+
+    #[cfg_attr(feature = "location", track_caller)]
+    fn wrap_error_debug<V>(self, value: V) -> Result<T, Mishap>
+    where
+        V: fmt::Debug,
+    {
+        match self {
+            Ok(t) => Ok(t),
+            Err(error) => Err(Mishap::from_msg_and_error(format!("{value:?}"), error)),
+        }
+    }
+
+    #[cfg_attr(feature = "location", track_caller)]
+    fn wrap_error_no_msg(self) -> Result<T, Mishap> {
+        match self {
+            Ok(t) => Ok(t),
+            Err(error) => Err(Mishap::from_error(error)),
+        }
+    }
+}
+
+/// Wraps an existing [`Mishap`], nesting it under the new message.
+///
+/// This makes it possible to keep adding context with the same methods once a `Mishap` has been
+/// created: `result.wrap_error("a").wrap_error("b")` produces `b` caused by `a`, rather than
+/// siblings. It's equivalent to calling [`Mishap::wrap_mishap`] on the error.
+impl<T> WrapError<T, Mishap> for Result<T, Mishap> {
+    #[cfg_attr(feature = "location", track_caller)]
+    fn wrap_error<D>(self, msg: D) -> Result<T, Mishap>
+    where
+        D: fmt::Display + Send + Sync + 'static,
+    {
+        match self {
+            Ok(t) => Ok(t),
+            Err(error) => Err(error.wrap_mishap(msg)),
+        }
+    }
+
+    #[cfg_attr(feature = "location", track_caller)]
+    fn wrap_error_with<D, F>(self, f: F) -> Result<T, Mishap>
+    where
+        D: fmt::Display + Send + Sync + 'static,
+        F: FnOnce() -> D,
+    {
+        match self {
+            Ok(t) => Ok(t),
+            Err(error) => Err(error.wrap_mishap(f())),
+        }
+    }
+
+    #[cfg_attr(feature = "location", track_caller)]
+    fn wrap_error_debug<V>(self, value: V) -> Result<T, Mishap>
+    where
+        V: fmt::Debug,
+    {
+        match self {
+            Ok(t) => Ok(t),
+            Err(error) => Err(error.wrap_mishap(format!("{value:?}"))),
+        }
     }
 
+    #[cfg_attr(feature = "location", track_caller)]
     fn wrap_error_no_msg(self) -> Result<T, Mishap> {
-        self.map_err(Mishap::from_error)
+        self
     }
 }
 
@@ -131,19 +265,27 @@ where
     I: IntoIterator<Item = E>,
     E: std::error::Error + Send + Sync + 'static,
 {
+    #[cfg_attr(feature = "location", track_caller)]
     fn wrap_errors<D>(self, msg: D) -> Result<T, Mishap>
     where
         D: fmt::Display + Send + Sync + 'static,
     {
-        self.map_err(|sources| Mishap::from_msg_and_errors(msg, sources))
+        match self {
+            Ok(t) => Ok(t),
+            Err(sources) => Err(Mishap::from_msg_and_errors(msg, sources)),
+        }
     }
 
+    #[cfg_attr(feature = "location", track_caller)]
     fn wrap_errors_with<D, F>(self, f: F) -> Result<T, Mishap>
     where
         D: fmt::Display + Send + Sync + 'static,
         F: FnOnce() -> D,
     {
-        self.map_err(|sources| Mishap::from_msg_and_errors(f(), sources))
+        match self {
+            Ok(t) => Ok(t),
+            Err(sources) => Err(Mishap::from_msg_and_errors(f(), sources)),
+        }
     }
 }
 
@@ -151,23 +293,35 @@ impl<T, ET> WrapErrorTree<T, ET> for Result<T, ET>
 where
     ET: ErrorTree + 'static,
 {
+    #[cfg_attr(feature = "location", track_caller)]
     fn wrap_error_tree<D>(self, msg: D) -> Result<T, Mishap>
     where
         D: fmt::Display + Send + Sync + 'static,
     {
-        self.map_err(|error| Mishap::from_msg_and_error_tree(msg, error))
+        match self {
+            Ok(t) => Ok(t),
+            Err(error) => Err(Mishap::from_msg_and_error_tree(msg, error)),
+        }
     }
 
+    #[cfg_attr(feature = "location", track_caller)]
     fn wrap_error_tree_with<D, F>(self, f: F) -> Result<T, Mishap>
     where
         D: fmt::Display + Send + Sync + 'static,
         F: FnOnce() -> D,
     {
-        self.map_err(|error| Mishap::from_msg_and_error_tree(f(), error))
+        match self {
+            Ok(t) => Ok(t),
+            Err(error) => Err(Mishap::from_msg_and_error_tree(f(), error)),
+        }
     }
 
+    #[cfg_attr(feature = "location", track_caller)]
     fn wrap_error_tree_no_msg(self) -> Result<T, Mishap> {
-        self.map_err(Mishap::from_error_tree)
+        match self {
+            Ok(t) => Ok(t),
+            Err(error) => Err(Mishap::from_error_tree(error)),
+        }
     }
 }
 
@@ -176,36 +330,52 @@ where
     I: IntoIterator<Item = ET>,
     ET: ErrorTree + 'static,
 {
+    #[cfg_attr(feature = "location", track_caller)]
     fn wrap_error_trees<D>(self, msg: D) -> Result<T, Mishap>
     where
         D: fmt::Display + Send + Sync + 'static,
     {
-        self.map_err(|error| Mishap::from_msg_and_error_trees(msg, error))
+        match self {
+            Ok(t) => Ok(t),
+            Err(error) => Err(Mishap::from_msg_and_error_trees(msg, error)),
+        }
     }
 
+    #[cfg_attr(feature = "location", track_caller)]
     fn wrap_error_trees_with<D, F>(self, f: F) -> Result<T, Mishap>
     where
         D: fmt::Display + Send + Sync + 'static,
         F: FnOnce() -> D,
     {
-        self.map_err(|error| Mishap::from_msg_and_error_trees(f(), error))
+        match self {
+            Ok(t) => Ok(t),
+            Err(error) => Err(Mishap::from_msg_and_error_trees(f(), error)),
+        }
     }
 }
 
 impl<T> WrapAnyhow<T> for anyhow::Result<T> {
+    #[cfg_attr(feature = "location", track_caller)]
     fn wrap_anyhow<D>(self, msg: D) -> Result<T, Mishap>
     where
         D: fmt::Display + Send + Sync + 'static,
     {
-        self.map_err(|error| Mishap::from_msg_and_anyhow(msg, error))
+        match self {
+            Ok(t) => Ok(t),
+            Err(error) => Err(Mishap::from_msg_and_anyhow(msg, error)),
+        }
     }
 
+    #[cfg_attr(feature = "location", track_caller)]
     fn wrap_anyhow_with<D, F>(self, f: F) -> Result<T, Mishap>
     where
         D: fmt::Display + Send + Sync + 'static,
         F: FnOnce() -> D,
     {
-        self.map_err(|error| Mishap::from_msg_and_anyhow(f(), error))
+        match self {
+            Ok(t) => Ok(t),
+            Err(error) => Err(Mishap::from_msg_and_anyhow(f(), error)),
+        }
     }
 }
 
@@ -213,19 +383,27 @@ impl<I, T> WrapAnyhows<T> for Result<T, I>
 where
     I: IntoIterator<Item = anyhow::Error>,
 {
+    #[cfg_attr(feature = "location", track_caller)]
     fn wrap_anyhows<D>(self, msg: D) -> Result<T, Mishap>
     where
         D: fmt::Display + Send + Sync + 'static,
     {
-        self.map_err(|error| Mishap::from_msg_and_anyhows(msg, error))
+        match self {
+            Ok(t) => Ok(t),
+            Err(error) => Err(Mishap::from_msg_and_anyhows(msg, error)),
+        }
     }
 
+    #[cfg_attr(feature = "location", track_caller)]
     fn wrap_anyhows_with<D, F>(self, f: F) -> Result<T, Mishap>
     where
         D: fmt::Display + Send + Sync + 'static,
         F: FnOnce() -> D,
     {
-        self.map_err(|error| Mishap::from_msg_and_anyhows(f(), error))
+        match self {
+            Ok(t) => Ok(t),
+            Err(error) => Err(Mishap::from_msg_and_anyhows(f(), error)),
+        }
     }
 }
 
@@ -258,7 +436,7 @@ where
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("Mishap")
             .field("msg", &Quoted(&self.msg))
-            .field("sources", &self.sources)
+            .field("sources", &DebugSources(&self.sources))
             .finish()
     }
 }
@@ -0,0 +1,73 @@
+use err_tree::{testing::assert_tree, ErrorTreeExt};
+use mishap::{try_collect_errors, ErrorAccumulator, Mishap};
+
+#[test]
+fn test_empty() {
+    let acc = ErrorAccumulator::new("failed to process items");
+    assert!(acc.is_empty());
+    assert_eq!(acc.len(), 0);
+    assert!(acc.into_result().is_ok());
+
+    let mut acc = ErrorAccumulator::new("failed to process items");
+    assert_eq!(acc.push_result(Ok(1)), Some(1));
+    assert!(acc.is_empty());
+    assert!(acc.into_mishap().is_none());
+}
+
+#[test]
+fn test_non_empty() {
+    let mut acc = ErrorAccumulator::new("failed to process items");
+    acc.push(Mishap::from_msg("item 1 is invalid"));
+    assert_eq!(
+        acc.push_result::<()>(Err(Mishap::from_msg("item 2 is invalid"))),
+        None
+    );
+    assert_eq!(acc.push_result(Ok(3)), Some(3));
+    assert!(!acc.is_empty());
+    assert_eq!(acc.len(), 2);
+
+    let error = acc.into_result().unwrap_err();
+    assert_eq!(
+        error.display_tree().to_string(),
+        "failed to process items\n\
+         \n\
+         Caused by:\n\n  \
+           + item 1 is invalid\n  \
+           + item 2 is invalid\n",
+    );
+}
+
+#[test]
+fn test_try_collect_errors() {
+    let values = try_collect_errors([Ok(1), Ok(2), Ok(3)]).unwrap();
+    assert_eq!(values, [1, 2, 3]);
+
+    let mishap = try_collect_errors::<u32, _>([
+        Err(Mishap::from_msg("item 1 is invalid")),
+        Err(Mishap::from_msg("item 2 is invalid")),
+    ])
+    .unwrap_err();
+    assert_tree!(
+        mishap,
+        "2 errors occurred" => {
+            "item 1 is invalid",
+            "item 2 is invalid",
+        }
+    );
+
+    let mishap = try_collect_errors([
+        Ok(1),
+        Err(Mishap::from_messages(&[
+            "item 2 is invalid",
+            "not a number",
+        ])),
+        Ok(3),
+    ])
+    .unwrap_err();
+    assert_tree!(
+        mishap,
+        "1 error occurred" => {
+            "item 2 is invalid" => { "not a number" },
+        }
+    );
+}
@@ -0,0 +1,366 @@
+use err_tree::{
+    testing::assert_tree, ErrorTree, ErrorTreeExt, ErrorTreeSource, ErrorTreeWrapper,
+    OwnedErrorTree,
+};
+use mishap::{Mishap, WrapError};
+use std::fmt;
+
+/// A deliberately buggy tree, where sources can loop back to their ancestors.
+#[derive(Debug)]
+struct Node {
+    msg: &'static str,
+    sources: &'static [&'static Node],
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.msg)
+    }
+}
+
+impl ErrorTree for Node {
+    fn sources(&self) -> Box<dyn Iterator<Item = ErrorTreeSource<'_>> + '_> {
+        Box::new(
+            self.sources
+                .iter()
+                .map(|&source| ErrorTreeSource::Tree(source)),
+        )
+    }
+}
+
+// root -> [a, b]
+// a -> [root]
+// b -> [leaf, b, leaf]
+static ROOT: Node = Node {
+    msg: "root",
+    sources: &[&A, &B],
+};
+static A: Node = Node {
+    msg: "a",
+    sources: &[&ROOT],
+};
+static B: Node = Node {
+    msg: "b",
+    sources: &[&LEAF, &B, &LEAF],
+};
+static LEAF: Node = Node {
+    msg: "leaf",
+    sources: &[],
+};
+
+// chain -> chain2 -> chain
+static CHAIN: Node = Node {
+    msg: "chain",
+    sources: &[&CHAIN2],
+};
+static CHAIN2: Node = Node {
+    msg: "chain2",
+    sources: &[&CHAIN],
+};
+
+/// An error whose source is itself.
+#[derive(Debug)]
+struct LoopError {
+    msg: &'static str,
+}
+
+impl fmt::Display for LoopError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.msg)
+    }
+}
+
+impl std::error::Error for LoopError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&LOOP_ERROR)
+    }
+}
+
+static LOOP_ERROR: LoopError = LoopError { msg: "loop" };
+
+#[test]
+fn test_display_cycle() {
+    assert_eq!(
+        ROOT.display_tree().to_string(),
+        "root\n\
+         \n\
+         Caused by:\n\n  \
+           + a\n      \
+               - (cycle)\n  \
+           + b\n    \
+             + leaf\n    \
+             + (cycle)\n    \
+             + leaf\n",
+    );
+    assert_eq!(
+        CHAIN.display_tree().to_string(),
+        "chain\n\nCaused by:\n\n  - chain2\n  - (cycle)\n",
+    );
+    assert_eq!(
+        ROOT.display_tree().outline('*').to_string(),
+        "* root\n** a\n*** (cycle)\n** b\n*** leaf\n*** (cycle)\n*** leaf\n",
+    );
+
+    // The same node appearing in different branches isn't a cycle.
+    assert_eq!(B.display_tree().to_string().matches("leaf").count(), 2);
+
+    // Error chains are checked too.
+    let source = ErrorTreeSource::Error(&LOOP_ERROR);
+    assert_eq!(
+        source.display_tree().to_string(),
+        "loop\n\nCaused by:\n  - (cycle)\n",
+    );
+    let tree = mishap::Mishap::from_msg_and_error_trees(
+        "wrapper",
+        [
+            err_tree::ErrorWrapper::new(LoopError { msg: "outer" }),
+            err_tree::ErrorWrapper::new(LoopError { msg: "other" }),
+        ],
+    );
+    assert_eq!(
+        tree.display_tree().to_string(),
+        "wrapper\n\
+         \n\
+         Caused by:\n\n  \
+           + outer\n      \
+               - loop\n      \
+               - (cycle)\n  \
+           + other\n      \
+               - loop\n      \
+               - (cycle)\n",
+    );
+    assert_eq!(
+        tree.display_tree().max_depth(1).to_string(),
+        "wrapper\n\
+         \n\
+         Caused by:\n\n  \
+           + outer\n      \
+               ... (1 more level)\n  \
+           + other\n      \
+               ... (1 more level)\n",
+    );
+}
+
+#[test]
+fn test_iter_cycle() {
+    let messages = |iter: &mut dyn Iterator<Item = (usize, ErrorTreeSource<'_>)>| {
+        iter.map(|(depth, source)| format!("{depth}:{source}"))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        messages(&mut ROOT.iter_dfs()),
+        ["0:a", "1:root", "0:b", "1:leaf", "1:b", "1:leaf"],
+    );
+    assert_eq!(
+        messages(&mut ROOT.iter_bfs()),
+        ["0:a", "0:b", "1:root", "1:leaf", "1:b", "1:leaf"],
+    );
+
+    let leaves: Vec<_> = ROOT.leaves().map(|leaf| leaf.to_string()).collect();
+    assert_eq!(leaves, ["leaf", "leaf"]);
+
+    let chain: Vec<_> = CHAIN.chain().map(|source| source.to_string()).collect();
+    assert_eq!(chain, ["chain2", "chain"]);
+}
+
+#[test]
+fn test_walk_cycle() {
+    let layout: Vec<_> = ROOT
+        .walk_with_layout()
+        .map(|node| format!("{}:{}", node.depth, node.source))
+        .collect();
+    assert_eq!(layout, ["1:a", "2:root", "1:b", "2:leaf", "2:b", "2:leaf"]);
+    let layout: Vec<_> = CHAIN
+        .walk_with_layout()
+        .map(|node| format!("{}:{}", node.depth, node.source))
+        .collect();
+    assert_eq!(layout, ["1:chain2", "2:chain"]);
+
+    assert_eq!(
+        ROOT.find(|source| source.to_string() == "leaf")
+            .map(|source| source.to_string()),
+        Some("leaf".to_owned()),
+    );
+    assert!(CHAIN
+        .find(|source| source.to_string() == "missing")
+        .is_none());
+
+    assert_eq!(ROOT.depth(), 2);
+    assert_eq!(CHAIN.depth(), 2);
+    assert_eq!(ROOT.node_count(), 7);
+    assert_eq!(CHAIN.node_count(), 3);
+    assert_eq!(ROOT.depth_histogram(), [1, 2, 4]);
+    assert_eq!(CHAIN.depth_histogram(), [1, 1, 1]);
+
+    let leaves: Vec<_> = ROOT.distinct_leaf_messages().into_iter().collect();
+    assert_eq!(leaves, ["leaf"]);
+    assert!(CHAIN.distinct_leaf_messages().is_empty());
+
+    // Nodes that loop back are counted as leaves.
+    assert_eq!(
+        CHAIN.estimated_json_size(),
+        r#"{"msg":"chain","sources":[{"msg":"chain2","sources":[{"msg":"chain"}]}]}"#.len(),
+    );
+    assert_eq!(
+        ROOT.estimated_json_size(),
+        concat!(
+            r#"{"msg":"root","sources":[{"msg":"a","sources":[{"msg":"root"}]},"#,
+            r#"{"msg":"b","sources":[{"msg":"leaf"},{"msg":"b"},{"msg":"leaf"}]}]}"#,
+        )
+        .len(),
+    );
+}
+
+#[test]
+fn test_compare_cycle() {
+    // Sources that loop back are compared as leaves.
+    assert!(ROOT.structural_eq(&ROOT));
+    assert!(CHAIN.structural_eq(&OwnedErrorTree::new(&CHAIN)));
+    assert!(!CHAIN.structural_eq(&CHAIN2));
+    assert_eq!(ErrorTreeSource::Tree(&ROOT), ErrorTreeSource::Tree(&ROOT));
+    assert_ne!(ErrorTreeSource::Tree(&ROOT), ErrorTreeSource::Tree(&B));
+
+    assert_eq!(
+        CHAIN.longest_common_root_prefix(&CHAIN),
+        ["chain", "chain2", "chain"],
+    );
+    assert_eq!(
+        ROOT.longest_common_root_prefix(&ROOT),
+        ["root", "a", "root"]
+    );
+}
+
+#[test]
+fn test_owned_cycle() {
+    // Owned copies are cut off at sources that loop back, which become leaves.
+    assert_tree!(ROOT, "root" => { "a" => { "root" }, "b" => { "leaf", "b", "leaf" } });
+    assert_tree!(CHAIN, "chain" => { "chain2" => { "chain" } });
+
+    let mapped = CHAIN.map_tree(|node, depth| format!("{depth}: {node}"));
+    assert_tree!(mapped, "0: chain" => { "1: chain2" => { "2: chain" } });
+
+    // Omitted descendants are counted as if the pruned node were the root, so the count for a
+    // includes root and b, and stops at the second a.
+    assert_tree!(
+        ROOT.prune_to_depth(1),
+        "root" => {
+            "a" => { "... 6 descendants omitted" },
+            "b" => { "... 3 descendants omitted" },
+        }
+    );
+    assert_tree!(CHAIN.prune_to_depth(5), "chain" => { "chain2" => { "chain" } });
+
+    // The wrapper's tree is a reference to CHAIN, so the loop is noticed when chain2 repeats.
+    let error = ErrorTreeWrapper::new(&CHAIN);
+    let chain: Vec<_> =
+        std::iter::successors(std::error::Error::source(&error), |error| error.source())
+            .map(|error| error.to_string())
+            .collect();
+    assert_eq!(chain, ["chain2", "chain", "chain2"]);
+}
+
+#[test]
+fn test_display_compact_cycle() {
+    assert_eq!(
+        ROOT.display_tree_compact().to_string(),
+        "root: {a: (cycle); b: {leaf; (cycle); leaf}}",
+    );
+    assert_eq!(
+        CHAIN.display_tree_compact().to_string(),
+        "chain: chain2: (cycle)",
+    );
+}
+
+#[cfg(feature = "miette")]
+#[test]
+fn test_miette_cycle() {
+    use miette::Diagnostic;
+
+    fn shape(diagnostic: &dyn Diagnostic) -> String {
+        let related: Vec<_> = diagnostic.related().into_iter().flatten().collect();
+        if related.is_empty() {
+            return diagnostic.to_string();
+        }
+        let related: Vec<_> = related.into_iter().map(shape).collect();
+        format!("{} {{{}}}", diagnostic, related.join(", "))
+    }
+
+    // The wrapper holds a reference to ROOT, which is a separate node from ROOT itself, so ROOT
+    // is captured once more below a before the loop is noticed.
+    let diagnostic = err_tree::MietteErrorTree::new(&ROOT);
+    assert_eq!(
+        shape(&diagnostic),
+        "root {a {root {a, b {leaf, b, leaf}}}, b {leaf, b, leaf}}",
+    );
+}
+
+#[test]
+fn test_mishap_cycle() {
+    // As with miette, the mishap's source is a reference to CHAIN rather than CHAIN itself, so
+    // the loop is noticed when chain2 repeats.
+    let mishap = Mishap::from_msg_and_error_tree("wrapper", &CHAIN);
+    assert_eq!(
+        mishap.context_messages(),
+        ["wrapper", "chain", "chain2", "chain", "chain2"],
+    );
+
+    let mishap = Mishap::from_msg_and_error_trees(
+        "wrapper",
+        [err_tree::ErrorWrapper::new(LoopError { msg: "outer" })],
+    );
+    assert_eq!(mishap.io_kind(), None);
+    assert_eq!(mishap.first_os_error(), None);
+
+    // The leaves of ROOT aren't backed by errors, so they aren't retryable.
+    let mishap = Mishap::from_msg_and_error_tree("wrapper", &ROOT);
+    assert!(!mishap.is_retryable());
+}
+
+#[test]
+fn test_newtype_error_not_cycle() {
+    // An error whose source is its only field shares an address and size with that field, but
+    // isn't a cycle.
+    #[derive(Debug)]
+    struct ConfigError(std::io::Error);
+
+    impl fmt::Display for ConfigError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("failed to read config")
+        }
+    }
+
+    impl std::error::Error for ConfigError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    let io_error = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+    let result: Result<(), _> = Err(ConfigError(io_error));
+    let mishap = result.wrap_error("loading service").unwrap_err();
+    assert_eq!(
+        mishap.display_tree().to_string(),
+        "loading service\n\
+         \n\
+         Caused by:\n\n  \
+           - failed to read config\n  \
+           - no such file\n",
+    );
+    let messages: Vec<_> = mishap
+        .iter_dfs()
+        .map(|(_, source)| source.to_string())
+        .collect();
+    assert_eq!(messages, ["failed to read config", "no such file"]);
+    let leaves: Vec<_> = mishap.leaves().map(|leaf| leaf.to_string()).collect();
+    assert_eq!(leaves, ["no such file"]);
+
+    // The same applies to the error displayed on its own.
+    let error = ConfigError(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        "no such file",
+    ));
+    assert_eq!(
+        ErrorTreeSource::Error(&error).display_tree().to_string(),
+        "failed to read config\n\nCaused by:\n  - no such file\n",
+    );
+}
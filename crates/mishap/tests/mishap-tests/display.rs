@@ -1,14 +1,24 @@
-use err_tree::ErrorTreeExt;
+use err_tree::{ErrorTree, ErrorTreeExt, Severity};
 use mishap::Mishap;
+use std::time::Duration;
 
 #[test]
 fn test_complex() {
-    assert_outputs(mishap_testdata::complex(), "complex");
+    assert_outputs(on_unnamed_thread(mishap_testdata::complex), "complex");
 }
 
 #[test]
 fn test_single_source() {
-    assert_outputs(mishap_testdata::single_source(), "single-source");
+    assert_outputs(
+        on_unnamed_thread(mishap_testdata::single_source),
+        "single-source",
+    );
+}
+
+/// Creates the `Mishap` on an unnamed thread, so that the output doesn't depend on whether the
+/// `thread-name` feature is enabled.
+fn on_unnamed_thread(f: fn() -> Mishap) -> Mishap {
+    std::thread::spawn(f).join().unwrap()
 }
 
 fn assert_outputs(mishap: Mishap, filename_prefix: &str) {
@@ -24,6 +34,10 @@ fn assert_outputs(mishap: Mishap, filename_prefix: &str) {
         format!("tests/outputs/{filename_prefix}-display-tree.txt"),
         &mishap.display_tree().to_string(),
     );
+    expectorate::assert_contents(
+        format!("tests/outputs/{filename_prefix}-display-sources.txt"),
+        &mishap.display_sources().to_string(),
+    );
     expectorate::assert_contents(
         format!("tests/outputs/{filename_prefix}-debug.txt"),
         &format!("{:?}", mishap),
@@ -33,3 +47,523 @@ fn assert_outputs(mishap: Mishap, filename_prefix: &str) {
         &format!("{:#?}", mishap),
     );
 }
+
+#[test]
+fn test_deep_chain() {
+    // Long chains of trees are displayed without recursing for each level -- ensure that the
+    // output is the same as for any other chain.
+    let mut mishap = Mishap::from_msg("level 0");
+    for level in 1..1000 {
+        mishap = mishap.wrap_mishap(format!("level {level}\nline 2"));
+    }
+
+    let mut expected = "level 999\nline 2\n\nCaused by:\n\n".to_owned();
+    for level in (1..999).rev() {
+        expected.push_str(&format!("  - level {level}\n    line 2\n"));
+    }
+    expected.push_str("  - level 0\n");
+
+    assert_eq!(mishap.display_tree().to_string(), expected);
+}
+
+#[test]
+fn test_chain_fast_path() {
+    // Chains are displayed with a dedicated loop, unless sources might be elided. Setting a
+    // maximum depth that's never reached forces the general path, which must produce the same
+    // output.
+    let mut deep = Mishap::from_msg("level 0");
+    for level in 1..1000 {
+        deep = deep.wrap_mishap(format!("level {level}\nline 2"));
+    }
+
+    for mishap in [mishap_testdata::single_source(), deep] {
+        let general = |display: err_tree::ErrorTreeDisplay<'_, Mishap>| {
+            display.max_depth(usize::MAX).to_string()
+        };
+        assert_eq!(
+            mishap.display_tree().to_string(),
+            general(mishap.display_tree()),
+        );
+        assert_eq!(
+            mishap.display_sources().to_string(),
+            general(mishap.display_sources()),
+        );
+        assert_eq!(
+            mishap
+                .display_tree()
+                .colored(true)
+                .numbered(true)
+                .to_string(),
+            general(mishap.display_tree().colored(true).numbered(true)),
+        );
+        assert_eq!(
+            mishap.display_tree().with_bullets('*', '#').to_string(),
+            general(mishap.display_tree().with_bullets('*', '#')),
+        );
+    }
+}
+
+#[test]
+fn test_trailing_newline() {
+    let leaf = Mishap::from_msg("leaf");
+    let chain = Mishap::from_msg_and_anyhow("outer", anyhow::anyhow!("inner"));
+    let tree_chain = Mishap::from_msg("inner").wrap_mishap("outer");
+    let branching = Mishap::from_msg_and_error_trees(
+        "outer",
+        [Mishap::from_msg("inner 1"), Mishap::from_msg("inner 2")],
+    );
+
+    for (name, mishap) in [
+        ("leaf", &leaf),
+        ("chain", &chain),
+        ("tree chain", &tree_chain),
+        ("branching", &branching),
+        ("from testdata", &mishap_testdata::complex()),
+    ] {
+        let output = mishap.display_tree().to_string();
+        assert!(
+            output.ends_with('\n') && !output.ends_with("\n\n"),
+            "{name}: output ends with exactly one newline: {output:?}",
+        );
+
+        let trimmed = mishap.display_tree().trim_trailing_newline().to_string();
+        assert_eq!(
+            trimmed,
+            output.strip_suffix('\n').unwrap(),
+            "{name}: trimmed output is missing only the final newline",
+        );
+    }
+
+    assert_eq!(leaf.display_tree().to_string(), "leaf\n");
+    assert_eq!(
+        leaf.display_tree().trim_trailing_newline().to_string(),
+        "leaf"
+    );
+    // A leaf has no sources to display.
+    assert_eq!(leaf.display_sources().to_string(), "");
+    assert_eq!(
+        branching
+            .display_sources()
+            .trim_trailing_newline()
+            .to_string(),
+        "  + inner 1\n  + inner 2",
+    );
+
+    // Sources are displayed the same way.
+    let source = chain.sources().next().unwrap();
+    assert_eq!(source.display_tree().to_string(), "inner\n");
+    assert_eq!(
+        source.display_tree().trim_trailing_newline().to_string(),
+        "inner"
+    );
+}
+
+#[test]
+fn test_show_durations() {
+    let mishap = Mishap::from_msg_and_error_trees(
+        "all requests failed",
+        [
+            Mishap::from_msg("connection refused")
+                .with_duration(Duration::from_millis(12))
+                .wrap_mishap("request 1 failed"),
+            Mishap::from_msg("timed out").wrap_mishap("request 2 failed"),
+        ],
+    )
+    .with_duration(Duration::from_millis(1234));
+
+    assert_eq!(
+        mishap.display_tree().show_durations().to_string(),
+        "all requests failed [1234ms]\n\
+         \n\
+         Caused by:\n\n  \
+           + request 1 failed\n      \
+               - connection refused [12ms]\n  \
+           + request 2 failed\n      \
+               - timed out\n",
+    );
+
+    // Durations aren't shown by default.
+    assert!(!mishap.display_tree().to_string().contains("ms]"));
+}
+
+#[test]
+fn test_debug_node_limit() {
+    let mishap = Mishap::from_msg_and_error_trees(
+        "root",
+        (0..1000).map(|i| Mishap::from_msg(format!("leaf {i}"))),
+    );
+
+    // The limit is global, so other tests run at the same time may observe it. Use a limit that's
+    // larger than the testdata trees.
+    mishap::set_debug_node_limit(Some(50));
+    assert_eq!(mishap::debug_node_limit(), Some(50));
+    let output = format!("{:#?}", mishap);
+    mishap::set_debug_node_limit(None);
+
+    assert!(output.contains("\"leaf 48\",\n"), "leaf 48 is shown");
+    assert!(!output.contains("leaf 49"), "leaf 49 is not shown");
+    assert!(
+        output.ends_with("        ... (truncated),\n    ],\n}"),
+        "output is truncated: {output}"
+    );
+
+    // Without a limit, every node is shown.
+    assert!(format!("{:#?}", mishap).contains("leaf 999"));
+}
+
+#[test]
+fn test_max_output_len() {
+    let mishap = mishap_testdata::complex();
+    let full = mishap.display_tree().to_string();
+
+    let output = mishap.display_tree().max_output_len(20).to_string();
+    assert_eq!(output, "top-level line1\ntop-... (output truncated)");
+
+    // The limit doesn't split characters.
+    let mishap = Mishap::from_msg("ünïcödé").wrap_mishap("öüter");
+    assert_eq!(
+        mishap.display_tree().max_output_len(3).to_string(),
+        "ö... (output truncated)",
+    );
+
+    // Output that fits isn't changed.
+    let mishap = mishap_testdata::complex();
+    assert_eq!(
+        mishap.display_tree().max_output_len(full.len()).to_string(),
+        full
+    );
+}
+
+#[test]
+fn test_wrap_width() {
+    let mishap = Mishap::from_msg_and_error_trees(
+        "failed to synchronize the local replica with the upstream server",
+        [
+            Mishap::from_messages(&[
+                "request to https://example.com/api/v1/replicas/synchronize failed",
+                "the connection was reset by the remote peer after 30 seconds",
+            ]),
+            Mishap::from_msg("short message"),
+        ],
+    );
+    let output = mishap.display_tree().wrap_width(40).to_string();
+    assert!(
+        output.lines().all(|line| line.chars().count() <= 40),
+        "all lines fit in 40 columns:\n{output}",
+    );
+    expectorate::assert_contents("tests/outputs/wrap-width.txt", &output);
+}
+
+#[test]
+fn test_wrap_width_small() {
+    let mishap = Mishap::from_msg_and_error_trees(
+        "sync failed",
+        [Mishap::from_msg("disk full"), Mishap::from_msg("timed out")],
+    );
+    let unwrapped = mishap.display_tree().to_string();
+
+    // A width of 0 disables wrapping.
+    assert_eq!(mishap.display_tree().wrap_width(0).to_string(), unwrapped);
+
+    // Each line holds at least one character, and the header is kept as-is.
+    assert_eq!(
+        mishap.display_tree().wrap_width(1).to_string(),
+        "s\ny\nn\nc\nf\na\ni\nl\ne\nd\n\
+         \n\
+         Caused by:\n\n  \
+           + d\n    i\n    s\n    k\n    f\n    u\n    l\n    l\n  \
+           + t\n    i\n    m\n    e\n    d\n    o\n    u\n    t\n",
+    );
+}
+
+#[test]
+fn test_min_severity() {
+    // The same shape as the complex testdata tree, with severities attached.
+    let mishap = Mishap::from_msg_and_error_trees(
+        "top-level",
+        [
+            Mishap::from_msg_and_error_trees(
+                "sync failed",
+                [
+                    Mishap::from_msg("disk full").with_severity(Severity::Fatal),
+                    Mishap::from_msg("retrying").with_severity(Severity::Info),
+                ],
+            )
+            .with_severity(Severity::Error),
+            Mishap::from_msg("cache miss")
+                .with_severity(Severity::Warning)
+                .wrap_mishap("lookup failed"),
+            Mishap::from_msg("config invalid")
+                .with_severity(Severity::Fatal)
+                .wrap_mishap("startup failed"),
+            Mishap::from_msg("no severity"),
+        ],
+    );
+
+    // Nodes are kept if they or any of their descendants are severe enough.
+    assert_eq!(
+        mishap
+            .display_tree()
+            .min_severity(Severity::Fatal)
+            .to_string(),
+        "top-level\n\
+         \n\
+         Caused by:\n\n  \
+           + sync failed\n      \
+               - disk full\n  \
+           + startup failed\n      \
+               - config invalid\n",
+    );
+    assert_eq!(
+        mishap
+            .display_tree()
+            .min_severity(Severity::Warning)
+            .to_string(),
+        "top-level\n\
+         \n\
+         Caused by:\n\n  \
+           + sync failed\n      \
+               - disk full\n  \
+           + lookup failed\n      \
+               - cache miss\n  \
+           + startup failed\n      \
+               - config invalid\n",
+    );
+
+    // If nothing meets the threshold, only the root is displayed.
+    let mishap = Mishap::from_msg("inner")
+        .with_severity(Severity::Info)
+        .wrap_mishap("outer");
+    assert_eq!(
+        mishap
+            .display_tree()
+            .min_severity(Severity::Error)
+            .to_string(),
+        "outer\n"
+    );
+}
+
+#[test]
+fn test_outline() {
+    expectorate::assert_contents(
+        "tests/outputs/complex-outline.txt",
+        &mishap_testdata::complex()
+            .display_tree()
+            .outline('*')
+            .to_string(),
+    );
+
+    let mishap = Mishap::from_msg_and_error_trees(
+        "outer",
+        [
+            Mishap::from_messages(&["inner 1", "cause"]),
+            Mishap::from_msg("inner 2"),
+        ],
+    );
+    assert_eq!(
+        mishap.display_tree().outline('-').to_string(),
+        "- outer\n-- inner 1\n--- cause\n-- inner 2\n",
+    );
+    assert_eq!(
+        mishap
+            .display_tree()
+            .outline('-')
+            .sources_only()
+            .to_string(),
+        "- inner 1\n-- cause\n- inner 2\n",
+    );
+}
+
+#[test]
+fn test_hide_root_message() {
+    let mishap = mishap_testdata::single_source();
+    let output = mishap.display_tree().hide_root_message().to_string();
+    expectorate::assert_contents("tests/outputs/single-source-display-hide-root.txt", &output);
+
+    // This is the same as displaying the sources under a `Caused by:` header.
+    assert_eq!(
+        output,
+        format!("Caused by:\n\n{}", mishap.display_sources()),
+    );
+
+    // A leaf has no sources to display.
+    assert_eq!(
+        Mishap::from_msg("leaf")
+            .display_tree()
+            .hide_root_message()
+            .to_string(),
+        "",
+    );
+}
+
+#[test]
+fn test_with_node_formatter() {
+    let mishap = mishap_testdata::complex();
+    let output = mishap
+        .display_tree()
+        .with_node_formatter(|node, depth| format!("[{depth}] {node}"))
+        .to_string();
+    expectorate::assert_contents("tests/outputs/complex-node-formatter.txt", &output);
+
+    // Other options still apply.
+    let mishap = Mishap::from_msg_and_anyhow("outer", anyhow::anyhow!("inner"));
+    assert_eq!(
+        mishap
+            .display_tree()
+            .outline('*')
+            .with_node_formatter(|node, depth| format!("{node} ({depth})"))
+            .to_string(),
+        "* outer (0)\n** inner (1)\n",
+    );
+}
+
+#[test]
+fn test_colored() {
+    let mishap = on_unnamed_thread(mishap_testdata::single_source);
+    let output = mishap.display_tree().colored(true).to_string();
+    expectorate::assert_contents("tests/outputs/single-source-display-colored.txt", &output);
+
+    // Disabling colors leaves the output unchanged.
+    assert_eq!(
+        mishap.display_tree().colored(false).to_string(),
+        mishap.display_tree().to_string(),
+    );
+
+    // Only markers and headers are colored.
+    let mishap = Mishap::from_msg_and_error_trees(
+        "outer",
+        [Mishap::from_msg("inner 1"), Mishap::from_msg("inner 2")],
+    );
+    assert_eq!(
+        mishap.display_tree().colored(true).to_string(),
+        "outer\n\n\x1b[1mCaused by:\x1b[0m\n\n  \x1b[36m+\x1b[0m inner 1\n  \x1b[36m+\x1b[0m inner 2\n",
+    );
+    assert_eq!(
+        mishap.display_tree().outline('*').colored(true).to_string(),
+        "\x1b[36m*\x1b[0m outer\n\x1b[36m**\x1b[0m inner 1\n\x1b[36m**\x1b[0m inner 2\n",
+    );
+}
+
+#[test]
+fn test_with_bullets() {
+    let mishap = mishap_testdata::complex();
+    let output = mishap.display_tree().with_bullets('|', '*').to_string();
+    expectorate::assert_contents("tests/outputs/complex-custom-bullets.txt", &output);
+
+    // The default bullets are unchanged.
+    assert_eq!(
+        mishap.display_tree().with_bullets('-', '+').to_string(),
+        mishap.display_tree().to_string(),
+    );
+
+    // Wrapped lines still line up with the message.
+    let mishap = Mishap::from_msg_and_anyhow("outer", anyhow::anyhow!("one two three four"));
+    assert_eq!(
+        mishap
+            .display_tree()
+            .with_bullets('•', '◦')
+            .wrap_width(12)
+            .to_string(),
+        "outer\n\nCaused by:\n\n  • one two\n    three\n    four\n",
+    );
+}
+
+#[test]
+fn test_numbered() {
+    let mishap = mishap_testdata::complex();
+    let output = mishap.display_tree().numbered(true).to_string();
+    expectorate::assert_contents("tests/outputs/complex-numbered.txt", &output);
+
+    assert_eq!(
+        mishap.display_tree().numbered(false).to_string(),
+        mishap.display_tree().to_string(),
+    );
+
+    // Wider numbers widen the indentation, including for wrapped lines.
+    let mishap = Mishap::from_msg_and_error_trees(
+        "outer",
+        (1..=10)
+            .map(|n| Mishap::from_messages(&[format!("source number {n}"), "cause".to_owned()])),
+    );
+    let output = mishap
+        .display_tree()
+        .numbered(true)
+        .wrap_width(16)
+        .to_string();
+    assert!(
+        output.ends_with("  9. source\n     number 9\n       - cause\n  10. source\n      number 10\n        - cause\n"),
+        "output:\n{output}",
+    );
+}
+
+#[test]
+fn test_max_depth() {
+    let mishap = mishap_testdata::complex();
+    let output = mishap.display_tree().max_depth(2).to_string();
+    expectorate::assert_contents("tests/outputs/complex-max-depth.txt", &output);
+
+    // A limit at least as deep as the tree doesn't change anything.
+    assert_eq!(
+        mishap.display_tree().max_depth(6).to_string(),
+        mishap.display_tree().to_string(),
+    );
+
+    assert_eq!(
+        mishap.display_tree().max_depth(0).to_string(),
+        "top-level line1\ntop-level line2\n\nCaused by:\n\n  ... (6 more levels)\n",
+    );
+    assert_eq!(
+        mishap.display_tree().max_depth(5).outline('*').to_string(),
+        "* top-level line1\n  top-level line2\n\
+         ** mishap5 line1\n   mishap5 line2\n\
+         *** mishap4\n\
+         **** mishap2 line1\n\n     mishap2 line 2\n\
+         ***** mishap1 line1\n      mishap1 line2\n\
+         ****** anyhow error2\n\
+         ******* ... (1 more level)\n\
+         **** mishap3 line1\n     mishap3 line2\n\
+         ** mishap7 line1\n   mishap7 line2\n\
+         *** mishap6 line1\n    mishap6 line2\n\
+         ** mishap8 line1\n   mishap8 line2\n\
+         *** anyhow error3\n\
+         *** anyhow error4\n",
+    );
+
+    // Error chains are elided too.
+    let mishap = mishap_testdata::single_source();
+    assert_eq!(
+        mishap.display_tree().max_depth(2).to_string(),
+        "mishap2 line1\nmishap2 line2\n\nCaused by:\n\n  \
+           - mishap1 line1\n    mishap1 line2\n  \
+           - anyhow error3\n  \
+           ... (2 more levels)\n",
+    );
+}
+
+#[test]
+fn test_display_tree_compact() {
+    assert_eq!(
+        mishap_testdata::complex().display_tree_compact().to_string(),
+        "top-level line1 top-level line2: {\
+           mishap5 line1 mishap5 line2: mishap4: {\
+             mishap2 line1 mishap2 line 2: mishap1 line1 mishap1 line2: anyhow error2: anyhow error; \
+             mishap3 line1 mishap3 line2\
+           }; \
+           mishap7 line1 mishap7 line2: mishap6 line1 mishap6 line2; \
+           mishap8 line1 mishap8 line2: {anyhow error3; anyhow error4}\
+         }",
+    );
+    assert_eq!(
+        mishap_testdata::single_source()
+            .display_tree_compact()
+            .to_string(),
+        "mishap2 line1 mishap2 line2: mishap1 line1 mishap1 line2: \
+         anyhow error3: anyhow error2: anyhow error",
+    );
+    assert_eq!(
+        Mishap::from_msg("leaf\n")
+            .display_tree_compact()
+            .to_string(),
+        "leaf"
+    );
+}
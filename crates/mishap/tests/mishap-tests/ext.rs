@@ -0,0 +1,611 @@
+use err_tree::{ErrorTree, ErrorTreeExt, ErrorTreeSource, OwnedErrorTree};
+use mishap::Mishap;
+use std::collections::BTreeSet;
+
+#[test]
+fn test_longest_common_root_prefix() {
+    let a = Mishap::from_messages(&["loading config", "reading file", "permission denied"]);
+    let b = Mishap::from_messages(&["loading config", "reading file", "file not found"]);
+    assert_eq!(
+        a.longest_common_root_prefix(&b),
+        ["loading config", "reading file"],
+    );
+
+    let c = Mishap::from_messages(&["loading config"]);
+    assert_eq!(a.longest_common_root_prefix(&c), ["loading config"]);
+
+    let d = Mishap::from_messages(&["saving config", "reading file"]);
+    assert!(a.longest_common_root_prefix(&d).is_empty());
+
+    let complex = mishap_testdata::complex();
+    assert_eq!(
+        complex
+            .longest_common_root_prefix(&mishap_testdata::complex())
+            .len(),
+        7,
+        "identical trees share the entire first-source spine",
+    );
+}
+
+#[test]
+fn test_map_tree() {
+    let mishap = mishap_testdata::complex();
+    let mapped = mishap.map_tree(|node, depth| format!("{depth}: {node}"));
+
+    // The structure is preserved, and each message includes its depth.
+    fn add_depths(tree: &OwnedErrorTree, depth: usize) -> OwnedErrorTree {
+        OwnedErrorTree::from_msg_and_sources(
+            format!("{depth}: {}", tree.msg),
+            tree.sources
+                .iter()
+                .map(|source| add_depths(source, depth + 1))
+                .collect(),
+        )
+    }
+    let expected = add_depths(&OwnedErrorTree::new(&mishap), 0);
+    assert_eq!(mapped, expected);
+    assert_eq!(mapped.msg, "0: top-level line1\ntop-level line2");
+}
+
+#[test]
+fn test_map_tree_shape() {
+    let mishap = Mishap::from_msg_and_error_trees(
+        "outer",
+        [
+            Mishap::from_messages(&["inner 1", "cause"]),
+            Mishap::from_msg("inner 2"),
+        ],
+    );
+    let mapped = mishap.map_tree(|node, depth| format!("{depth}: {node}"));
+    assert_eq!(
+        mapped,
+        OwnedErrorTree::from_msg_and_sources(
+            "0: outer",
+            vec![
+                OwnedErrorTree::from_msg_and_sources(
+                    "1: inner 1",
+                    vec![OwnedErrorTree::from_msg_and_sources("2: cause", vec![])],
+                ),
+                OwnedErrorTree::from_msg_and_sources("1: inner 2", vec![]),
+            ],
+        ),
+    );
+}
+
+#[test]
+fn test_walk_with_layout() {
+    let mishap = mishap_testdata::complex();
+
+    // Draw the tree using the layout information.
+    let mut output = String::new();
+    for node in mishap.walk_with_layout() {
+        assert_eq!(node.ancestors_last.len(), node.depth - 1);
+        for &last in &node.ancestors_last {
+            output.push_str(if last { "   " } else { "│  " });
+        }
+        output.push_str(if node.is_last_sibling {
+            "└─ "
+        } else {
+            "├─ "
+        });
+        let msg = node.source.to_string();
+        output.push_str(msg.lines().next().unwrap());
+        output.push('\n');
+    }
+
+    assert_eq!(
+        output,
+        "├─ mishap5 line1\n\
+         │  └─ mishap4\n\
+         │     ├─ mishap2 line1\n\
+         │     │  └─ mishap1 line1\n\
+         │     │     └─ anyhow error2\n\
+         │     │        └─ anyhow error\n\
+         │     └─ mishap3 line1\n\
+         ├─ mishap7 line1\n\
+         │  └─ mishap6 line1\n\
+         └─ mishap8 line1\n   \
+            ├─ anyhow error3\n   \
+            └─ anyhow error4\n",
+    );
+}
+
+#[test]
+fn test_prune_to_depth() {
+    let mishap = mishap_testdata::complex();
+    let pruned = mishap.prune_to_depth(1);
+    assert_eq!(
+        pruned,
+        OwnedErrorTree::from_msg_and_sources(
+            "top-level line1\ntop-level line2",
+            vec![
+                OwnedErrorTree::from_msg_and_sources(
+                    "mishap5 line1\nmishap5 line2",
+                    vec![OwnedErrorTree::from_msg_and_sources(
+                        "... 6 descendants omitted",
+                        vec![],
+                    )],
+                ),
+                OwnedErrorTree::from_msg_and_sources(
+                    "mishap7 line1\nmishap7 line2",
+                    vec![OwnedErrorTree::from_msg_and_sources(
+                        "... 1 descendant omitted",
+                        vec![],
+                    )],
+                ),
+                OwnedErrorTree::from_msg_and_sources(
+                    "mishap8 line1\nmishap8 line2",
+                    vec![OwnedErrorTree::from_msg_and_sources(
+                        "... 2 descendants omitted",
+                        vec![],
+                    )],
+                ),
+            ],
+        ),
+    );
+
+    // Trees that are shallow enough are copied as-is.
+    assert_eq!(mishap.prune_to_depth(10), OwnedErrorTree::new(&mishap));
+}
+
+#[test]
+fn test_distinct_leaf_messages() {
+    let mishap = Mishap::from_msg_and_error_trees(
+        "all requests failed",
+        [
+            Mishap::from_messages(&["request 1 failed", "connection refused"]),
+            Mishap::from_messages(&["request 2 failed", "timed out"]),
+            Mishap::from_messages(&["request 3 failed", "connection refused"]),
+            Mishap::from_msg("connection refused"),
+        ],
+    );
+    assert_eq!(
+        mishap.distinct_leaf_messages(),
+        BTreeSet::from(["connection refused".to_owned(), "timed out".to_owned()]),
+    );
+
+    // A tree without sources is its own leaf.
+    let leaf = Mishap::from_msg("timed out");
+    assert_eq!(
+        leaf.distinct_leaf_messages(),
+        BTreeSet::from(["timed out".to_owned()])
+    );
+}
+
+#[test]
+fn test_depth_histogram() {
+    assert_eq!(
+        mishap_testdata::complex().depth_histogram(),
+        [1, 3, 4, 2, 1, 1, 1],
+    );
+    assert_eq!(mishap_testdata::single_source().depth_histogram(), [1; 5]);
+    assert_eq!(Mishap::from_msg("leaf").depth_histogram(), [1]);
+}
+
+#[test]
+fn test_depth() {
+    assert_eq!(mishap_testdata::complex().depth(), 6);
+    assert_eq!(mishap_testdata::single_source().depth(), 4);
+    assert_eq!(Mishap::from_msg("leaf").depth(), 0);
+
+    let messages: Vec<_> = (0..=1_000).map(|i| format!("level {i}")).collect();
+    assert_eq!(Mishap::from_messages(&messages).depth(), 1_000);
+}
+
+#[test]
+fn test_node_count() {
+    assert_eq!(mishap_testdata::complex().node_count(), 13);
+    assert_eq!(mishap_testdata::single_source().node_count(), 5);
+    assert_eq!(Mishap::from_msg("leaf").node_count(), 1);
+
+    // The count agrees with the depth histogram.
+    let mishap = mishap_testdata::complex();
+    assert_eq!(
+        mishap.node_count(),
+        mishap.depth_histogram().iter().sum::<usize>(),
+    );
+}
+
+#[test]
+fn test_source_accessors() {
+    let mishap = Mishap::from_msg_and_error_trees(
+        "outer",
+        [Mishap::from_messages(&["tree source", "cause"])],
+    );
+    let source = mishap.sources().next().unwrap();
+    assert!(source.is_tree());
+    assert!(!source.is_error());
+    assert_eq!(source.as_tree().unwrap().to_string(), "tree source");
+    // A Mishap is also an error, but as_error only matches the Error variant.
+    assert!(source.as_tree().unwrap().as_error().is_some());
+    assert!(source.as_error().is_none());
+
+    let mishap = Mishap::from_msg_and_anyhow("outer", anyhow::anyhow!("error source"));
+    let source = mishap.sources().next().unwrap();
+    assert!(source.is_error());
+    assert!(!source.is_tree());
+    assert_eq!(source.as_error().unwrap().to_string(), "error source");
+    assert!(source.as_tree().is_none());
+
+    // The predicates are handy in iterator chains.
+    let mishap = mishap_testdata::complex();
+    let errors = mishap
+        .iter_dfs()
+        .filter_map(|(_, source)| source.as_error())
+        .count();
+    let trees = mishap
+        .iter_dfs()
+        .filter(|(_, source)| source.is_tree())
+        .count();
+    assert_eq!(errors + trees, mishap.node_count() - 1);
+    assert!(errors > 0 && trees > 0);
+}
+
+#[test]
+fn test_source_to_owned_tree() {
+    let mishap = Mishap::from_msg_and_error_trees(
+        "outer",
+        [
+            Mishap::from_messages(&["tree source", "cause"]),
+            Mishap::from_msg("leaf"),
+        ],
+    );
+    let source = mishap.sources().next().unwrap();
+    assert!(matches!(source, ErrorTreeSource::Tree(_)));
+    assert_eq!(
+        source.to_owned_tree(),
+        OwnedErrorTree::from_msg_and_sources(
+            "tree source",
+            vec![OwnedErrorTree::from_msg_and_sources("cause", vec![])],
+        ),
+    );
+
+    let error = anyhow::anyhow!("root cause")
+        .context("middle")
+        .context("error source");
+    let mishap = Mishap::from_msg_and_anyhow("outer", error);
+    let source = mishap.sources().next().unwrap();
+    assert!(matches!(source, ErrorTreeSource::Error(_)));
+    assert_eq!(
+        source.to_owned_tree(),
+        OwnedErrorTree::from_msg_and_sources(
+            "error source",
+            vec![OwnedErrorTree::from_msg_and_sources(
+                "middle",
+                vec![OwnedErrorTree::from_msg_and_sources("root cause", vec![])],
+            )],
+        ),
+    );
+}
+
+#[test]
+fn test_find() {
+    let mishap = mishap_testdata::complex();
+    let found = mishap
+        .find(|source| source.to_string().contains("mishap3"))
+        .unwrap();
+    assert_eq!(found.to_string(), "mishap3 line1\nmishap3 line2");
+
+    // The first match in depth-first order is returned.
+    let found = mishap
+        .find(|source| source.to_string().starts_with("anyhow error"))
+        .unwrap();
+    assert_eq!(found.to_string(), "anyhow error2");
+
+    // The walk stops at the first match.
+    let mut visited = Vec::new();
+    mishap.find(|source| {
+        visited.push(source.to_string());
+        source.to_string() == "mishap4"
+    });
+    assert_eq!(visited, ["mishap5 line1\nmishap5 line2", "mishap4"],);
+
+    // The root isn't a source.
+    assert!(mishap
+        .find(|source| source.to_string().starts_with("top-level"))
+        .is_none());
+}
+
+#[test]
+fn test_find_map() {
+    let mishap = mishap_testdata::complex();
+    let found = mishap.find_map(|source| {
+        let msg = source.to_string();
+        (msg.starts_with("anyhow error") && source.sources().next().is_none()).then_some(msg)
+    });
+    assert_eq!(found.as_deref(), Some("anyhow error"));
+
+    // Sources are visited in depth-first pre-order, and the walk stops at the first match.
+    let mut visited = Vec::new();
+    let found = mishap.find_map(|source| {
+        visited.push(source.to_string());
+        (source.to_string() == "mishap4").then_some(visited.len())
+    });
+    assert_eq!(found, Some(2));
+    assert_eq!(visited, ["mishap5 line1\nmishap5 line2", "mishap4"]);
+
+    // The root isn't visited.
+    assert_eq!(
+        mishap.find_map(|source| source.to_string().starts_with("top-level").then_some(())),
+        None,
+    );
+
+    // The result can borrow from the tree.
+    let mishap = Mishap::from_msg_and_error(
+        "request failed",
+        std::io::Error::new(std::io::ErrorKind::TimedOut, "connection timed out"),
+    )
+    .wrap_mishap("operation failed");
+    let timeout = mishap.find_map(|source| {
+        let error = source.as_error().or_else(|| source.as_tree()?.as_error())?;
+        error
+            .downcast_ref::<std::io::Error>()
+            .filter(|error| error.kind() == std::io::ErrorKind::TimedOut)
+    });
+    assert_eq!(timeout.unwrap().to_string(), "connection timed out");
+}
+
+#[test]
+fn test_leaves() {
+    let mishap = mishap_testdata::complex();
+    let leaves: Vec<_> = mishap.leaves().map(|leaf| leaf.to_string()).collect();
+    assert_eq!(
+        leaves,
+        [
+            "anyhow error",
+            "mishap3 line1\nmishap3 line2",
+            "mishap6 line1\nmishap6 line2",
+            "anyhow error3",
+            "anyhow error4",
+        ],
+    );
+    assert!(mishap.leaves().all(|leaf| leaf.sources().next().is_none()));
+
+    let leaves: Vec<_> = mishap_testdata::single_source()
+        .leaves()
+        .map(|leaf| leaf.to_string())
+        .collect();
+    assert_eq!(leaves, ["anyhow error"]);
+
+    // A tree without sources is its own leaf.
+    let leaf = Mishap::from_msg("leaf");
+    let leaves: Vec<_> = leaf.leaves().map(|leaf| leaf.to_string()).collect();
+    assert_eq!(leaves, ["leaf"]);
+}
+
+#[test]
+fn test_iter_bfs() {
+    let mishap = mishap_testdata::complex();
+    let nodes: Vec<_> = mishap
+        .iter_bfs()
+        .map(|(depth, source)| (depth, source.to_string().lines().next().unwrap().to_owned()))
+        .collect();
+    assert_eq!(
+        nodes,
+        [
+            (0, "mishap5 line1"),
+            (0, "mishap7 line1"),
+            (0, "mishap8 line1"),
+            (1, "mishap4"),
+            (1, "mishap6 line1"),
+            (1, "anyhow error3"),
+            (1, "anyhow error4"),
+            (2, "mishap2 line1"),
+            (2, "mishap3 line1"),
+            (3, "mishap1 line1"),
+            (4, "anyhow error2"),
+            (5, "anyhow error"),
+        ]
+        .map(|(depth, msg)| (depth, msg.to_owned())),
+    );
+
+    let depths: Vec<_> = mishap_testdata::single_source()
+        .iter_bfs()
+        .map(|(depth, _)| depth)
+        .collect();
+    assert_eq!(depths, [0, 1, 2, 3]);
+}
+
+#[test]
+fn test_iter_dfs() {
+    let mishap = mishap_testdata::complex();
+    let nodes: Vec<_> = mishap
+        .iter_dfs()
+        .map(|(depth, source)| (depth, source.to_string().lines().next().unwrap().to_owned()))
+        .collect();
+    assert_eq!(
+        nodes,
+        [
+            (0, "mishap5 line1"),
+            (1, "mishap4"),
+            (2, "mishap2 line1"),
+            (3, "mishap1 line1"),
+            (4, "anyhow error2"),
+            (5, "anyhow error"),
+            (2, "mishap3 line1"),
+            (0, "mishap7 line1"),
+            (1, "mishap6 line1"),
+            (0, "mishap8 line1"),
+            (1, "anyhow error3"),
+            (1, "anyhow error4"),
+        ]
+        .map(|(depth, msg)| (depth, msg.to_owned())),
+    );
+
+    // Both error and tree sources are visited.
+    assert!(mishap
+        .iter_dfs()
+        .any(|(_, source)| matches!(source, ErrorTreeSource::Error(_))));
+    assert!(mishap
+        .iter_dfs()
+        .any(|(_, source)| matches!(source, ErrorTreeSource::Tree(_))));
+
+    // The order matches walk_with_layout, which uses depth 1 for the root's sources.
+    let layout: Vec<_> = mishap
+        .walk_with_layout()
+        .map(|node| (node.depth - 1, node.source.to_string()))
+        .collect();
+    let dfs: Vec<_> = mishap
+        .iter_dfs()
+        .map(|(depth, source)| (depth, source.to_string()))
+        .collect();
+    assert_eq!(dfs, layout);
+}
+
+#[test]
+fn test_structural_eq() {
+    let mishap = mishap_testdata::complex();
+    assert!(mishap.structural_eq(&mishap_testdata::complex()));
+    assert!(mishap.structural_eq(&mishap));
+
+    // Error and tree sources with the same messages are equal, regardless of their types.
+    let anyhow = Mishap::from_msg_and_anyhow("outer", anyhow::anyhow!("cause").context("middle"));
+    let owned = OwnedErrorTree::from_msg_and_sources(
+        "outer",
+        vec![OwnedErrorTree::from_msg_and_sources(
+            "middle",
+            vec![OwnedErrorTree::from_msg_and_sources("cause", vec![])],
+        )],
+    );
+    assert!(anyhow.structural_eq(&owned));
+    assert!(owned.structural_eq(&anyhow));
+
+    // Sources are compared in order.
+    let a = Mishap::from_msg_and_error_trees(
+        "outer",
+        [Mishap::from_msg("first"), Mishap::from_msg("second")],
+    );
+    let b = Mishap::from_msg_and_error_trees(
+        "outer",
+        [Mishap::from_msg("second"), Mishap::from_msg("first")],
+    );
+    assert!(!a.structural_eq(&b));
+
+    // Extra sources at any depth make trees unequal.
+    let c = Mishap::from_msg_and_error_trees(
+        "outer",
+        [
+            Mishap::from_msg("first"),
+            Mishap::from_messages(&["second", "cause"]),
+        ],
+    );
+    assert!(!a.structural_eq(&c));
+    assert!(!c.structural_eq(&a));
+    assert!(!a.structural_eq(&Mishap::from_msg("outer")));
+}
+
+#[test]
+fn test_source_eq() {
+    let anyhow = Mishap::from_msg_and_anyhow("outer", anyhow::anyhow!("cause").context("middle"));
+    let owned = OwnedErrorTree::from_msg_and_sources(
+        "outer",
+        vec![OwnedErrorTree::from_msg_and_sources(
+            "middle",
+            vec![OwnedErrorTree::from_msg_and_sources("cause", vec![])],
+        )],
+    );
+
+    // An Error source and a Tree source with the same messages and structure are equal.
+    let error_source = anyhow.sources().next().unwrap();
+    let tree_source = owned.sources().next().unwrap();
+    assert!(error_source.is_error() && tree_source.is_tree());
+    assert_eq!(error_source, tree_source);
+    assert_eq!(tree_source, error_source);
+    assert_eq!(error_source, error_source);
+
+    // Different messages at the top level or further down are unequal.
+    let other = Mishap::from_messages(&["outer", "middle", "other cause"]);
+    let other_source = other.sources().next().unwrap();
+    assert_eq!(other_source.to_string(), "middle");
+    assert_ne!(error_source, other_source);
+    assert_ne!(tree_source, ErrorTreeSource::Tree(&owned));
+
+    // So are sources with a different number of sources.
+    let shallow = OwnedErrorTree::from_msg_and_sources(
+        "outer",
+        vec![OwnedErrorTree::from_msg_and_sources("middle", vec![])],
+    );
+    assert_ne!(tree_source, shallow.sources().next().unwrap());
+
+    let complex = mishap_testdata::complex();
+    let complex2 = mishap_testdata::complex();
+    assert!(complex.sources().eq(complex2.sources()));
+}
+
+#[test]
+fn test_chain() {
+    let mishap = mishap_testdata::single_source();
+    let chain: Vec<_> = mishap.chain().map(|source| source.to_string()).collect();
+    assert_eq!(
+        chain,
+        [
+            "mishap1 line1\nmishap1 line2",
+            "anyhow error3",
+            "anyhow error2",
+            "anyhow error",
+        ],
+    );
+
+    // Only the first source is followed at each level.
+    let chain: Vec<_> = mishap_testdata::complex()
+        .chain()
+        .map(|source| source.to_string())
+        .collect();
+    assert_eq!(
+        chain,
+        [
+            "mishap5 line1\nmishap5 line2",
+            "mishap4",
+            "mishap2 line1\n\nmishap2 line 2",
+            "mishap1 line1\nmishap1 line2",
+            "anyhow error2",
+            "anyhow error",
+        ],
+    );
+    assert_eq!(Mishap::from_msg("leaf").chain().count(), 0);
+}
+
+#[test]
+fn test_root_cause() {
+    let mishap = mishap_testdata::single_source();
+    assert_eq!(mishap.root_cause().to_string(), "anyhow error");
+    assert!(mishap.root_cause().is_error());
+
+    // For a branching tree, only the first source of each node is followed, even though other
+    // branches have leaves of their own.
+    let mishap = mishap_testdata::complex();
+    assert_eq!(mishap.root_cause().to_string(), "anyhow error");
+    assert_eq!(
+        mishap.root_cause().to_string(),
+        mishap.chain().last().unwrap().to_string(),
+    );
+    assert!(mishap.leaves().count() > 1);
+
+    let mishap = Mishap::from_msg("leaf");
+    assert!(mishap.root_cause().is_tree());
+    assert_eq!(mishap.root_cause().to_string(), "leaf");
+}
+
+#[test]
+fn test_owned_copies_of_deep_tree() {
+    // Owned copies are built without recursing for each level, so deep trees don't overflow the
+    // stack.
+    let mut tree = OwnedErrorTree::from_msg_and_sources("level 0", Vec::new());
+    for level in 1..10_000 {
+        tree = OwnedErrorTree::from_msg_and_sources(format!("level {level}"), vec![tree]);
+    }
+
+    assert!(OwnedErrorTree::new(&tree).structural_eq(&tree));
+
+    let mapped = tree.map_tree(|node, depth| format!("{depth}: {node}"));
+    assert_eq!(mapped.depth(), 9_999);
+    assert_eq!(mapped.msg, "0: level 9999");
+    assert_eq!(mapped.root_cause().to_string(), "9999: level 0");
+
+    let pruned = tree.prune_to_depth(5_000);
+    assert_eq!(pruned.depth(), 5_001);
+    assert_eq!(
+        pruned.root_cause().to_string(),
+        "... 4999 descendants omitted",
+    );
+}
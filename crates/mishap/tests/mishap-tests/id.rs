@@ -0,0 +1,60 @@
+use mishap::{Mishap, WrapErrorTree};
+
+#[test]
+fn test_id_preserved_on_wrap() {
+    let mishap = Mishap::from_msg("inner");
+    let id = mishap.id().expect("id is generated on creation");
+
+    let wrapped = mishap.wrap_mishap("outer");
+    assert_eq!(wrapped.id(), Some(id), "wrap_mishap preserves the id");
+
+    let wrapped: Result<(), Mishap> = Err(wrapped);
+    let wrapped = wrapped.wrap_error_tree("outermost").unwrap_err();
+    assert_eq!(wrapped.id(), Some(id), "wrap_error_tree preserves the id");
+
+    let parts = Mishap::from_parts("parts", vec![wrapped]);
+    assert_ne!(
+        parts.id(),
+        Some(id),
+        "from_parts creates a new node with its own id"
+    );
+
+    let other = Mishap::from_msg("inner");
+    assert_ne!(other.id(), Some(id), "new mishaps get new ids");
+}
+
+#[cfg(feature = "serde-err-tree")]
+#[test]
+fn test_id_serialized() {
+    use serde_err_tree::Ser;
+
+    let mishap = Mishap::from_msg("inner").wrap_mishap("outer");
+    let id = mishap.id().expect("id is generated on creation");
+
+    let value = serde_json::to_value(Ser::new(&mishap).with_metadata()).unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({
+            "msg": "outer",
+            "sources": [{"msg": "inner"}],
+            "extra": {"error_id": id.to_string()},
+        }),
+    );
+
+    // The id doesn't collide with the ids written for repeated subtrees when deduplicating.
+    let mishap = Mishap::from_parts(
+        "outer",
+        vec![Mishap::from_msg("inner"), Mishap::from_msg("inner")],
+    );
+    let id = mishap.id().expect("id is generated on creation");
+    let ser = Ser::new(&mishap).with_metadata().dedupe_subtrees();
+    let value = serde_json::to_value(ser).unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({
+            "msg": "outer",
+            "sources": [{"id": 1, "msg": "inner"}, {"ref": 1}],
+            "extra": {"error_id": id.to_string()},
+        }),
+    );
+}
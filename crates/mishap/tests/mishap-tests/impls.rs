@@ -0,0 +1,172 @@
+use err_tree::{
+    sources_from, testing::assert_tree, ErrorTree, ErrorTreeExt, ErrorTreeSource, ErrorTreeWrapper,
+    KeyedErrors, PoisonTree,
+};
+use mishap::{Mishap, WrapErrorTree};
+use std::{collections::BTreeMap, sync::Mutex};
+
+#[test]
+fn test_boxed_error() {
+    let error: Box<dyn std::error::Error + Send + Sync> =
+        anyhow::anyhow!("root cause").context("outer").into();
+    let sources: Vec<_> = error.sources().map(|s| s.to_string()).collect();
+    assert_eq!(sources, ["root cause"]);
+    assert_eq!(
+        error.display_tree().to_string(),
+        "outer\n\nCaused by:\n\n  - root cause\n",
+    );
+}
+
+#[test]
+fn test_poison_tree() {
+    let mutex = Mutex::new(vec![1, 2, 3]);
+    std::thread::scope(|s| {
+        let handle = s.spawn(|| {
+            let _guard = mutex.lock().unwrap();
+            panic!("poisoning the mutex");
+        });
+        assert!(handle.join().is_err(), "thread panicked");
+    });
+
+    let mishap = mutex
+        .lock()
+        .map_err(PoisonTree::from)
+        .wrap_error_tree("failed to read cache")
+        .unwrap_err();
+    assert_eq!(
+        mishap.display_tree().to_string(),
+        "failed to read cache\n\nCaused by:\n\n  - lock poisoned\n",
+    );
+
+    let error = PoisonTree::from(mutex.try_lock().unwrap_err());
+    assert_eq!(error.to_string(), "lock poisoned");
+    assert_eq!(error.sources().count(), 0);
+
+    let _guard = mutex.lock().unwrap_or_else(|error| error.into_inner());
+    let error = PoisonTree::from(mutex.try_lock().unwrap_err());
+    assert_eq!(error.to_string(), "lock would block");
+}
+
+#[test]
+fn test_sources_from() {
+    #[derive(Debug)]
+    struct DeployError {
+        build: Mishap,
+        upload: std::io::Error,
+    }
+
+    impl std::fmt::Display for DeployError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("deploy failed")
+        }
+    }
+
+    impl ErrorTree for DeployError {
+        fn sources(&self) -> Box<dyn Iterator<Item = ErrorTreeSource<'_>> + '_> {
+            sources_from!(tree: &self.build, error: &self.upload)
+        }
+    }
+
+    let error = DeployError {
+        build: Mishap::from_messages(&["build failed", "compiler crashed"]),
+        upload: std::io::Error::other("upload failed"),
+    };
+    let sources: Vec<_> = error.sources().collect();
+    assert!(matches!(sources[0], ErrorTreeSource::Tree(_)));
+    assert!(matches!(sources[1], ErrorTreeSource::Error(_)));
+    assert_tree!(
+        error,
+        "deploy failed" => { "build failed" => { "compiler crashed" }, "upload failed" }
+    );
+
+    #[derive(Debug)]
+    struct Leaf;
+
+    impl std::fmt::Display for Leaf {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("leaf")
+        }
+    }
+
+    impl ErrorTree for Leaf {
+        fn sources(&self) -> Box<dyn Iterator<Item = ErrorTreeSource<'_>> + '_> {
+            sources_from!()
+        }
+    }
+
+    assert_eq!(Leaf.sources().count(), 0);
+}
+
+#[test]
+fn test_keyed_errors() {
+    let errors = BTreeMap::from([
+        ("port", std::io::Error::other("must be a number")),
+        ("host", std::io::Error::other("must not be empty")),
+        ("timeout", std::io::Error::other("must be positive")),
+    ]);
+    let tree = KeyedErrors::new("invalid config", errors);
+    assert_eq!(tree.len(), 3);
+    assert_tree!(
+        tree,
+        "invalid config" => {
+            "host: must not be empty",
+            "port: must be a number",
+            "timeout: must be positive",
+        }
+    );
+
+    // Each source is backed by the original error.
+    let backed = tree
+        .sources()
+        .filter_map(|source| match source {
+            ErrorTreeSource::Tree(tree) => tree.as_error(),
+            ErrorTreeSource::Error(_) => None,
+        })
+        .filter(|error| error.is::<std::io::Error>())
+        .count();
+    assert_eq!(backed, 3);
+
+    let errors = tree.into_inner();
+    assert_eq!(
+        errors.keys().copied().collect::<Vec<_>>(),
+        ["host", "port", "timeout"]
+    );
+}
+
+#[test]
+fn test_error_tree_wrapper() {
+    let mishap = mishap_testdata::complex();
+    let expected = mishap.to_string();
+    let wrapper = ErrorTreeWrapper::new(mishap);
+    assert_eq!(wrapper.to_string(), expected);
+
+    // Each tree is collapsed into its first source.
+    let error: Box<dyn std::error::Error + Send + Sync> = Box::new(wrapper);
+    let chain: Vec<_> = std::iter::successors(error.source(), |e| e.source())
+        .map(|e| e.to_string())
+        .collect();
+    assert_eq!(
+        chain,
+        [
+            "mishap5 line1\nmishap5 line2",
+            "mishap4",
+            "mishap2 line1\n\nmishap2 line 2",
+            "mishap1 line1\nmishap1 line2",
+            "anyhow error2",
+            "anyhow error",
+        ],
+    );
+
+    // The original tree is still available.
+    let wrapper = error.downcast::<ErrorTreeWrapper<Mishap>>().unwrap();
+    assert_eq!(wrapper.as_inner().sources().count(), 3);
+    let mishap = wrapper.into_inner();
+    assert_eq!(mishap.to_string(), expected);
+    assert_eq!(
+        mishap.display_tree().to_string(),
+        mishap_testdata::complex().display_tree().to_string(),
+    );
+
+    let leaf = ErrorTreeWrapper::new(Mishap::from_msg("leaf"));
+    assert!(std::error::Error::source(&leaf).is_none());
+}
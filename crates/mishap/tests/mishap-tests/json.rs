@@ -0,0 +1,31 @@
+use err_tree::ErrorTreeExt;
+use mishap::Mishap;
+
+#[test]
+fn test_from_serde_json() {
+    let json = "{\n  \"a\": 1,\n  \"b\": [1, 2,, 3]\n}";
+    let error = serde_json::from_str::<serde_json::Value>(json).unwrap_err();
+    let mishap = Mishap::from_serde_json(error);
+    assert_eq!(mishap.to_string(), "JSON parse error at 3:14 (syntax)");
+
+    // The original error is preserved as a source.
+    let source = mishap
+        .chain_errors()
+        .nth(1)
+        .and_then(|error| error.downcast_ref::<serde_json::Error>())
+        .expect("source is a serde_json::Error");
+    assert_eq!((source.line(), source.column()), (3, 14));
+    assert_eq!(
+        mishap.display_tree().to_string(),
+        format!("JSON parse error at 3:14 (syntax)\n\nCaused by:\n\n  - {source}\n"),
+    );
+
+    #[derive(Debug, serde::Deserialize)]
+    #[allow(dead_code)]
+    struct Data {
+        a: u32,
+    }
+    let error = serde_json::from_str::<Data>("{\"a\": \"one\"}").unwrap_err();
+    let mishap = Mishap::from_serde_json(error);
+    assert_eq!(mishap.to_string(), "JSON parse error at 1:11 (data)");
+}
@@ -0,0 +1,29 @@
+use mishap::{Mishap, WrapError};
+
+#[test]
+fn test_location() {
+    let line = line!();
+    let mishap = Mishap::from_msg("inner");
+    let location = mishap.location().expect("location is captured");
+    assert_eq!(location.file(), file!());
+    assert_eq!(location.line(), line + 1);
+
+    // Each wrapping records its own call site.
+    let line = line!();
+    let wrapped = mishap.wrap_mishap("outer");
+    assert_eq!(wrapped.location().unwrap().line(), line + 1);
+
+    let line = line!();
+    let result: Result<(), _> = Err(std::io::Error::from(std::io::ErrorKind::NotFound));
+    let error = result.wrap_error("failed to open file").unwrap_err();
+    let location = error.location().unwrap();
+    assert_eq!((location.file(), location.line()), (file!(), line + 2));
+
+    let line = line!();
+    let error = Err::<(), _>(error).wrap_error_with(|| "outer").unwrap_err();
+    assert_eq!(error.location().unwrap().line(), line + 1);
+
+    // Copies keep the original location.
+    let copy = error.clone_stringified();
+    assert_eq!(copy.location().unwrap().line(), line + 1);
+}
@@ -1 +1,22 @@
+mod accumulator;
+mod cycle;
 mod display;
+mod ext;
+#[cfg(feature = "uuid")]
+mod id;
+mod impls;
+#[cfg(feature = "serde_json")]
+mod json;
+#[cfg(feature = "location")]
+mod location;
+#[cfg(feature = "miette")]
+mod miette;
+mod mishap;
+mod retry;
+#[cfg(feature = "futures")]
+mod stream;
+mod testing;
+#[cfg(feature = "thread-name")]
+mod thread_name;
+#[cfg(feature = "tracing")]
+mod trace;
@@ -0,0 +1,33 @@
+use err_tree::{ErrorTree, ErrorTreeSource, MietteErrorTree};
+use miette::Diagnostic;
+
+#[test]
+fn test_related() {
+    let mishap = mishap_testdata::complex();
+    let diagnostic = MietteErrorTree::new(&mishap);
+    assert_eq!(diagnostic.to_string(), mishap.to_string());
+    assert_related(&diagnostic, mishap.sources());
+}
+
+#[test]
+fn test_leaf() {
+    let mishap = mishap::Mishap::from_msg("leaf");
+    let diagnostic = MietteErrorTree::new(mishap);
+    assert!(diagnostic.related().is_none());
+    assert_eq!(diagnostic.into_inner().to_string(), "leaf");
+}
+
+/// Checks that the related diagnostics of `diagnostic` match `sources`, recursively.
+#[track_caller]
+fn assert_related<'a>(
+    diagnostic: &dyn Diagnostic,
+    sources: impl Iterator<Item = ErrorTreeSource<'a>>,
+) {
+    let related: Vec<_> = diagnostic.related().into_iter().flatten().collect();
+    let sources: Vec<_> = sources.collect();
+    assert_eq!(related.len(), sources.len(), "for {diagnostic}");
+    for (related, source) in related.into_iter().zip(sources) {
+        assert_eq!(related.to_string(), source.to_string());
+        assert_related(related, source.sources());
+    }
+}
@@ -0,0 +1,551 @@
+use anyhow::anyhow;
+use err_tree::{testing::assert_tree, ErrorTree, ErrorTreeExt, ErrorTreeSource};
+use mishap::{Mishap, WrapError};
+
+#[test]
+fn test_chain_errors() {
+    let error = anyhow!("anyhow error").context("anyhow error2");
+    let mishap = Mishap::from_msg_and_anyhow("mishap1", error);
+    let messages: Vec<_> = mishap.chain_errors().map(|e| e.to_string()).collect();
+    assert_eq!(messages, ["mishap1", "anyhow error2", "anyhow error"]);
+
+    // single_source() is a tree at the root, so it doesn't have a chain.
+    let mishap = mishap_testdata::single_source();
+    assert_eq!(mishap.chain_errors().count(), 0);
+
+    // Its only source is backed by an anyhow chain, which yields each context frame.
+    let inner = mishap_testdata::single_source_inner();
+    let source = mishap
+        .sources()
+        .next()
+        .expect("single_source() has a source");
+    assert_eq!(source, ErrorTreeSource::Tree(&inner));
+    let messages: Vec<_> = inner.chain_errors().map(|e| e.to_string()).collect();
+    assert_eq!(
+        messages,
+        [
+            "mishap1 line1\nmishap1 line2",
+            "anyhow error3",
+            "anyhow error2",
+            "anyhow error",
+        ],
+    );
+}
+
+#[test]
+fn test_from_messages() {
+    let mishap = Mishap::from_messages(&["loading config", "reading file", "permission denied"]);
+    assert_eq!(
+        mishap.display_tree().to_string(),
+        "loading config\n\
+         \n\
+         Caused by:\n\n  \
+           - reading file\n  \
+           - permission denied\n",
+    );
+}
+
+#[test]
+#[should_panic = "requires at least one message"]
+fn test_from_messages_empty() {
+    _ = Mishap::from_messages::<&str>(&[]);
+}
+
+#[test]
+fn test_help() {
+    let mishap = Mishap::from_msg("permission denied").with_help("try running with sudo");
+    assert_eq!(mishap.help(), Some("try running with sudo"));
+    assert_eq!(mishap.to_string(), "permission denied");
+    assert_eq!(
+        mishap.display_report().to_string(),
+        "permission denied\n\nhelp: try running with sudo\n",
+    );
+
+    // Help is preserved when wrapping.
+    let mishap = mishap
+        .wrap_mishap("failed to read file")
+        .with_help("check the file's permissions\nor try running with sudo");
+    assert_eq!(
+        mishap.display_report().to_string(),
+        "failed to read file\n\
+         \n\
+         Caused by:\n\n  \
+           - permission denied\n\
+         \n\
+         help: check the file's permissions\n      \
+               or try running with sudo\n",
+    );
+    assert_eq!(
+        mishap.display_tree().to_string(),
+        "failed to read file\n\nCaused by:\n\n  - permission denied\n",
+        "display_tree doesn't include help"
+    );
+
+    let mishap = Mishap::from_msg("no help");
+    assert_eq!(mishap.help(), None);
+    assert_eq!(mishap.display_report().to_string(), "no help\n");
+}
+
+#[test]
+fn test_from_tuple() {
+    let error = std::io::Error::new(std::io::ErrorKind::NotFound, "file not found");
+    let mishap = Mishap::from(("failed to read config", error));
+    assert_eq!(
+        mishap.display_tree().to_string(),
+        "failed to read config\n\nCaused by:\n\n  - file not found\n",
+    );
+
+    let mishap = Mishap::from(("failed to start", mishap));
+    assert_eq!(
+        mishap.display_tree().to_string(),
+        "failed to start\n\
+         \n\
+         Caused by:\n\n  \
+           - failed to read config\n  \
+           - file not found\n",
+    );
+}
+
+#[test]
+fn test_wrap_error_debug() {
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    struct Request {
+        id: u32,
+        path: &'static str,
+    }
+
+    let request = Request {
+        id: 7,
+        path: "/books",
+    };
+    let expected = format!("{request:?}");
+    let result: Result<(), _> = Err(std::io::Error::other("connection reset"));
+    let mishap = result.wrap_error_debug(request).unwrap_err();
+    assert_eq!(mishap.to_string(), expected);
+    assert_eq!(mishap.to_string(), r#"Request { id: 7, path: "/books" }"#);
+}
+
+#[test]
+fn test_from_msg_with_mixed_sources() {
+    let mishap = Mishap::from_msg_with_mixed_sources(
+        "sync failed",
+        vec![anyhow!("connection refused").context("failed to fetch")],
+        vec![Mishap::from_messages(&["failed to apply", "disk full"]).into_boxed()],
+    );
+    assert_eq!(
+        mishap.display_tree().to_string(),
+        "sync failed\n\
+         \n\
+         Caused by:\n\n  \
+           + failed to fetch\n      \
+               - connection refused\n  \
+           + failed to apply\n      \
+               - disk full\n",
+    );
+}
+
+#[test]
+fn test_context_messages() {
+    let mishap = mishap_testdata::single_source();
+    assert_eq!(
+        mishap.context_messages(),
+        [
+            "mishap2 line1\nmishap2 line2",
+            "mishap1 line1\nmishap1 line2",
+            "anyhow error3",
+            "anyhow error2",
+            "anyhow error",
+        ],
+    );
+
+    // At branch points, the first source is followed.
+    let mishap = mishap_testdata::complex();
+    assert_eq!(
+        mishap.context_messages(),
+        [
+            "top-level line1\ntop-level line2",
+            "mishap5 line1\nmishap5 line2",
+            "mishap4",
+            "mishap2 line1\n\nmishap2 line 2",
+            "mishap1 line1\nmishap1 line2",
+            "anyhow error2",
+            "anyhow error",
+        ],
+    );
+}
+
+#[test]
+fn test_from_mishaps() {
+    let mishap = Mishap::from_mishaps("nothing failed", []);
+    assert_eq!(mishap.display_tree().to_string(), "nothing failed\n");
+
+    let mishap = Mishap::from_mishaps(
+        "request failed",
+        [Mishap::from_msg("timed out").with_help("try again later")],
+    );
+    assert_eq!(
+        mishap.display_tree().to_string(),
+        "request failed\n\nCaused by:\n\n  - timed out\n",
+    );
+    assert_eq!(mishap.help(), Some("try again later"));
+
+    let mishap = Mishap::from_mishaps(
+        "all requests failed",
+        vec![
+            Mishap::from_msg("timed out"),
+            Mishap::from_msg("connection refused"),
+        ],
+    );
+    assert_eq!(
+        mishap.display_tree().to_string(),
+        "all requests failed\n\
+         \n\
+         Caused by:\n\n  \
+           + timed out\n  \
+           + connection refused\n",
+    );
+}
+
+#[test]
+fn test_collect() {
+    let mishap = Mishap::collect("no tasks failed", []);
+    assert_tree!(mishap, "no tasks failed");
+
+    // A single mishap is still wrapped, and its help text isn't carried over.
+    let mishap = Mishap::collect(
+        "1 task failed",
+        [Mishap::from_msg("timed out").with_help("try again later")],
+    );
+    assert_tree!(mishap, "1 task failed" => { "timed out" });
+    assert_eq!(mishap.help(), None);
+
+    let mishap = Mishap::collect(
+        "2 tasks failed",
+        vec![
+            Mishap::from_msg_and_error_trees(
+                "task 1 failed",
+                [Mishap::from_msg("disk full"), Mishap::from_msg("read-only")],
+            ),
+            Mishap::from_messages(&["task 2 failed", "connection refused"]),
+        ],
+    );
+    assert_tree!(
+        mishap,
+        "2 tasks failed" => {
+            "task 1 failed" => {
+                "disk full",
+                "read-only",
+            },
+            "task 2 failed" => { "connection refused" },
+        }
+    );
+}
+
+#[test]
+fn test_from_parts() {
+    let mishap = Mishap::from_parts("leaf", vec![]);
+    assert_eq!(mishap.display_tree().to_string(), "leaf\n");
+
+    // A single child is still wrapped, and its help text isn't carried over.
+    let child = Mishap::from_msg("child").with_help("try again later");
+    let mishap = Mishap::from_parts("parent", vec![child]);
+    assert_eq!(
+        mishap.display_tree().to_string(),
+        "parent\n\nCaused by:\n\n  - child\n",
+    );
+    assert_eq!(mishap.help(), None);
+
+    let mishap = Mishap::from_parts(
+        "parent",
+        vec![
+            Mishap::from_msg("child 1"),
+            Mishap::from_messages(&["child 2", "grandchild"]),
+        ],
+    );
+    assert_eq!(
+        mishap.display_tree().to_string(),
+        "parent\n\nCaused by:\n\n  + child 1\n  + child 2\n      - grandchild\n",
+    );
+}
+
+#[test]
+fn test_downcast_ref() {
+    #[derive(Debug, PartialEq)]
+    struct RequestId(u32);
+
+    impl std::fmt::Display for RequestId {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "request {}", self.0)
+        }
+    }
+
+    let error = anyhow::Error::new(std::io::Error::from(std::io::ErrorKind::TimedOut))
+        .context(RequestId(42))
+        .context("operation failed");
+    let mishap = Mishap::from_anyhow(error);
+
+    // Both context values and errors in the chain can be recovered, as with anyhow.
+    assert_eq!(mishap.downcast_ref::<RequestId>(), Some(&RequestId(42)));
+    assert_eq!(
+        mishap
+            .downcast_ref::<std::io::Error>()
+            .map(|error| error.kind()),
+        Some(std::io::ErrorKind::TimedOut),
+    );
+    assert!(mishap.downcast_ref::<std::fmt::Error>().is_none());
+
+    // Adding a message is still a chain.
+    let mishap = Mishap::from_msg_and_anyhow("outer", anyhow!(RequestId(7)));
+    assert_eq!(mishap.downcast_ref::<RequestId>(), Some(&RequestId(7)));
+
+    // Trees don't have a single chain to downcast.
+    let mishap = mishap.wrap_mishap("wrapped");
+    assert!(mishap.downcast_ref::<RequestId>().is_none());
+}
+
+#[test]
+fn test_downcast() {
+    #[derive(Debug, PartialEq)]
+    struct ParseError {
+        line: usize,
+    }
+
+    impl std::fmt::Display for ParseError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "parse error on line {}", self.line)
+        }
+    }
+
+    impl std::error::Error for ParseError {}
+
+    let mishap = Mishap::from_error(ParseError { line: 3 });
+    assert_eq!(
+        mishap.downcast_ref::<ParseError>(),
+        Some(&ParseError { line: 3 }),
+    );
+    assert_eq!(
+        mishap.downcast::<ParseError>().unwrap(),
+        ParseError { line: 3 }
+    );
+
+    // A failed downcast returns the original mishap.
+    let mishap = Mishap::from_error(ParseError { line: 5 }).with_help("check the input");
+    let mishap = mishap.downcast::<std::io::Error>().unwrap_err();
+    assert_eq!(mishap.to_string(), "parse error on line 5");
+    assert_eq!(mishap.help(), Some("check the input"));
+    assert_eq!(
+        mishap.downcast::<ParseError>().unwrap(),
+        ParseError { line: 5 }
+    );
+
+    // Trees are returned unchanged.
+    let mishap = Mishap::from_error(ParseError { line: 7 }).wrap_mishap("wrapped");
+    let mishap = mishap.downcast::<ParseError>().unwrap_err();
+    assert_eq!(
+        mishap.display_tree().to_string(),
+        "wrapped\n\nCaused by:\n\n  - parse error on line 7\n",
+    );
+}
+
+#[test]
+fn test_clone_stringified() {
+    for mishap in [mishap_testdata::complex(), mishap_testdata::single_source()] {
+        let clone = mishap.clone_stringified();
+        assert_eq!(
+            clone.display_tree().to_string(),
+            mishap.display_tree().to_string(),
+        );
+        assert!(clone.structural_eq(&mishap));
+    }
+
+    let mishap = Mishap::from_error(std::io::Error::from(std::io::ErrorKind::NotFound))
+        .wrap_mishap("outer")
+        .with_help("try again");
+    let clone = mishap.clone_stringified();
+    assert_eq!(clone.help(), Some("try again"));
+    assert_eq!(clone.to_string(), "outer");
+    drop(mishap);
+    assert_eq!(
+        clone.display_tree().to_string(),
+        "outer\n\nCaused by:\n\n  - entity not found\n",
+    );
+}
+
+#[test]
+fn test_pop_first_source() {
+    let mishap = mishap_testdata::complex();
+    let expected_first = mishap.sources().next().unwrap().display_tree().to_string();
+    let expected_rest: Vec<_> = mishap
+        .sources()
+        .skip(1)
+        .map(|source| source.display_tree().to_string())
+        .collect();
+
+    let (root, first) = mishap.pop_first_source();
+    assert_eq!(first.unwrap().display_tree().to_string(), expected_first);
+    assert_eq!(root.to_string(), "top-level line1\ntop-level line2");
+    let rest: Vec<_> = root
+        .sources()
+        .map(|source| source.display_tree().to_string())
+        .collect();
+    assert_eq!(rest, expected_rest);
+
+    // The remaining sources can be popped one at a time, in order.
+    let (root, second) = root.pop_first_source();
+    assert_eq!(second.unwrap().display_tree().to_string(), expected_rest[0]);
+    let (root, third) = root.pop_first_source();
+    assert_eq!(third.unwrap().display_tree().to_string(), expected_rest[1]);
+    let (root, none) = root.pop_first_source();
+    assert!(none.is_none());
+    assert_eq!(
+        root.display_tree().to_string(),
+        "top-level line1\ntop-level line2\n"
+    );
+
+    let leaf = Mishap::from_msg("leaf").with_help("try again");
+    let (leaf, first) = leaf.pop_first_source();
+    assert!(first.is_none());
+    assert_eq!(leaf.to_string(), "leaf");
+    assert_eq!(leaf.help(), Some("try again"));
+}
+
+#[test]
+fn test_first_os_error() {
+    let mishap = Mishap::from_msg_and_error_trees(
+        "all operations failed",
+        [
+            Mishap::from_msg("no OS error here"),
+            Mishap::from_msg_and_error(
+                "failed to open file",
+                std::io::Error::other("not an OS error"),
+            ),
+            Mishap::from_error(std::io::Error::from_raw_os_error(2))
+                .wrap_mishap("failed to read config")
+                .wrap_mishap("failed to load settings"),
+            Mishap::from_error(std::io::Error::from_raw_os_error(13)),
+        ],
+    );
+    assert_eq!(mishap.first_os_error(), Some(2));
+
+    // Errors deep inside an anyhow chain are found too.
+    let error = anyhow::Error::new(std::io::Error::from_raw_os_error(13))
+        .context("inner")
+        .context("outer");
+    assert_eq!(Mishap::from_anyhow(error).first_os_error(), Some(13));
+
+    assert_eq!(mishap_testdata::complex().first_os_error(), None);
+}
+
+#[test]
+fn test_io_kind() {
+    let result: Result<(), _> = Err(std::io::Error::from(std::io::ErrorKind::NotFound));
+    let mishap = result
+        .wrap_error("failed to open file")
+        .wrap_error("failed to read config")
+        .wrap_error("failed to load settings")
+        .unwrap_err();
+    assert_eq!(mishap.io_kind(), Some(std::io::ErrorKind::NotFound));
+
+    let mishap = Mishap::from_error(std::io::Error::from(std::io::ErrorKind::PermissionDenied));
+    assert_eq!(mishap.io_kind(), Some(std::io::ErrorKind::PermissionDenied));
+
+    // The first io::Error in depth-first order is used, even if it isn't the first source.
+    let mishap = Mishap::from_msg_and_error_trees(
+        "all operations failed",
+        [
+            Mishap::from_msg("not an io error"),
+            Mishap::from_error(std::io::Error::from(std::io::ErrorKind::TimedOut))
+                .wrap_mishap("request failed"),
+            Mishap::from_error(std::io::Error::from(std::io::ErrorKind::NotFound)),
+        ],
+    );
+    assert_eq!(mishap.io_kind(), Some(std::io::ErrorKind::TimedOut));
+
+    assert_eq!(Mishap::from_msg("not an io error").io_kind(), None);
+}
+
+#[test]
+fn test_wrap_error_mishap() {
+    let result: Result<(), std::io::Error> = Err(std::io::Error::other("root cause"));
+    let mishap = result
+        .wrap_error("a")
+        .wrap_error("b")
+        .wrap_error_with(|| "c")
+        .unwrap_err();
+
+    // Each message is nested under the next one, rather than added as a sibling.
+    assert_tree!(mishap, "c" => { "b" => { "a" => { "root cause" } } });
+    assert_eq!(
+        mishap.display_tree().to_string(),
+        "c\n\nCaused by:\n\n  - b\n  - a\n  - root cause\n",
+    );
+}
+
+#[test]
+fn test_suggestions() {
+    let mishap = Mishap::from_msg("permission denied")
+        .with_suggestions(vec!["run with sudo".to_owned()])
+        .wrap_mishap("failed to read config")
+        .with_suggestions(vec![
+            "check the file's permissions".to_owned(),
+            "pass a different path\nwith --config".to_owned(),
+        ])
+        .with_help("see the docs");
+
+    // Suggestions from inner layers are preserved, and come first.
+    assert_eq!(
+        mishap.suggestions(),
+        [
+            "run with sudo",
+            "check the file's permissions",
+            "pass a different path\nwith --config",
+        ],
+    );
+    assert_eq!(
+        mishap.display_report().show_suggestions().to_string(),
+        "failed to read config\n\
+         \n\
+         Caused by:\n\n  \
+           - permission denied\n\
+         \n\
+         Suggestions:\n  \
+           1. run with sudo\n  \
+           2. check the file's permissions\n  \
+           3. pass a different path\n     \
+              with --config\n\
+         \n\
+         help: see the docs\n",
+    );
+
+    // Suggestions are only shown if requested.
+    assert!(!mishap.display_report().to_string().contains("Suggestions"));
+
+    let mishap = Mishap::from_msg("no suggestions");
+    assert!(mishap.suggestions().is_empty());
+    assert_eq!(
+        mishap.display_report().show_suggestions().to_string(),
+        "no suggestions\n",
+    );
+}
+
+#[test]
+fn test_from_field_errors() {
+    let mishap = Mishap::from_field_errors([
+        ("email".to_owned(), "must contain an @".to_owned()),
+        ("name".to_owned(), "must not be empty".to_owned()),
+        ("age".to_owned(), "must be a number".to_owned()),
+    ]);
+    assert_tree!(
+        mishap,
+        "validation failed" => {
+            "email: must contain an @",
+            "name: must not be empty",
+            "age: must be a number",
+        }
+    );
+
+    // With no errors, the result is a leaf.
+    let mishap = Mishap::from_field_errors(Vec::new());
+    assert_tree!(mishap, "validation failed");
+}
@@ -0,0 +1,66 @@
+use mishap::{Mishap, RetryPolicy};
+use std::{error::Error, io};
+
+fn io_error(kind: io::ErrorKind) -> io::Error {
+    io::Error::new(kind, format!("{kind:?}"))
+}
+
+#[test]
+fn test_all_transient() {
+    let mishap = Mishap::from_msg_and_error_trees(
+        "all requests failed",
+        [
+            Mishap::from_msg_and_error("request 1 failed", io_error(io::ErrorKind::TimedOut)),
+            Mishap::from_msg_and_errors(
+                "request 2 failed",
+                [
+                    io_error(io::ErrorKind::ConnectionReset),
+                    io_error(io::ErrorKind::ConnectionRefused),
+                ],
+            ),
+            Mishap::from_error(io_error(io::ErrorKind::Interrupted))
+                .wrap_mishap("request 3 failed"),
+        ],
+    );
+    assert!(mishap.is_retryable());
+
+    // A single leaf is retryable as well.
+    assert!(Mishap::from_error(io_error(io::ErrorKind::TimedOut)).is_retryable());
+}
+
+#[test]
+fn test_mixed() {
+    let mishap = Mishap::from_msg_and_error_trees(
+        "all requests failed",
+        [
+            Mishap::from_msg_and_error("request 1 failed", io_error(io::ErrorKind::TimedOut)),
+            Mishap::from_msg_and_error(
+                "request 2 failed",
+                io_error(io::ErrorKind::PermissionDenied),
+            ),
+        ],
+    );
+    assert!(!mishap.is_retryable());
+
+    // Leaves that aren't IO errors aren't retryable by default.
+    assert!(!Mishap::from_msg("timed out").is_retryable());
+}
+
+#[test]
+fn test_custom_policy() {
+    struct MessagePolicy;
+
+    impl RetryPolicy for MessagePolicy {
+        fn is_retryable(&self, error: &(dyn Error + 'static)) -> bool {
+            error.to_string() == "try again"
+        }
+    }
+
+    let mishap = Mishap::from_messages(&["request failed", "try again"]);
+    assert!(!mishap.is_retryable());
+    assert!(mishap.is_retryable_with(&MessagePolicy));
+
+    // Closures can be used as policies too.
+    let policy = |error: &(dyn Error + 'static)| error.to_string().starts_with("try");
+    assert!(mishap.is_retryable_with(&policy));
+}
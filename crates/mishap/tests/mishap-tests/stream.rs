@@ -0,0 +1,27 @@
+use err_tree::ErrorTreeExt;
+use futures::{executor::block_on, stream};
+use mishap::collect_stream_errors;
+use std::io;
+
+#[test]
+fn test_collect_stream_errors() {
+    let items = stream::iter([Ok(1), Ok(2), Ok(3)]);
+    let result = block_on(collect_stream_errors::<_, _, io::Error>(items));
+    assert_eq!(result.unwrap(), [1, 2, 3]);
+
+    let items = stream::iter([
+        Ok(1),
+        Err(io::Error::other("item 2 failed")),
+        Ok(3),
+        Err(io::Error::other("item 4 failed")),
+    ]);
+    let mishap = block_on(collect_stream_errors(items)).unwrap_err();
+    assert_eq!(
+        mishap.display_tree().to_string(),
+        "2 errors occurred\n\
+         \n\
+         Caused by:\n\n  \
+           + item 2 failed\n  \
+           + item 4 failed\n",
+    );
+}
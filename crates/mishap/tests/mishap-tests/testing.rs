@@ -0,0 +1,41 @@
+use err_tree::testing::assert_tree;
+use mishap::Mishap;
+
+#[test]
+fn test_assert_tree() {
+    let mishap = Mishap::from_msg_and_error_trees(
+        "all requests failed",
+        [
+            Mishap::from_messages(&["request 1 failed", "connection refused"]),
+            Mishap::from_msg("request 2 failed"),
+        ],
+    );
+    assert_tree!(
+        mishap,
+        "all requests failed" => {
+            "request 1 failed" => { "connection refused" },
+            "request 2 failed",
+        }
+    );
+
+    // Leaves and arbitrary expressions work as well.
+    let n = 3;
+    assert_tree!(
+        Mishap::from_msg(format!("request {n} failed")),
+        format!("request {n} failed")
+    );
+}
+
+#[test]
+#[should_panic = "error tree does not match"]
+fn test_assert_tree_mismatch() {
+    let mishap = Mishap::from_messages(&["request 1 failed", "connection refused"]);
+    assert_tree!(mishap, "request 1 failed" => { "timed out" });
+}
+
+#[test]
+#[should_panic = "error tree does not match"]
+fn test_assert_tree_structure_mismatch() {
+    let mishap = Mishap::from_messages(&["request 1 failed", "connection refused"]);
+    assert_tree!(mishap, "request 1 failed");
+}
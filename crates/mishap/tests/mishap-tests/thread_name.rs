@@ -0,0 +1,28 @@
+use mishap::Mishap;
+
+#[test]
+fn test_thread_name() {
+    let mishap = std::thread::Builder::new()
+        .name("worker-1".to_owned())
+        .spawn(|| Mishap::from_msg("inner"))
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(mishap.thread_name(), Some("worker-1"));
+
+    // Wrapping on another thread preserves the original thread name.
+    let wrapped = std::thread::Builder::new()
+        .name("worker-2".to_owned())
+        .spawn(|| mishap.wrap_mishap("outer"))
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(wrapped.thread_name(), Some("worker-1"));
+    assert!(format!("{wrapped:#?}").starts_with("thread: \"worker-1\"\n"));
+
+    let unnamed = std::thread::spawn(|| Mishap::from_msg("unnamed"))
+        .join()
+        .unwrap();
+    assert_eq!(unnamed.thread_name(), None);
+    assert!(!format!("{unnamed:#?}").contains("thread:"));
+}
@@ -0,0 +1,99 @@
+use err_tree::ErrorTreeExt;
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Level, Metadata, Subscriber,
+};
+
+/// A subscriber that records the level, depth and message of each event.
+#[derive(Clone, Default)]
+struct Capture {
+    events: Arc<Mutex<Vec<(Level, u64, String)>>>,
+}
+
+impl Subscriber for Capture {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _span: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut visitor = CaptureVisitor::default();
+        event.record(&mut visitor);
+        self.events.lock().unwrap().push((
+            *event.metadata().level(),
+            visitor.depth.expect("depth is recorded"),
+            visitor.message.expect("message is recorded"),
+        ));
+    }
+
+    fn enter(&self, _span: &span::Id) {}
+
+    fn exit(&self, _span: &span::Id) {}
+}
+
+#[derive(Default)]
+struct CaptureVisitor {
+    depth: Option<u64>,
+    message: Option<String>,
+}
+
+impl Visit for CaptureVisitor {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == "depth" {
+            self.depth = Some(value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = Some(format!("{value:?}"));
+        }
+    }
+}
+
+#[test]
+fn test_emit_tracing() {
+    let capture = Capture::default();
+    let mishap = mishap_testdata::single_source();
+    tracing::subscriber::with_default(capture.clone(), || mishap.emit_tracing(Level::WARN));
+
+    let events = capture.events.lock().unwrap();
+    assert_eq!(
+        *events,
+        [
+            (Level::WARN, 0, "mishap2 line1\nmishap2 line2".to_owned()),
+            (Level::WARN, 1, "mishap1 line1\nmishap1 line2".to_owned()),
+            (Level::WARN, 2, "anyhow error3".to_owned()),
+            (Level::WARN, 3, "anyhow error2".to_owned()),
+            (Level::WARN, 4, "anyhow error".to_owned()),
+        ],
+    );
+}
+
+#[test]
+fn test_emit_tracing_complex() {
+    let capture = Capture::default();
+    let mishap = mishap_testdata::complex();
+    tracing::subscriber::with_default(capture.clone(), || mishap.emit_tracing(Level::ERROR));
+
+    // There's one event per node, each at the requested level.
+    let events = capture.events.lock().unwrap();
+    assert_eq!(events.len(), mishap.node_count());
+    assert!(events.iter().all(|(level, _, _)| *level == Level::ERROR));
+    assert_eq!(events[0].1, 0);
+    assert_eq!(
+        events.iter().map(|(_, depth, _)| *depth).max(),
+        Some(mishap.depth() as u64),
+    );
+}
@@ -1,19 +1,156 @@
-use err_tree::{ErrorTree, ErrorTreeSource};
+use crate::ErrorTreeMetadata;
+use err_tree::{ErrorTree, ErrorTreeExt, ErrorTreeSource, NodeMetadata};
 use serde::{
     ser::{SerializeSeq, SerializeStruct},
     Serialize, Serializer,
 };
+use serde_json::Value;
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet},
+    error, fmt,
+    time::{Duration, SystemTime},
+};
 
 /// A wrapper which implements [`Serialize`] for arbitrary error trees.
 ///
-/// For a way to deserialize this format, see [`SerdeErrorTree`](crate::SerdeErrorTree).
-pub struct Ser<ET> {
+/// Each node is serialized as `{"msg": ..., "sources": [...]}`, where `"sources"` is omitted for
+/// nodes without any sources. For a way to deserialize this format, see
+/// [`SerdeErrorTree`](crate::SerdeErrorTree).
+pub struct Ser<'a, ET> {
     et: ET,
+    options: SerOptions<'a>,
+    // Set by `with_metadata`, to access the metadata of `et` without requiring
+    // `ET: ErrorTreeMetadata` everywhere.
+    as_metadata: Option<fn(&ET) -> &dyn ErrorTreeMetadata>,
 }
 
-impl<ET> Ser<ET> {
+impl<'a, ET> Ser<'a, ET> {
     pub fn new(et: ET) -> Self {
-        Self { et }
+        Self {
+            et,
+            options: SerOptions::default(),
+            as_metadata: None,
+        }
+    }
+
+    /// Only serializes sources that match a predicate on their message.
+    ///
+    /// A source is kept if its own message matches `pred`, or if any of its descendants' messages
+    /// match `pred`. Sources that are kept are serialized along with all of their kept
+    /// descendants; everything else is skipped.
+    ///
+    /// The root of the tree is always serialized, regardless of whether its message matches.
+    pub fn filter(mut self, pred: impl Fn(&str) -> bool + 'a) -> Self {
+        self.options.filter = Some(Box::new(pred));
+        self
+    }
+
+    /// Serializes repeated subtrees as references to their first occurrence.
+    ///
+    /// Before serializing, subtrees are compared by their messages and structure. Two subtrees
+    /// are the same if their roots have the same message and their sources are the same, in
+    /// order. With this option enabled:
+    ///
+    /// * The first occurrence of a subtree that is referenced later gets an additional `"id"`
+    ///   field. The id is the subtree's position in a depth-first pre-order walk of the tree, with
+    ///   the root at position 0.
+    /// * Later occurrences of the same subtree are serialized as `{"ref": <id>}`, where `<id>` is
+    ///   the id of the first occurrence.
+    ///
+    /// This can significantly reduce the size of large aggregated trees with many identical
+    /// branches. To deserialize this format, use [`dedupe::deserialize`](crate::dedupe::deserialize).
+    pub fn dedupe_subtrees(mut self) -> Self {
+        self.options.dedupe = true;
+        self
+    }
+
+    /// Adds a `"type"` field to each node, describing its shape.
+    ///
+    /// The type is one of:
+    ///
+    /// * `"leaf"` for a node with no sources.
+    /// * `"chain"` for a node with exactly one source.
+    /// * `"tree"` for a node with more than one source.
+    ///
+    /// The type is computed from the sources that are actually serialized, and acts as a tag for
+    /// consumers that represent nodes as a discriminated union. The field is ignored while
+    /// deserializing.
+    pub fn with_type_tags(mut self) -> Self {
+        self.options.type_tags = true;
+        self
+    }
+
+    /// Adds timing information recorded in each node's [`metadata`](ErrorTree::metadata).
+    ///
+    /// For nodes that recorded them, this adds:
+    ///
+    /// * `"timestamp"`: the time at which the node's operation failed, as the number of
+    ///   milliseconds since the Unix epoch.
+    /// * `"duration_ms"`: how long the node's operation took before failing, in milliseconds.
+    ///
+    /// Each field is omitted for nodes that didn't record it. Together, these fields allow
+    /// reconstructing a timeline of when each layer failed and how long it took.
+    pub fn with_timing(mut self) -> Self {
+        self.options.timing = true;
+        self
+    }
+
+    /// Adds a top-level `"rendered"` field containing the tree as displayed by
+    /// [`display_tree`](err_tree::ErrorTreeExt::display_tree).
+    ///
+    /// This makes the output both human- and machine-readable: consumers can show the rendered
+    /// form directly, or walk the structured `"sources"`. The rendered form always contains the
+    /// entire tree, even if [`filter`](Self::filter) is used. Since it repeats every message in
+    /// the tree, this roughly doubles the size of the output. The field is ignored while
+    /// deserializing.
+    pub fn with_rendered(mut self) -> Self {
+        self.options.rendered = true;
+        self
+    }
+
+    /// Adds a `"debug"` field to each node, containing the node's [`Debug`](fmt::Debug) output.
+    ///
+    /// The field is formatted with `{:?}`, alongside the [`Display`](fmt::Display) output in
+    /// `"msg"`, which can help with debugging stored errors. Since `Debug` output often includes
+    /// the node's sources, this can make the output much larger. The node that replaces sources
+    /// cut off by [`with_max_depth`](Self::with_max_depth) doesn't get this field.
+    ///
+    /// If `include` is false, which is the default, the field is omitted and the output is
+    /// unchanged. The field is ignored while deserializing.
+    pub fn with_debug(mut self, include: bool) -> Self {
+        self.options.debug = include;
+        self
+    }
+
+    /// Adds the extra metadata provided by [`ErrorTreeMetadata`] to each node.
+    ///
+    /// Each node with non-empty metadata gets an additional `"extra"` object containing it, which
+    /// is deserialized into [`SerdeErrorTree::extra`](crate::SerdeErrorTree::extra). Nodes without
+    /// metadata are serialized as usual. With [`dedupe_subtrees`](Self::dedupe_subtrees), subtrees
+    /// are still compared by their messages alone.
+    pub fn with_metadata(mut self) -> Self
+    where
+        ET: ErrorTreeMetadata,
+    {
+        self.as_metadata = Some(|et| et);
+        self
+    }
+
+    /// Limits the depth of the serialized tree.
+    ///
+    /// The root has depth 0, its direct sources have depth 1, and so on. Nodes up to `max_depth`
+    /// are serialized as usual. A node at `max_depth` that has sources gets a single
+    /// `{"msg": "... (truncated)"}` node as its only source instead, and the sources below it are
+    /// never visited. This guards against serializing huge trees, for example ones built from
+    /// untrusted input.
+    ///
+    /// [`filter`](Self::filter) and [`dedupe_subtrees`](Self::dedupe_subtrees) still inspect the
+    /// whole tree, as does [`with_rendered`](Self::with_rendered), which always renders the
+    /// entire tree.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.options.max_depth = Some(max_depth);
+        self
     }
 
     pub fn into_inner(self) -> ET {
@@ -21,98 +158,443 @@ impl<ET> Ser<ET> {
     }
 }
 
-impl<ET: ErrorTree> Serialize for Ser<ET> {
+impl<'a, ET: ErrorTree> Serialize for Ser<'a, ET> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        // Walk the tree and its sources.
+        let dedupe = self
+            .options
+            .dedupe
+            .then(|| DedupeState::new(&self.et, self.et.sources()));
+        let state = SerState {
+            options: &self.options,
+            dedupe,
+        };
+        let rendered = self
+            .options
+            .rendered
+            .then(|| self.et.display_tree().to_string());
 
-        let mut map = serializer.serialize_struct("ErrorTree", 2)?;
-        map.serialize_field("msg", &self.et.to_string())?;
-        map.serialize_field("sources", &SerSources { tree: &self.et })?;
-
-        map.end()
+        // Walk the tree and its sources.
+        let extra_metadata = self.as_metadata.map(|as_metadata| as_metadata(&self.et));
+        let sources = match extra_metadata {
+            Some(metadata) => metadata.sources_with_metadata(),
+            None => Box::new(self.et.sources().map(|source| (source, None))),
+        };
+        let node = NodeFields {
+            msg: &self.et,
+            debug: &self.et,
+            metadata: self.et.metadata(),
+            extra: extra_metadata.and_then(|metadata| metadata.extra()),
+            pos: 0,
+            depth: 0,
+            rendered: rendered.as_deref(),
+        };
+        serialize_node(serializer, node, sources, &state)
     }
 }
 
-impl<ET> From<ET> for Ser<ET> {
+impl<'a, ET> From<ET> for Ser<'a, ET> {
     fn from(et: ET) -> Self {
         Self::new(et)
     }
 }
 
-struct SerSources<ET> {
-    tree: ET,
+/// A wrapper which implements [`Serialize`] for plain [`std::error::Error`] chains.
+///
+/// The error and each of its [`source`](error::Error::source)s are serialized in the same format
+/// as [`Ser`], so the output can be deserialized into a [`SerdeErrorTree`](crate::SerdeErrorTree).
+/// Since an error has at most one source, every node in the output has at most one entry in its
+/// `"sources"`.
+///
+/// ```
+/// use serde_err_tree::SerStdError;
+///
+/// let error = std::io::Error::new(std::io::ErrorKind::Other, "disk on fire");
+/// let json = serde_json::to_string(&SerStdError::new(&error)).unwrap();
+/// assert_eq!(json, r#"{"msg":"disk on fire"}"#);
+/// ```
+#[derive(Clone, Copy)]
+pub struct SerStdError<'a> {
+    error: &'a (dyn error::Error + 'static),
 }
 
-impl<ET: ErrorTree> Serialize for SerSources<ET> {
+impl<'a> SerStdError<'a> {
+    pub fn new(error: &'a (dyn error::Error + 'static)) -> Self {
+        Self { error }
+    }
+
+    pub fn into_inner(self) -> &'a (dyn error::Error + 'static) {
+        self.error
+    }
+}
+
+impl<'a> Serialize for SerStdError<'a> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let sources = self.tree.sources();
-        let mut seq = serializer.serialize_seq(Some(sources.size_hint().0))?;
-        for source in sources {
-            seq.serialize_element(&SerSource { source })?;
+        SerSource {
+            source: ErrorTreeSource::Error(self.error),
+            extra_metadata: None,
+            pos: 0,
+            depth: 0,
+            state: &SerState {
+                options: &SerOptions::default(),
+                dedupe: None,
+            },
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'a> From<&'a (dyn error::Error + 'static)> for SerStdError<'a> {
+    fn from(error: &'a (dyn error::Error + 'static)) -> Self {
+        Self::new(error)
+    }
+}
+
+#[derive(Default)]
+struct SerOptions<'a> {
+    filter: Option<Box<FilterFn<'a>>>,
+    dedupe: bool,
+    type_tags: bool,
+    timing: bool,
+    rendered: bool,
+    debug: bool,
+    max_depth: Option<usize>,
+}
+
+type FilterFn<'a> = dyn Fn(&str) -> bool + 'a;
+
+impl<'a> SerOptions<'a> {
+    fn keep(&self, source: ErrorTreeSource<'_>) -> bool {
+        match &self.filter {
+            Some(filter) => matches_filter(filter, source),
+            None => true,
+        }
+    }
+}
+
+fn matches_filter(filter: &FilterFn<'_>, source: ErrorTreeSource<'_>) -> bool {
+    filter(&source.to_string())
+        || source
+            .sources()
+            .any(|source| matches_filter(filter, source))
+}
+
+/// State for a single serialization of a tree.
+struct SerState<'o, 'a> {
+    options: &'o SerOptions<'a>,
+    dedupe: Option<DedupeState>,
+}
+
+struct DedupeState {
+    // Indexed by each node's position in a depth-first pre-order walk of the tree.
+    nodes: Vec<NodeInfo>,
+    // Classes of subtrees that will be serialized as references at least once.
+    referenced: HashSet<usize>,
+    // The position of the first serialized node for each class.
+    seen: RefCell<HashMap<usize, usize>>,
+}
+
+#[derive(Clone, Copy)]
+struct NodeInfo {
+    // Subtrees with the same messages and structure, and only those, share a class.
+    class: usize,
+    // The number of nodes in the subtree rooted at this node, including the node itself.
+    size: usize,
+}
+
+impl DedupeState {
+    fn new<'s>(msg: &dyn fmt::Display, sources: impl Iterator<Item = ErrorTreeSource<'s>>) -> Self {
+        let mut nodes = Vec::new();
+        classify(&mut nodes, &mut HashMap::new(), msg, sources);
+
+        // Walk the tree in the same order as serialization, skipping over the contents of
+        // subtrees that will be serialized as references.
+        let mut first_seen = HashSet::new();
+        let mut referenced = HashSet::new();
+        let mut pos = 0;
+        while let Some(node) = nodes.get(pos) {
+            if first_seen.insert(node.class) {
+                pos += 1;
+            } else {
+                referenced.insert(node.class);
+                pos += node.size;
+            }
+        }
+
+        Self {
+            nodes,
+            referenced,
+            seen: RefCell::default(),
         }
-        seq.end()
     }
 }
 
-struct SerSource<'a> {
-    source: ErrorTreeSource<'a>,
+/// Assigns a class to the subtree rooted at `msg` and to each of its descendants, returning the
+/// class of the subtree.
+///
+/// `classes` maps each distinct message and list of source classes to its class. Since it's
+/// keyed on the full contents of a node rather than a hash, two subtrees only share a class if
+/// they're actually the same.
+fn classify<'s>(
+    nodes: &mut Vec<NodeInfo>,
+    classes: &mut HashMap<(String, Vec<usize>), usize>,
+    msg: &dyn fmt::Display,
+    sources: impl Iterator<Item = ErrorTreeSource<'s>>,
+) -> usize {
+    let pos = nodes.len();
+    // This is replaced once the class is known.
+    nodes.push(NodeInfo { class: 0, size: 0 });
+
+    let source_classes = sources
+        .map(|source| classify(nodes, classes, &source, source.sources()))
+        .collect();
+    let next_class = classes.len();
+    let class = *classes
+        .entry((msg.to_string(), source_classes))
+        .or_insert(next_class);
+
+    nodes[pos] = NodeInfo {
+        class,
+        size: nodes.len() - pos,
+    };
+    class
+}
+
+/// A source along with its [`ErrorTreeMetadata`], if it has any.
+type SourceWithMetadata<'s> = (ErrorTreeSource<'s>, Option<&'s dyn ErrorTreeMetadata>);
+
+/// The parts of a node that [`serialize_node`] needs, apart from its sources.
+struct NodeFields<'n> {
+    msg: &'n dyn fmt::Display,
+    debug: &'n dyn fmt::Debug,
+    metadata: Option<&'n NodeMetadata>,
+    extra: Option<&'n BTreeMap<String, Value>>,
+    // The node's position in a depth-first pre-order walk of the tree, with the root at 0.
+    pos: usize,
+    // The node's depth, with the root at 0.
+    depth: usize,
+    // The rendered tree, which is only serialized for the root.
+    rendered: Option<&'n str>,
 }
 
-impl<'a> Serialize for SerSource<'a> {
+fn serialize_node<'s, S>(
+    serializer: S,
+    node: NodeFields<'_>,
+    mut sources: impl Iterator<Item = SourceWithMetadata<'s>>,
+    state: &SerState<'_, '_>,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let NodeFields {
+        msg,
+        debug,
+        metadata,
+        extra,
+        pos,
+        depth,
+        rendered,
+    } = node;
+
+    let mut id = None;
+    if let Some(dedupe) = &state.dedupe {
+        let class = dedupe.nodes[pos].class;
+        if let Some(&first) = dedupe.seen.borrow().get(&class) {
+            let mut map = serializer.serialize_struct("ErrorTreeRef", 1)?;
+            map.serialize_field("ref", &first)?;
+            return map.end();
+        }
+        if dedupe.referenced.contains(&class) {
+            dedupe.seen.borrow_mut().insert(class, pos);
+            id = Some(pos);
+        }
+    }
+
+    let mut kept = Vec::new();
+    let truncated = if state.options.max_depth == Some(depth) {
+        // Don't walk any further than this node's direct sources.
+        sources.any(|(source, _)| state.options.keep(source))
+    } else {
+        let mut next_pos = pos + 1;
+        for (source, extra_metadata) in sources {
+            let source_pos = next_pos;
+            if let Some(dedupe) = &state.dedupe {
+                next_pos += dedupe.nodes[source_pos].size;
+            }
+            if state.options.keep(source) {
+                kept.push((source, extra_metadata, source_pos, depth + 1));
+            }
+        }
+        false
+    };
+    // A truncated node has the truncation marker as its only source.
+    let source_count = if truncated { 1 } else { kept.len() };
+
+    let node_type = state.options.type_tags.then_some(match source_count {
+        0 => "leaf",
+        1 => "chain",
+        _ => "tree",
+    });
+
+    let (timestamp, duration_ms) = match metadata {
+        Some(metadata) if state.options.timing => (
+            metadata
+                .timestamp()
+                .and_then(|timestamp| timestamp.duration_since(SystemTime::UNIX_EPOCH).ok())
+                .map(millis),
+            metadata.duration().map(millis),
+        ),
+        _ => (None, None),
+    };
+
+    let extra = extra.filter(|extra| !extra.is_empty());
+    let debug = state.options.debug.then(|| format!("{debug:?}"));
+
+    let len = 1
+        + usize::from(source_count > 0)
+        + usize::from(id.is_some())
+        + usize::from(extra.is_some())
+        + usize::from(debug.is_some())
+        + usize::from(node_type.is_some())
+        + usize::from(timestamp.is_some())
+        + usize::from(duration_ms.is_some())
+        + usize::from(rendered.is_some());
+    let mut map = serializer.serialize_struct("ErrorTree", len)?;
+    if let Some(id) = id {
+        map.serialize_field("id", &id)?;
+    }
+    if let Some(node_type) = node_type {
+        map.serialize_field("type", node_type)?;
+    }
+    if let Some(timestamp) = timestamp {
+        map.serialize_field("timestamp", &timestamp)?;
+    }
+    if let Some(duration_ms) = duration_ms {
+        map.serialize_field("duration_ms", &duration_ms)?;
+    }
+    map.serialize_field("msg", &msg.to_string())?;
+    if let Some(debug) = debug {
+        map.serialize_field("debug", &debug)?;
+    }
+    // Leaves are common, so omit their empty sources to save space.
+    if truncated {
+        map.serialize_field(
+            "sources",
+            &[Truncated {
+                type_tags: state.options.type_tags,
+            }],
+        )?;
+    } else if kept.is_empty() {
+        map.skip_field("sources")?;
+    } else {
+        map.serialize_field(
+            "sources",
+            &SerSources {
+                sources: &kept,
+                state,
+            },
+        )?;
+    }
+    if let Some(extra) = extra {
+        map.serialize_field("extra", extra)?;
+    }
+    if let Some(rendered) = rendered {
+        map.serialize_field("rendered", rendered)?;
+    }
+    map.end()
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+/// The node serialized in place of the sources of a node at the maximum depth.
+struct Truncated {
+    type_tags: bool,
+}
+
+impl Serialize for Truncated {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        match self.source {
-            ErrorTreeSource::Error(error) => SerError { error }.serialize(serializer),
-            ErrorTreeSource::Tree(tree) => Ser::new(tree).serialize(serializer),
+        let mut map = serializer.serialize_struct("ErrorTree", 1 + usize::from(self.type_tags))?;
+        if self.type_tags {
+            map.serialize_field("type", "leaf")?;
         }
+        map.serialize_field("msg", "... (truncated)")?;
+        map.skip_field("sources")?;
+        map.end()
     }
 }
 
-// TODO: worth exposing this?
-struct SerError<'a> {
-    error: &'a (dyn std::error::Error + 'static),
+struct SerSources<'s, 'o, 'a> {
+    // Each source along with its metadata, its position in the tree and its depth.
+    sources: &'s [(
+        ErrorTreeSource<'s>,
+        Option<&'s dyn ErrorTreeMetadata>,
+        usize,
+        usize,
+    )],
+    state: &'s SerState<'o, 'a>,
 }
 
-impl<'a> Serialize for SerError<'a> {
+impl<'s, 'o, 'a> Serialize for SerSources<'s, 'o, 'a> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        // Use the same serialization format as error trees with one source.
-        let mut map = serializer.serialize_struct("ErrorTree", 2)?;
-        map.serialize_field("msg", &self.error.to_string())?;
-        map.serialize_field(
-            "sources",
-            &SerErrorSources {
-                source: self.error.source(),
-            },
-        )?;
-        map.end()
+        let mut seq = serializer.serialize_seq(Some(self.sources.len()))?;
+        for &(source, extra_metadata, pos, depth) in self.sources {
+            seq.serialize_element(&SerSource {
+                source,
+                extra_metadata,
+                pos,
+                depth,
+                state: self.state,
+            })?;
+        }
+        seq.end()
     }
 }
 
-struct SerErrorSources<'a> {
-    source: Option<&'a (dyn std::error::Error + 'static)>,
+struct SerSource<'s, 'o, 'a> {
+    source: ErrorTreeSource<'s>,
+    extra_metadata: Option<&'s dyn ErrorTreeMetadata>,
+    pos: usize,
+    depth: usize,
+    state: &'s SerState<'o, 'a>,
 }
 
-impl<'a> Serialize for SerErrorSources<'a> {
+impl<'s, 'o, 'a> Serialize for SerSource<'s, 'o, 'a> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let iter = self.source.into_iter();
-        let mut seq = serializer.serialize_seq(Some(iter.size_hint().0))?;
-        if let Some(error) = self.source {
-            seq.serialize_element(&SerError { error })?;
-        }
-        seq.end()
+        // Errors and error trees share the same serialization format: an error is represented as
+        // a tree with at most one source.
+        let (debug, metadata): (&dyn fmt::Debug, _) = match self.source {
+            ErrorTreeSource::Error(error) => (error, None),
+            ErrorTreeSource::Tree(tree) => (tree, tree.metadata()),
+        };
+        let sources = match self.extra_metadata {
+            Some(extra_metadata) => extra_metadata.sources_with_metadata(),
+            None => Box::new(self.source.sources().map(|source| (source, None))),
+        };
+        let node = NodeFields {
+            msg: &self.source,
+            debug,
+            metadata,
+            extra: self.extra_metadata.and_then(|metadata| metadata.extra()),
+            pos: self.pos,
+            depth: self.depth,
+            rendered: None,
+        };
+        serialize_node(serializer, node, sources, self.state)
     }
 }
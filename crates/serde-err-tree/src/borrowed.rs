@@ -0,0 +1,73 @@
+use crate::SerdeErrorTree;
+use err_tree::{ErrorTree, ErrorTreeSource};
+use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, fmt};
+
+/// A variant of [`SerdeErrorTree`] that borrows its messages from the input where possible.
+///
+/// Deserializing a message that doesn't contain any escape sequences doesn't allocate: it's stored
+/// as a [`Cow::Borrowed`] slice of the input. This makes ingesting large numbers of trees
+/// cheaper. The serialization format is the same as for [`SerdeErrorTree`].
+///
+/// [`ErrorTree`] requires sources to be `'static`, so it's only implemented for
+/// `BorrowedErrorTree<'static>`. Use [`into_owned`](Self::into_owned) to convert a tree that
+/// borrows from a shorter-lived buffer.
+///
+/// ```
+/// use serde_err_tree::BorrowedErrorTree;
+/// use std::borrow::Cow;
+///
+/// let json = r#"{"msg":"outer","sources":[{"msg":"inner","sources":[]}]}"#;
+/// let tree: BorrowedErrorTree<'_> = serde_json::from_str(json).unwrap();
+/// assert!(matches!(tree.sources[0].msg, Cow::Borrowed("inner")));
+/// ```
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BorrowedErrorTree<'a> {
+    /// The message for this node in the error tree.
+    #[serde(borrow)]
+    pub msg: Cow<'a, str>,
+
+    /// The sources of this node.
+    #[serde(borrow)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<BorrowedErrorTree<'a>>,
+}
+
+impl<'a> BorrowedErrorTree<'a> {
+    /// Converts this tree into one that owns all of its messages.
+    pub fn into_owned(self) -> BorrowedErrorTree<'static> {
+        BorrowedErrorTree {
+            msg: Cow::Owned(self.msg.into_owned()),
+            sources: self
+                .sources
+                .into_iter()
+                .map(BorrowedErrorTree::into_owned)
+                .collect(),
+        }
+    }
+}
+
+impl<'a> From<BorrowedErrorTree<'a>> for SerdeErrorTree {
+    fn from(tree: BorrowedErrorTree<'a>) -> Self {
+        SerdeErrorTree::from_msg_and_sources(
+            tree.msg,
+            tree.sources.into_iter().map(SerdeErrorTree::from).collect(),
+        )
+    }
+}
+
+impl<'a> fmt::Display for BorrowedErrorTree<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.msg)
+    }
+}
+
+impl ErrorTree for BorrowedErrorTree<'static> {
+    fn sources(&self) -> Box<dyn Iterator<Item = ErrorTreeSource<'_>> + '_> {
+        Box::new(
+            self.sources
+                .iter()
+                .map(|source| ErrorTreeSource::Tree(source)),
+        )
+    }
+}
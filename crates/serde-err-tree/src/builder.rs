@@ -0,0 +1,171 @@
+use crate::SerdeErrorTree;
+use std::fmt;
+
+/// Incrementally builds a [`SerdeErrorTree`], enforcing limits on its size as nodes are added.
+///
+/// This is useful when a tree is assembled from untrusted input, such as messages received one
+/// at a time over a streaming protocol: an over-large tree is rejected as soon as it exceeds a
+/// limit, rather than after it has been fully built.
+///
+/// ```
+/// use serde_err_tree::SerdeErrorTreeBuilder;
+///
+/// let mut builder = SerdeErrorTreeBuilder::new("root").max_nodes(3);
+/// let root = builder.root();
+/// let child = builder.push_child(root, "child").unwrap();
+/// builder.push_child(child, "grandchild").unwrap();
+///
+/// // The tree already has 3 nodes.
+/// assert!(builder.push_child(root, "one too many").is_err());
+///
+/// let tree = builder.build();
+/// assert_eq!(tree.sources[0].sources[0].msg, "grandchild");
+/// ```
+#[derive(Clone, Debug)]
+pub struct SerdeErrorTreeBuilder {
+    nodes: Vec<Node>,
+    max_nodes: usize,
+    max_depth: usize,
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    msg: String,
+    depth: usize,
+    children: Vec<usize>,
+}
+
+impl SerdeErrorTreeBuilder {
+    /// Creates a new builder whose tree consists of a single root with the given message.
+    ///
+    /// By default, there are no limits on the size of the tree.
+    pub fn new(root_msg: impl Into<String>) -> Self {
+        Self {
+            nodes: vec![Node {
+                msg: root_msg.into(),
+                depth: 0,
+                children: Vec::new(),
+            }],
+            max_nodes: usize::MAX,
+            max_depth: usize::MAX,
+        }
+    }
+
+    /// Sets the maximum number of nodes in the tree, including the root.
+    #[inline]
+    pub fn max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = max_nodes;
+        self
+    }
+
+    /// Sets the maximum depth of any node in the tree. The root has depth 0.
+    #[inline]
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Returns the id of the root node.
+    #[inline]
+    pub fn root(&self) -> BuilderNodeId {
+        BuilderNodeId(0)
+    }
+
+    /// Returns the number of nodes added so far, including the root.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Always returns false, since a tree always has a root.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Adds a new child with the given message as the last source of `parent`, and returns its
+    /// id.
+    ///
+    /// Returns an error, leaving the tree unchanged, if adding the node would exceed a limit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `parent` was not returned by this builder.
+    pub fn push_child(
+        &mut self,
+        parent: BuilderNodeId,
+        msg: impl Into<String>,
+    ) -> Result<BuilderNodeId, BuildError> {
+        let depth = self
+            .nodes
+            .get(parent.0)
+            .expect("parent was returned by this builder")
+            .depth
+            + 1;
+        if self.nodes.len() >= self.max_nodes {
+            return Err(BuildError::TooManyNodes {
+                max_nodes: self.max_nodes,
+            });
+        }
+        if depth > self.max_depth {
+            return Err(BuildError::TooDeep {
+                max_depth: self.max_depth,
+            });
+        }
+
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            msg: msg.into(),
+            depth,
+            children: Vec::new(),
+        });
+        self.nodes[parent.0].children.push(index);
+        Ok(BuilderNodeId(index))
+    }
+
+    /// Finishes building the tree.
+    pub fn build(self) -> SerdeErrorTree {
+        let nodes = self
+            .nodes
+            .into_iter()
+            .map(|node| (node.msg, node.children))
+            .collect();
+        SerdeErrorTree::from_nodes(nodes, 0)
+    }
+}
+
+/// Identifies a node added to a [`SerdeErrorTreeBuilder`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct BuilderNodeId(usize);
+
+/// An error returned by [`SerdeErrorTreeBuilder::push_child`] when a limit would be exceeded.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum BuildError {
+    /// The tree already has the maximum number of nodes.
+    TooManyNodes {
+        /// The maximum number of nodes.
+        max_nodes: usize,
+    },
+
+    /// The new node would be deeper than the maximum depth.
+    TooDeep {
+        /// The maximum depth.
+        max_depth: usize,
+    },
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::TooManyNodes { max_nodes } => {
+                write!(f, "tree would exceed the maximum of {max_nodes} nodes")
+            }
+            BuildError::TooDeep { max_depth } => {
+                write!(f, "tree would exceed the maximum depth of {max_depth}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
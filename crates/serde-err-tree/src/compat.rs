@@ -0,0 +1,52 @@
+//! Helpers for exchanging serialized trees between different versions of the format.
+//!
+//! The base format is `{"msg": ..., "sources": [...]}`, where `"sources"` may be omitted for
+//! leaves. Options on [`Ser`](crate::Ser) add
+//! optional fields on top of it, such as `"type"`, `"timestamp"`, `"debug"` or `"rendered"`, and
+//! [`Ser::dedupe_subtrees`](crate::Ser::dedupe_subtrees) replaces repeated subtrees with
+//! references. Consumers that only understand the base format can use [`normalize`] to read
+//! trees produced by newer writers.
+
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Downgrades a serialized tree to the base `{"msg": ..., "sources": [...]}` shape, in place.
+///
+/// For every node in the tree:
+///
+/// * All fields other than `"msg"` and `"sources"` are removed.
+/// * References to repeated subtrees (`{"ref": <id>}`) are replaced with a copy of the subtree
+///   they refer to.
+///
+/// A normalized tree can be deserialized into a [`SerdeErrorTree`](crate::SerdeErrorTree).
+/// Values that aren't objects are left alone, as are references to ids that don't appear
+/// earlier in the tree.
+pub fn normalize(value: &mut Value) {
+    let mut ids = HashMap::new();
+    normalize_node(value, &mut ids);
+}
+
+fn normalize_node(value: &mut Value, ids: &mut HashMap<u64, Value>) {
+    let Value::Object(map) = value else {
+        return;
+    };
+
+    if let Some(id) = map.get("ref").and_then(Value::as_u64) {
+        if let Some(subtree) = ids.get(&id) {
+            *value = subtree.clone();
+        }
+        return;
+    }
+
+    let id = map.get("id").and_then(Value::as_u64);
+    map.retain(|key, _| key == "msg" || key == "sources");
+    if let Some(Value::Array(sources)) = map.get_mut("sources") {
+        for source in sources {
+            normalize_node(source, ids);
+        }
+    }
+
+    if let Some(id) = id {
+        ids.insert(id, Value::Object(map.clone()));
+    }
+}
@@ -0,0 +1,81 @@
+//! Support for deserializing trees serialized with [`Ser::dedupe_subtrees`](crate::Ser::dedupe_subtrees).
+//!
+//! In this format, a subtree that occurs more than once is serialized in full the first time it
+//! occurs, with an additional `"id"` field. Each later occurrence is replaced with a reference to
+//! the first one:
+//!
+//! ```json
+//! {
+//!   "msg": "top-level",
+//!   "sources": [
+//!     { "id": 1, "msg": "connection refused" },
+//!     { "ref": 1 }
+//!   ]
+//! }
+//! ```
+
+use crate::SerdeErrorTree;
+use serde::{de::Error, Deserialize, Deserializer};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// Deserializes a tree serialized with [`Ser::dedupe_subtrees`](crate::Ser::dedupe_subtrees),
+/// expanding each reference into a full copy of the subtree it refers to.
+///
+/// Trees serialized without deduplication are accepted as well.
+///
+/// This can be used with `#[serde(deserialize_with = "serde_err_tree::dedupe::deserialize")]`.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<SerdeErrorTree, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let node = DedupedNode::deserialize(deserializer)?;
+    let mut resolved = HashMap::new();
+    resolve(node, &mut resolved).map_err(D::Error::custom)
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DedupedNode {
+    Ref {
+        #[serde(rename = "ref")]
+        id: usize,
+    },
+    Tree {
+        #[serde(default)]
+        id: Option<usize>,
+        msg: String,
+        #[serde(default)]
+        sources: Vec<DedupedNode>,
+        #[serde(default)]
+        extra: BTreeMap<String, Value>,
+    },
+}
+
+fn resolve(
+    node: DedupedNode,
+    resolved: &mut HashMap<usize, SerdeErrorTree>,
+) -> Result<SerdeErrorTree, String> {
+    match node {
+        DedupedNode::Ref { id } => resolved
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| format!("reference to unknown id {id}")),
+        DedupedNode::Tree {
+            id,
+            msg,
+            sources,
+            extra,
+        } => {
+            let sources = sources
+                .into_iter()
+                .map(|source| resolve(source, resolved))
+                .collect::<Result<_, _>>()?;
+            let tree = SerdeErrorTree::from_msg_and_sources(msg, sources).with_extra(extra);
+            if let Some(id) = id {
+                resolved.insert(id, tree.clone());
+            }
+            Ok(tree)
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use crate::{Ser, SerdeErrorTree};
+use err_tree::{ErrorTree, ErrorTreeExt, ErrorTreeSource};
+
+/// Extension trait for [`ErrorTree`] with methods that depend on serialization.
+///
+/// This complements [`ErrorTreeExt`], which can't depend on `serde` itself.
+pub trait ErrorTreeSerdeExt: ErrorTree {
+    /// Renders this tree as both human-readable text and compact JSON.
+    ///
+    /// Returns `(text, json)`, where `text` is the output of
+    /// [`display_tree`](ErrorTreeExt::display_tree) and `json` is the output of [`Ser`]
+    /// serialized with [`serde_json::to_string`]. This is a convenience for workflows that both
+    /// log an error and store it: internally, the tree is still walked twice, once for each
+    /// output.
+    fn render_both(&self) -> (String, String) {
+        let text = self.display_tree().to_string();
+        let json =
+            serde_json::to_string(&Ser::new(self)).expect("serializing an error tree never fails");
+        (text, json)
+    }
+
+    /// Creates a copy of this tree that only contains the sources for which `keep` returns true.
+    ///
+    /// `keep` is called for each source in depth-first pre-order. When it returns false, the
+    /// source is dropped along with all of its descendants, which `keep` isn't called for. The
+    /// root is always kept. This is useful for dropping noisy subtrees before logging a tree.
+    fn to_pruned<F>(&self, keep: F) -> SerdeErrorTree
+    where
+        F: Fn(&ErrorTreeSource<'_>) -> bool,
+    {
+        SerdeErrorTree::from_msg_and_sources(self.to_string(), pruned(self.sources(), &keep))
+    }
+}
+
+fn pruned<'a>(
+    sources: impl Iterator<Item = ErrorTreeSource<'a>>,
+    keep: &dyn Fn(&ErrorTreeSource<'_>) -> bool,
+) -> Vec<SerdeErrorTree> {
+    sources
+        .filter(|source| keep(source))
+        .map(|source| {
+            SerdeErrorTree::from_msg_and_sources(source.to_string(), pruned(source.sources(), keep))
+        })
+        .collect()
+}
+
+impl<T: ErrorTree + ?Sized> ErrorTreeSerdeExt for T {}
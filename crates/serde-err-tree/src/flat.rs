@@ -0,0 +1,179 @@
+use crate::SerdeErrorTree;
+use err_tree::{ErrorTree, ErrorTreeExt};
+use serde::{ser::SerializeSeq, Deserialize, Serialize, Serializer};
+use std::fmt;
+
+/// An error tree represented as a flat list of nodes, each with the index of its parent.
+///
+/// This is convenient for columnar storage, where nested structures are awkward to represent. It
+/// serializes as a plain list:
+///
+/// ```json
+/// [
+///   { "msg": "top-level", "parent": null },
+///   { "msg": "first source", "parent": 0 },
+///   { "msg": "second source", "parent": 0 }
+/// ]
+/// ```
+///
+/// To serialize an error tree in this format without first building the list, use
+/// [`SerFlat`]. To turn the list back into a nested tree, use [`SerdeErrorTree::from_flat`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct FlatErrorTree {
+    /// The nodes of the tree, in depth-first pre-order when created from an error tree.
+    pub nodes: Vec<FlatNode>,
+}
+
+impl FlatErrorTree {
+    /// Creates a new flat tree from an arbitrary error tree.
+    ///
+    /// Nodes are listed in depth-first pre-order, so the root is at index 0, and each node's
+    /// parent comes before it.
+    pub fn new<ET: ErrorTree>(tree: ET) -> Self {
+        let mut nodes = Vec::new();
+        walk_flat(&tree, |msg, parent| {
+            nodes.push(FlatNode { msg, parent });
+            Ok::<_, std::convert::Infallible>(())
+        })
+        .unwrap_or_else(|never| match never {});
+        Self { nodes }
+    }
+}
+
+/// A single node of a [`FlatErrorTree`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FlatNode {
+    /// The message for this node in the error tree.
+    pub msg: String,
+
+    /// The index of this node's parent, or `None` for the root.
+    pub parent: Option<usize>,
+}
+
+/// A wrapper which serializes arbitrary error trees in the format of [`FlatErrorTree`].
+///
+/// Nodes are serialized as they're visited, without building a [`FlatErrorTree`] first.
+pub struct SerFlat<ET> {
+    et: ET,
+}
+
+impl<ET> SerFlat<ET> {
+    pub fn new(et: ET) -> Self {
+        Self { et }
+    }
+
+    pub fn into_inner(self) -> ET {
+        self.et
+    }
+}
+
+impl<ET: ErrorTree> Serialize for SerFlat<ET> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.et.node_count()))?;
+        walk_flat(&self.et, |msg, parent| {
+            seq.serialize_element(&FlatNode { msg, parent })
+        })?;
+        seq.end()
+    }
+}
+
+impl<ET> From<ET> for SerFlat<ET> {
+    fn from(et: ET) -> Self {
+        Self::new(et)
+    }
+}
+
+/// Calls `f` with the message and parent index of each node of `tree`, in depth-first pre-order.
+fn walk_flat<E>(
+    tree: &dyn ErrorTree,
+    mut f: impl FnMut(String, Option<usize>) -> Result<(), E>,
+) -> Result<(), E> {
+    f(tree.to_string(), None)?;
+
+    let mut next_index = 1;
+    let mut stack: Vec<_> = tree.sources().map(|source| (source, 0)).collect();
+    // Sources are collected in order, so reverse them to pop the first one next.
+    stack.reverse();
+    while let Some((source, parent)) = stack.pop() {
+        let index = next_index;
+        next_index += 1;
+        f(source.to_string(), Some(parent))?;
+
+        let len = stack.len();
+        stack.extend(source.sources().map(|source| (source, index)));
+        stack[len..].reverse();
+    }
+
+    Ok(())
+}
+
+impl SerdeErrorTree {
+    /// Reconstructs a nested tree from a [`FlatErrorTree`].
+    ///
+    /// The first node must be the root, and every other node's parent must come before it in the
+    /// list. Nodes don't have to be in depth-first order: the sources of each node are ordered by
+    /// their positions in the list.
+    pub fn from_flat(flat: FlatErrorTree) -> Result<Self, FromFlatError> {
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); flat.nodes.len()];
+        for (index, node) in flat.nodes.iter().enumerate() {
+            match (index, node.parent) {
+                (0, None) => {}
+                (0, Some(parent)) => return Err(FromFlatError::InvalidParent { index, parent }),
+                (_, None) => return Err(FromFlatError::MultipleRoots { index }),
+                (_, Some(parent)) if parent >= index => {
+                    return Err(FromFlatError::InvalidParent { index, parent });
+                }
+                (_, Some(parent)) => children[parent].push(index),
+            }
+        }
+        if flat.nodes.is_empty() {
+            return Err(FromFlatError::Empty);
+        }
+
+        let nodes = flat.nodes.into_iter().map(|node| node.msg).zip(children);
+        Ok(Self::from_nodes(nodes.collect(), 0))
+    }
+}
+
+/// An error returned by [`SerdeErrorTree::from_flat`] when the list doesn't describe a tree.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum FromFlatError {
+    /// The list has no nodes.
+    Empty,
+
+    /// A node's parent doesn't come before it in the list.
+    InvalidParent {
+        /// The index of the node.
+        index: usize,
+
+        /// The index of its parent.
+        parent: usize,
+    },
+
+    /// A node other than the first has no parent.
+    MultipleRoots {
+        /// The index of the node.
+        index: usize,
+    },
+}
+
+impl fmt::Display for FromFlatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FromFlatError::Empty => write!(f, "flat tree has no nodes"),
+            FromFlatError::InvalidParent { index, parent } => {
+                write!(f, "parent {parent} of node {index} does not come before it",)
+            }
+            FromFlatError::MultipleRoots { index } => {
+                write!(f, "node {index} is a second root")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FromFlatError {}
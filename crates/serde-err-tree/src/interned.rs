@@ -0,0 +1,94 @@
+use crate::{Ser, SerdeErrorTree};
+use err_tree::{ErrorTree, ErrorTreeSource};
+use serde::Serialize;
+use std::{collections::HashSet, fmt, sync::Arc};
+
+/// A set of shared strings, used to deduplicate messages across error trees.
+///
+/// See [`SerdeErrorTree::intern`].
+#[derive(Clone, Debug, Default)]
+pub struct StringInterner {
+    strings: HashSet<Arc<str>>,
+}
+
+impl StringInterner {
+    /// Creates a new, empty interner.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a shared copy of `s`, reusing an existing one if `s` was interned before.
+    pub fn intern(&mut self, s: &str) -> Arc<str> {
+        if let Some(existing) = self.strings.get(s) {
+            return existing.clone();
+        }
+        let s: Arc<str> = Arc::from(s);
+        self.strings.insert(s.clone());
+        s
+    }
+
+    /// Returns the number of distinct strings interned so far.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Returns true if no strings have been interned.
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+/// A variant of [`SerdeErrorTree`] whose messages are shared [`Arc<str>`]s.
+///
+/// Created with [`SerdeErrorTree::intern`]. When many trees contain the same messages, interning
+/// them with the same [`StringInterner`] means each distinct message is only stored once.
+///
+/// The serialization format is the same as for [`SerdeErrorTree`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InternedErrorTree {
+    /// The message for this node in the error tree.
+    pub msg: Arc<str>,
+
+    /// The sources of this node.
+    pub sources: Vec<InternedErrorTree>,
+}
+
+impl SerdeErrorTree {
+    /// Creates a copy of this tree with each message replaced by a shared string from
+    /// `interner`.
+    pub fn intern(&self, interner: &mut StringInterner) -> InternedErrorTree {
+        InternedErrorTree {
+            msg: interner.intern(&self.msg),
+            sources: self
+                .sources
+                .iter()
+                .map(|source| source.intern(interner))
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for InternedErrorTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.msg)
+    }
+}
+
+impl ErrorTree for InternedErrorTree {
+    fn sources(&self) -> Box<dyn Iterator<Item = ErrorTreeSource<'_>> + '_> {
+        Box::new(
+            self.sources
+                .iter()
+                .map(|source| ErrorTreeSource::Tree(source)),
+        )
+    }
+}
+
+impl Serialize for InternedErrorTree {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        Ser::new(self).serialize(serializer)
+    }
+}
@@ -2,9 +2,33 @@
 //!
 //! It's often useful to be able to serialize error trees over the wire, or to store them in a
 //! database. This crate provides a way to do that using [`serde`].
+//!
+//! # Optional features
+//!
+//! * `schemars`: implements `schemars::JsonSchema` for [`SerdeErrorTree`], so that a JSON Schema
+//!   describing the format can be generated, for example for an OpenAPI document. The recursive
+//!   `sources` field refers back to the tree's own definition. Not enabled by default.
 
 mod adapter;
+mod borrowed;
+mod builder;
+pub mod compat;
+pub mod dedupe;
+mod ext;
+mod flat;
+mod interned;
+mod metadata;
+pub mod ndjson;
+mod spanned;
+pub mod tracing_json;
 mod tree;
 
 pub use adapter::*;
+pub use borrowed::*;
+pub use builder::*;
+pub use ext::*;
+pub use flat::*;
+pub use interned::*;
+pub use metadata::*;
+pub use spanned::*;
 pub use tree::*;
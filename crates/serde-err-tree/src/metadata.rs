@@ -0,0 +1,42 @@
+use err_tree::{ErrorTree, ErrorTreeSource};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// An [`ErrorTree`] with extra metadata attached to its nodes, such as error codes.
+///
+/// With [`Ser::with_metadata`](crate::Ser::with_metadata), each node's metadata is serialized as
+/// an `"extra"` object, which [`SerdeErrorTree`](crate::SerdeErrorTree) deserializes into its
+/// [`extra`](crate::SerdeErrorTree::extra) field.
+///
+/// The sources returned by [`ErrorTree::sources`] are type-erased, so metadata for the rest of the
+/// tree is found through [`sources_with_metadata`](Self::sources_with_metadata) instead.
+pub trait ErrorTreeMetadata: ErrorTree {
+    /// Returns the extra metadata for this node, if any.
+    ///
+    /// Empty metadata is treated the same as `None`.
+    fn extra(&self) -> Option<&BTreeMap<String, Value>>;
+
+    /// Returns the sources of this node, each along with its metadata if it has any.
+    ///
+    /// The sources must be the same ones, in the same order, as those returned by
+    /// [`ErrorTree::sources`]. The default implementation returns them without any metadata.
+    fn sources_with_metadata(
+        &self,
+    ) -> Box<dyn Iterator<Item = (ErrorTreeSource<'_>, Option<&dyn ErrorTreeMetadata>)> + '_> {
+        Box::new(self.sources().map(|source| (source, None)))
+    }
+}
+
+impl<T: ErrorTreeMetadata + ?Sized> ErrorTreeMetadata for &T {
+    #[inline]
+    fn extra(&self) -> Option<&BTreeMap<String, Value>> {
+        (**self).extra()
+    }
+
+    #[inline]
+    fn sources_with_metadata(
+        &self,
+    ) -> Box<dyn Iterator<Item = (ErrorTreeSource<'_>, Option<&dyn ErrorTreeMetadata>)> + '_> {
+        (**self).sources_with_metadata()
+    }
+}
@@ -0,0 +1,260 @@
+//! Support for a flat, newline-delimited JSON (NDJSON) representation of error trees.
+//!
+//! In this format, each node of the tree is written on its own line, in depth-first pre-order:
+//!
+//! ```text
+//! {"id":0,"parent":null,"depth":0,"msg":"top-level"}
+//! {"id":1,"parent":0,"depth":1,"msg":"first source"}
+//! {"id":2,"parent":0,"depth":1,"msg":"second source"}
+//! ```
+//!
+//! * `id` is an identifier for the node, unique within the tree.
+//! * `parent` is the `id` of the node's parent, or `null` for the root.
+//! * `depth` is the distance from the root, which has depth 0.
+//! * `msg` is the message for the node.
+//!
+//! This format is convenient for line-oriented tools and log pipelines.
+
+use crate::SerdeErrorTree;
+use err_tree::{ErrorTree, ErrorTreeSource};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt};
+
+/// Serializes an error tree into NDJSON.
+///
+/// Nodes are written in depth-first pre-order, with ids assigned sequentially starting from 0 at
+/// the root. Each line, including the last one, is terminated by a newline.
+pub fn to_ndjson<ET: ErrorTree>(tree: ET) -> String {
+    let mut out = String::new();
+    push_line(&mut out, 0, None, 0, &tree);
+
+    let mut next_id = 1;
+    // Push sources in reverse order so that they're popped in order.
+    let mut stack: Vec<_> = reversed(tree.sources()).map(|s| (s, 0, 1)).collect();
+    while let Some((source, parent, depth)) = stack.pop() {
+        let id = next_id;
+        next_id += 1;
+        push_line(&mut out, id, Some(parent), depth, &source);
+        stack.extend(reversed(source.sources()).map(|s| (s, id, depth + 1)));
+    }
+
+    out
+}
+
+/// Parses NDJSON produced by [`to_ndjson`] back into a [`SerdeErrorTree`].
+///
+/// Lines don't have to be in depth-first order, but each node's parent must appear on an earlier
+/// line than the node itself. The sources of each node are ordered by the lines they appear on.
+/// Blank lines are ignored.
+///
+/// Returns an error if a line is invalid, or if the lines don't describe exactly one tree.
+pub fn from_ndjson(s: &str) -> Result<SerdeErrorTree, ParseError> {
+    struct Node {
+        msg: String,
+        depth: usize,
+        children: Vec<usize>,
+    }
+
+    let mut nodes: Vec<Node> = Vec::new();
+    let mut indexes = HashMap::new();
+    let mut root = None;
+
+    for (line_index, line) in s.lines().enumerate() {
+        let line_number = line_index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let NdjsonLine {
+            id,
+            parent,
+            depth,
+            msg,
+        } = serde_json::from_str(line).map_err(|error| ParseError::Json {
+            line: line_number,
+            error,
+        })?;
+
+        let index = nodes.len();
+        if indexes.insert(id, index).is_some() {
+            return Err(ParseError::DuplicateId {
+                line: line_number,
+                id,
+            });
+        }
+
+        let expected_depth = match parent {
+            Some(parent) => {
+                let parent_index = *indexes.get(&parent).ok_or(ParseError::MissingParent {
+                    line: line_number,
+                    id,
+                    parent,
+                })?;
+                nodes[parent_index].children.push(index);
+                nodes[parent_index].depth + 1
+            }
+            None => {
+                if root.replace(index).is_some() {
+                    return Err(ParseError::MultipleRoots {
+                        line: line_number,
+                        id,
+                    });
+                }
+                0
+            }
+        };
+        if depth != expected_depth {
+            return Err(ParseError::InvalidDepth {
+                line: line_number,
+                id,
+                depth,
+                expected: expected_depth,
+            });
+        }
+
+        nodes.push(Node {
+            msg,
+            depth,
+            children: Vec::new(),
+        });
+    }
+
+    let root = root.ok_or(ParseError::NoRoot)?;
+
+    let nodes = nodes
+        .into_iter()
+        .map(|node| (node.msg, node.children))
+        .collect();
+    Ok(SerdeErrorTree::from_nodes(nodes, root))
+}
+
+/// An error that occurred while parsing NDJSON with [`from_ndjson`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// A line could not be parsed as a node.
+    Json {
+        /// The line number, starting from 1.
+        line: usize,
+
+        /// The underlying error.
+        error: serde_json::Error,
+    },
+
+    /// A node has the same id as an earlier node.
+    DuplicateId {
+        /// The line number, starting from 1.
+        line: usize,
+
+        /// The duplicated id.
+        id: usize,
+    },
+
+    /// A node's parent does not appear on an earlier line.
+    MissingParent {
+        /// The line number, starting from 1.
+        line: usize,
+
+        /// The id of the node.
+        id: usize,
+
+        /// The id of the missing parent.
+        parent: usize,
+    },
+
+    /// A node's depth is inconsistent with its parent's depth.
+    InvalidDepth {
+        /// The line number, starting from 1.
+        line: usize,
+
+        /// The id of the node.
+        id: usize,
+
+        /// The depth recorded for the node.
+        depth: usize,
+
+        /// The depth implied by the node's parent.
+        expected: usize,
+    },
+
+    /// More than one node has no parent.
+    MultipleRoots {
+        /// The line number of the second root, starting from 1.
+        line: usize,
+
+        /// The id of the second root.
+        id: usize,
+    },
+
+    /// No node without a parent was found (for example, because the input was empty).
+    NoRoot,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Json { line, .. } => write!(f, "line {line}: invalid node"),
+            ParseError::DuplicateId { line, id } => {
+                write!(f, "line {line}: duplicate id {id}")
+            }
+            ParseError::MissingParent { line, id, parent } => write!(
+                f,
+                "line {line}: parent {parent} of node {id} does not appear on an earlier line",
+            ),
+            ParseError::InvalidDepth {
+                line,
+                id,
+                depth,
+                expected,
+            } => write!(
+                f,
+                "line {line}: node {id} has depth {depth}, but its parent implies depth {expected}",
+            ),
+            ParseError::MultipleRoots { line, id } => {
+                write!(f, "line {line}: node {id} is a second root")
+            }
+            ParseError::NoRoot => write!(f, "no root node found"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::Json { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct NdjsonLine {
+    id: usize,
+    parent: Option<usize>,
+    depth: usize,
+    msg: String,
+}
+
+fn push_line(
+    out: &mut String,
+    id: usize,
+    parent: Option<usize>,
+    depth: usize,
+    msg: &dyn fmt::Display,
+) {
+    let line = NdjsonLine {
+        id,
+        parent,
+        depth,
+        msg: msg.to_string(),
+    };
+    // Serializing a struct with string keys into a string can't fail.
+    out.push_str(&serde_json::to_string(&line).expect("serialization succeeded"));
+    out.push('\n');
+}
+
+fn reversed<'a>(
+    sources: impl Iterator<Item = ErrorTreeSource<'a>>,
+) -> impl Iterator<Item = ErrorTreeSource<'a>> {
+    sources.collect::<Vec<_>>().into_iter().rev()
+}
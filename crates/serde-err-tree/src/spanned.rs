@@ -0,0 +1,109 @@
+use err_tree::{ErrorTree, ErrorTreeSource};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// An owned error tree where each node can carry a span of some source text.
+///
+/// This is useful for errors produced by parsers, where each error relates to a location in an
+/// input file. Spans are byte ranges into the input, as most parsers report them. To display them
+/// as `(line:column)` after each node's message, call [`locate`](Self::locate) with the input.
+///
+/// The serialization format is the same as the one used by [`SerdeErrorTree`](crate::SerdeErrorTree),
+/// with an additional `"span": [start, end]` field on nodes that have a span. Lines and columns
+/// aren't serialized, so call `locate` again after deserializing a tree.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SpannedErrorTree {
+    /// The message for this node in the error tree.
+    pub msg: String,
+
+    /// The span of source text associated with this node, as a `(start, end)` pair of byte
+    /// offsets. The end is exclusive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<(usize, usize)>,
+
+    /// The `(line, column)` at which this node's span starts, both 1-based.
+    ///
+    /// This is computed from the span by [`locate`](Self::locate), and is not serialized.
+    #[serde(skip)]
+    pub position: Option<(usize, usize)>,
+
+    /// The sources of this node.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<SpannedErrorTree>,
+}
+
+impl SpannedErrorTree {
+    /// Creates a new node with the given message, no span, and no sources.
+    pub fn new(msg: impl Into<String>) -> Self {
+        Self {
+            msg: msg.into(),
+            span: None,
+            position: None,
+            sources: Vec::new(),
+        }
+    }
+
+    /// Sets the span for this node, as a range of byte offsets from `start` up to but not
+    /// including `end`.
+    pub fn with_span(mut self, start: usize, end: usize) -> Self {
+        self.span = Some((start, end));
+        self
+    }
+
+    /// Adds a source to this node.
+    pub fn with_source(mut self, source: SpannedErrorTree) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// Computes the [`position`](Self::position) of every node in this tree that has a span,
+    /// using `text` as the source text that the spans refer to.
+    ///
+    /// Columns are counted in characters. A span that starts past the end of `text` is treated as
+    /// starting at the end.
+    pub fn locate(&mut self, text: &str) {
+        if let Some((start, _)) = self.span {
+            self.position = Some(position(text, start));
+        }
+        for source in &mut self.sources {
+            source.locate(text);
+        }
+    }
+}
+
+impl fmt::Display for SpannedErrorTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.msg)?;
+        if let Some((line, column)) = self.position {
+            write!(f, " ({line}:{column})")?;
+        }
+        Ok(())
+    }
+}
+
+impl ErrorTree for SpannedErrorTree {
+    fn sources(&self) -> Box<dyn Iterator<Item = ErrorTreeSource<'_>> + '_> {
+        Box::new(
+            self.sources
+                .iter()
+                .map(|error| ErrorTreeSource::Tree(error)),
+        )
+    }
+}
+
+/// Returns the 1-based line and column of the byte `offset` in `text`.
+fn position(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text.as_bytes()[..offset.min(text.len())];
+    let line = before.iter().filter(|&&b| b == b'\n').count() + 1;
+    let line_start = before
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |newline| newline + 1);
+    // Count the characters that start before the offset, in case it's in the middle of one.
+    let column = text[line_start..]
+        .char_indices()
+        .take_while(|&(index, _)| line_start + index < offset)
+        .count()
+        + 1;
+    (line, column)
+}
@@ -0,0 +1,81 @@
+//! Support for serializing error trees in the field layout used by
+//! [`tracing-subscriber`](https://docs.rs/tracing-subscriber)'s JSON formatter.
+//!
+//! `tracing` events record an error's message in a field named `error`, and related values in
+//! fields that share its name as a dotted prefix. This module follows that convention: each node
+//! of the tree is an object with exactly two keys.
+//!
+//! * `"error"`: the message for this node, as a string.
+//! * `"error.sources"`: an array of this node's sources, each of which has the same shape. For
+//!   nodes without any sources, this is an empty array.
+//!
+//! For example:
+//!
+//! ```json
+//! {
+//!   "error": "failed to read config",
+//!   "error.sources": [
+//!     { "error": "file not found", "error.sources": [] }
+//!   ]
+//! }
+//! ```
+//!
+//! Because the top-level object uses the same keys as `tracing`'s JSON fields, it can be
+//! flattened into an event's `fields` object, so that existing dashboards keyed on `error`
+//! continue to work.
+
+use err_tree::{ErrorTree, ErrorTreeSource};
+use serde::{ser::SerializeMap, Serialize, Serializer};
+use std::fmt;
+
+/// A wrapper which serializes an error tree in the `tracing-subscriber` JSON layout.
+///
+/// See the [module documentation](self) for the schema.
+pub struct TracingJson<ET> {
+    et: ET,
+}
+
+impl<ET> TracingJson<ET> {
+    pub fn new(et: ET) -> Self {
+        Self { et }
+    }
+
+    pub fn into_inner(self) -> ET {
+        self.et
+    }
+}
+
+impl<ET: ErrorTree> Serialize for TracingJson<ET> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_node(serializer, &self.et, self.et.sources())
+    }
+}
+
+fn serialize_node<'s, S>(
+    serializer: S,
+    msg: &dyn fmt::Display,
+    sources: impl Iterator<Item = ErrorTreeSource<'s>>,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let sources: Vec<_> = sources.map(TracingSource).collect();
+    let mut map = serializer.serialize_map(Some(2))?;
+    map.serialize_entry("error", &msg.to_string())?;
+    map.serialize_entry("error.sources", &sources)?;
+    map.end()
+}
+
+struct TracingSource<'s>(ErrorTreeSource<'s>);
+
+impl<'s> Serialize for TracingSource<'s> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_node(serializer, &self.0, self.0.sources())
+    }
+}
@@ -1,18 +1,31 @@
-use crate::Ser;
+use crate::{ErrorTreeMetadata, Ser};
 use err_tree::{ErrorTree, ErrorTreeSource};
 use serde::{Deserialize, Serialize};
-use std::fmt;
+use serde_json::Value;
+use std::{collections::BTreeMap, fmt};
 
 /// An [`ErrorTree`] instance that can be serialized and deserialized.
 ///
 /// The output format is compatible with the one used by the [`Ser`] adapter.
-#[derive(Debug, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SerdeErrorTree {
     /// The message for this node in the error tree.
     pub msg: String,
 
     /// The sources of this node.
+    ///
+    /// This is omitted from the serialized form if it's empty. Payloads that include an empty
+    /// `"sources"` array are accepted as well.
+    #[serde(default)]
     pub sources: Vec<SerdeErrorTree>,
+
+    /// Extra metadata attached to this node, such as an error code.
+    ///
+    /// This is serialized as an `"extra"` object, which is omitted if the map is empty, so trees
+    /// without metadata use the same format as before. See [`ErrorTreeMetadata`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, Value>,
 }
 
 impl SerdeErrorTree {
@@ -20,6 +33,7 @@ impl SerdeErrorTree {
     pub fn new<ET: ErrorTree>(tree: ET) -> Self {
         Self {
             msg: tree.to_string(),
+            extra: BTreeMap::new(),
             sources: tree
                 .sources()
                 .map(|source| match source {
@@ -35,9 +49,77 @@ impl SerdeErrorTree {
         Self {
             msg: msg.into(),
             sources,
+            extra: BTreeMap::new(),
+        }
+    }
+
+    /// Builds a tree from a list of nodes, each with a message and the indexes of its children in
+    /// order, returning the tree rooted at `root`.
+    ///
+    /// Each node's parent must come before it in the list, and each node must be the child of at
+    /// most one other node.
+    pub(crate) fn from_nodes(nodes: Vec<(String, Vec<usize>)>, root: usize) -> Self {
+        // Parents always come before their children, so building trees in reverse order means
+        // that all of a node's children are built before the node itself.
+        let mut trees: Vec<Option<SerdeErrorTree>> = Vec::with_capacity(nodes.len());
+        trees.resize_with(nodes.len(), || None);
+        for (index, (msg, children)) in nodes.into_iter().enumerate().rev() {
+            let sources = children
+                .iter()
+                .map(|&child| {
+                    trees[child]
+                        .take()
+                        .expect("each child is built exactly once")
+                })
+                .collect();
+            trees[index] = Some(SerdeErrorTree::from_msg_and_sources(msg, sources));
+        }
+
+        trees[root].take().expect("root was built")
+    }
+
+    /// Replaces every subtree whose root matches `pred` with a tree built by `replacement`.
+    ///
+    /// Nodes are visited in depth-first pre-order, starting with `self`. When a node matches, it
+    /// is replaced along with all of its descendants, and the replacement is not visited. This is
+    /// useful for hiding entire branches, such as ones that contain sensitive details.
+    pub fn replace_subtrees(
+        &mut self,
+        mut pred: impl FnMut(&SerdeErrorTree) -> bool,
+        replacement: impl Fn() -> SerdeErrorTree,
+    ) {
+        self.replace_subtrees_impl(&mut pred, &replacement);
+    }
+
+    fn replace_subtrees_impl(
+        &mut self,
+        pred: &mut dyn FnMut(&SerdeErrorTree) -> bool,
+        replacement: &dyn Fn() -> SerdeErrorTree,
+    ) {
+        if pred(self) {
+            *self = replacement();
+            return;
+        }
+        for source in &mut self.sources {
+            source.replace_subtrees_impl(pred, replacement);
         }
     }
 
+    /// Converts this tree into a boxed [`ErrorTree`] trait object.
+    ///
+    /// This is the same as [`ErrorTree::into_boxed`], but doesn't require the trait to be in
+    /// scope. `Box<dyn ErrorTree>` also implements `From<SerdeErrorTree>`.
+    #[inline]
+    pub fn into_boxed(self) -> Box<dyn ErrorTree> {
+        Box::new(self)
+    }
+
+    /// Sets the extra metadata attached to this node.
+    pub fn with_extra(mut self, extra: BTreeMap<String, Value>) -> Self {
+        self.extra = extra;
+        self
+    }
+
     /// Creates a new [`SerdeErrorTree`] from an error.
     pub fn from_error<E: std::error::Error>(error: E) -> Self {
         // Can't use `err_tree::ErrorWrapper` here because that requires the error to be
@@ -63,6 +145,31 @@ impl ErrorTree for SerdeErrorTree {
     }
 }
 
+impl ErrorTreeMetadata for SerdeErrorTree {
+    #[inline]
+    fn extra(&self) -> Option<&BTreeMap<String, Value>> {
+        Some(&self.extra)
+    }
+
+    fn sources_with_metadata(
+        &self,
+    ) -> Box<dyn Iterator<Item = (ErrorTreeSource<'_>, Option<&dyn ErrorTreeMetadata>)> + '_> {
+        Box::new(self.sources.iter().map(|source| {
+            (
+                ErrorTreeSource::Tree(source),
+                Some(source as &dyn ErrorTreeMetadata),
+            )
+        }))
+    }
+}
+
+impl From<SerdeErrorTree> for Box<dyn ErrorTree> {
+    #[inline]
+    fn from(tree: SerdeErrorTree) -> Self {
+        tree.into_boxed()
+    }
+}
+
 impl Serialize for SerdeErrorTree {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -70,6 +177,6 @@ impl Serialize for SerdeErrorTree {
     {
         // Use the serializer we already have, which we know has the same format as this one. (We
         // test out roundtrips as part of our tests.)
-        Ser::new(self).serialize(serializer)
+        Ser::new(self).with_metadata().serialize(serializer)
     }
 }
@@ -0,0 +1,34 @@
+use err_tree::ErrorTreeExt;
+use pretty_assertions::assert_eq;
+use serde_err_tree::{BorrowedErrorTree, Ser, SerdeErrorTree};
+use std::borrow::Cow;
+
+#[test]
+fn test_borrowed_messages() {
+    let json = serde_json::to_string(&Ser::new(mishap_testdata::complex())).unwrap();
+    let tree: BorrowedErrorTree<'_> = serde_json::from_str(&json).unwrap();
+
+    // Messages without escapes point into the input buffer.
+    let input = json.as_bytes().as_ptr_range();
+    let source = &tree.sources[0].sources[0];
+    assert_eq!(source.msg, "mishap4");
+    match &source.msg {
+        Cow::Borrowed(msg) => assert!(input.contains(&msg.as_ptr())),
+        Cow::Owned(_) => panic!("message without escapes is borrowed"),
+    }
+
+    // Messages with escapes, such as newlines, have to be unescaped into an owned string.
+    assert_eq!(tree.msg, "top-level line1\ntop-level line2");
+    assert!(matches!(tree.msg, Cow::Owned(_)));
+
+    // The tree matches the owned representation.
+    let owned: SerdeErrorTree = serde_json::from_str(&json).unwrap();
+    assert_eq!(SerdeErrorTree::from(tree.clone()), owned);
+    assert_eq!(serde_json::to_string(&tree).unwrap(), json);
+
+    let tree = tree.into_owned();
+    assert_eq!(
+        tree.display_tree().to_string(),
+        mishap_testdata::complex().display_tree().to_string(),
+    );
+}
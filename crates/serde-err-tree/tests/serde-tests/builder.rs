@@ -0,0 +1,65 @@
+use pretty_assertions::assert_eq;
+use serde_err_tree::{BuildError, SerdeErrorTree, SerdeErrorTreeBuilder};
+
+#[test]
+fn test_builder() {
+    let mut builder = SerdeErrorTreeBuilder::new("root");
+    let root = builder.root();
+    let a = builder.push_child(root, "a").unwrap();
+    builder.push_child(root, "b").unwrap();
+    builder.push_child(a, "a1").unwrap();
+    builder.push_child(a, "a2").unwrap();
+    assert_eq!(builder.len(), 5);
+
+    let leaf = |msg: &str| SerdeErrorTree::from_msg_and_sources(msg, vec![]);
+    assert_eq!(
+        builder.build(),
+        SerdeErrorTree::from_msg_and_sources(
+            "root",
+            vec![
+                SerdeErrorTree::from_msg_and_sources("a", vec![leaf("a1"), leaf("a2")]),
+                leaf("b"),
+            ],
+        ),
+    );
+}
+
+#[test]
+fn test_builder_max_nodes() {
+    let mut builder = SerdeErrorTreeBuilder::new("root").max_nodes(3);
+    let root = builder.root();
+    let a = builder.push_child(root, "a").unwrap();
+    builder.push_child(a, "a1").unwrap();
+
+    // The limit is enforced as soon as it would be exceeded, and the tree is left unchanged.
+    assert_eq!(
+        builder.push_child(root, "b"),
+        Err(BuildError::TooManyNodes { max_nodes: 3 }),
+    );
+    assert_eq!(builder.len(), 3);
+    assert_eq!(
+        builder.build(),
+        SerdeErrorTree::from_msg_and_sources(
+            "root",
+            vec![SerdeErrorTree::from_msg_and_sources(
+                "a",
+                vec![SerdeErrorTree::from_msg_and_sources("a1", vec![])],
+            )],
+        ),
+    );
+}
+
+#[test]
+fn test_builder_max_depth() {
+    let mut builder = SerdeErrorTreeBuilder::new("root").max_depth(1);
+    let root = builder.root();
+    let a = builder.push_child(root, "a").unwrap();
+    assert_eq!(
+        builder.push_child(a, "a1"),
+        Err(BuildError::TooDeep { max_depth: 1 }),
+    );
+
+    // Shallower nodes can still be added.
+    builder.push_child(root, "b").unwrap();
+    assert_eq!(builder.build().sources.len(), 2);
+}
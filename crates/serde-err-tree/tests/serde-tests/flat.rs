@@ -0,0 +1,97 @@
+use err_tree::ErrorTreeExt;
+use pretty_assertions::assert_eq;
+use serde_err_tree::{FlatErrorTree, FlatNode, FromFlatError, SerFlat, SerdeErrorTree};
+
+#[test]
+fn test_flat_roundtrip() {
+    let mishap = mishap_testdata::complex();
+    let flat = FlatErrorTree::new(&mishap);
+    assert_eq!(flat.nodes.len(), mishap.node_count());
+
+    // Nodes are listed in depth-first pre-order.
+    let nodes: Vec<_> = flat
+        .nodes
+        .iter()
+        .map(|node| (node.msg.lines().next().unwrap(), node.parent))
+        .collect();
+    assert_eq!(
+        nodes,
+        [
+            ("top-level line1", None),
+            ("mishap5 line1", Some(0)),
+            ("mishap4", Some(1)),
+            ("mishap2 line1", Some(2)),
+            ("mishap1 line1", Some(3)),
+            ("anyhow error2", Some(4)),
+            ("anyhow error", Some(5)),
+            ("mishap3 line1", Some(2)),
+            ("mishap7 line1", Some(0)),
+            ("mishap6 line1", Some(8)),
+            ("mishap8 line1", Some(0)),
+            ("anyhow error3", Some(10)),
+            ("anyhow error4", Some(10)),
+        ],
+    );
+
+    // Serializing with SerFlat is the same as serializing the flat tree.
+    let json = serde_json::to_string(&SerFlat::new(&mishap)).unwrap();
+    assert_eq!(json, serde_json::to_string(&flat).unwrap());
+
+    let flat: FlatErrorTree = serde_json::from_str(&json).unwrap();
+    let tree = SerdeErrorTree::from_flat(flat).unwrap();
+    assert_eq!(tree, SerdeErrorTree::new(&mishap));
+    assert_eq!(
+        tree.display_tree().to_string(),
+        mishap.display_tree().to_string(),
+    );
+}
+
+#[test]
+fn test_from_flat_errors() {
+    let node = |msg: &str, parent| FlatNode {
+        msg: msg.to_owned(),
+        parent,
+    };
+
+    assert_eq!(
+        SerdeErrorTree::from_flat(FlatErrorTree { nodes: vec![] }),
+        Err(FromFlatError::Empty),
+    );
+    assert_eq!(
+        SerdeErrorTree::from_flat(FlatErrorTree {
+            nodes: vec![node("root", None), node("a", Some(1))],
+        }),
+        Err(FromFlatError::InvalidParent {
+            index: 1,
+            parent: 1,
+        }),
+    );
+    assert_eq!(
+        SerdeErrorTree::from_flat(FlatErrorTree {
+            nodes: vec![node("root", None), node("other root", None)],
+        }),
+        Err(FromFlatError::MultipleRoots { index: 1 }),
+    );
+
+    // Nodes don't have to be in depth-first order.
+    let tree = SerdeErrorTree::from_flat(FlatErrorTree {
+        nodes: vec![
+            node("root", None),
+            node("a", Some(0)),
+            node("b", Some(0)),
+            node("a1", Some(1)),
+        ],
+    })
+    .unwrap();
+    let leaf = |msg: &str| SerdeErrorTree::from_msg_and_sources(msg, vec![]);
+    assert_eq!(
+        tree,
+        SerdeErrorTree::from_msg_and_sources(
+            "root",
+            vec![
+                SerdeErrorTree::from_msg_and_sources("a", vec![leaf("a1")]),
+                leaf("b"),
+            ],
+        ),
+    );
+}
@@ -1 +1,10 @@
+mod borrowed;
+mod builder;
+mod flat;
+mod ndjson;
+mod roundtrip;
+#[cfg(feature = "schemars")]
+mod schema;
 mod serde_tests;
+mod spanned;
+mod tracing_json;
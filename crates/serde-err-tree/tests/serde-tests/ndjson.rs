@@ -0,0 +1,85 @@
+use pretty_assertions::assert_eq;
+use serde_err_tree::{
+    ndjson::{from_ndjson, to_ndjson, ParseError},
+    SerdeErrorTree,
+};
+
+#[test]
+fn test_roundtrip() {
+    for (mishap, filename_prefix) in [
+        (mishap_testdata::complex(), "complex"),
+        (mishap_testdata::single_source(), "single-source"),
+    ] {
+        let ndjson = to_ndjson(&mishap);
+        expectorate::assert_contents(
+            format!("tests/outputs/{filename_prefix}-ndjson.txt"),
+            &ndjson,
+        );
+
+        let tree = from_ndjson(&ndjson).unwrap();
+        assert_eq!(
+            tree,
+            SerdeErrorTree::new(&mishap),
+            "trees match after roundtrip"
+        );
+    }
+}
+
+#[test]
+fn test_parse_errors() {
+    let error = from_ndjson("").unwrap_err();
+    assert!(matches!(error, ParseError::NoRoot), "{error:?}");
+
+    let error = from_ndjson("not json").unwrap_err();
+    assert!(
+        matches!(error, ParseError::Json { line: 1, .. }),
+        "{error:?}"
+    );
+
+    let input = r#"{"id":0,"parent":null,"depth":0,"msg":"root"}
+{"id":0,"parent":0,"depth":1,"msg":"child"}"#;
+    let error = from_ndjson(input).unwrap_err();
+    assert!(
+        matches!(error, ParseError::DuplicateId { line: 2, id: 0 }),
+        "{error:?}"
+    );
+
+    let input = r#"{"id":0,"parent":null,"depth":0,"msg":"root"}
+{"id":1,"parent":2,"depth":1,"msg":"child"}"#;
+    let error = from_ndjson(input).unwrap_err();
+    assert!(
+        matches!(
+            error,
+            ParseError::MissingParent {
+                line: 2,
+                id: 1,
+                parent: 2
+            }
+        ),
+        "{error:?}"
+    );
+
+    let input = r#"{"id":0,"parent":null,"depth":0,"msg":"root"}
+{"id":1,"parent":0,"depth":2,"msg":"child"}"#;
+    let error = from_ndjson(input).unwrap_err();
+    assert!(
+        matches!(
+            error,
+            ParseError::InvalidDepth {
+                line: 2,
+                depth: 2,
+                expected: 1,
+                ..
+            }
+        ),
+        "{error:?}"
+    );
+
+    let input = r#"{"id":0,"parent":null,"depth":0,"msg":"root"}
+{"id":1,"parent":null,"depth":0,"msg":"another root"}"#;
+    let error = from_ndjson(input).unwrap_err();
+    assert!(
+        matches!(error, ParseError::MultipleRoots { line: 2, id: 1 }),
+        "{error:?}"
+    );
+}
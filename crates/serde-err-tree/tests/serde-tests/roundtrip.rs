@@ -0,0 +1,38 @@
+use proptest::prelude::*;
+use serde_err_tree::{dedupe, ndjson, Ser, SerdeErrorTree};
+
+/// Generates arbitrary trees with bounded depth and breadth.
+///
+/// Messages are arbitrary strings, so they include empty messages, Unicode, and characters that
+/// need to be escaped.
+fn arb_tree() -> impl Strategy<Value = SerdeErrorTree> {
+    let leaf = any::<String>().prop_map(|msg| SerdeErrorTree::from_msg_and_sources(msg, vec![]));
+    leaf.prop_recursive(8, 64, 4, |inner| {
+        (any::<String>(), prop::collection::vec(inner, 0..4))
+            .prop_map(|(msg, sources)| SerdeErrorTree::from_msg_and_sources(msg, sources))
+    })
+}
+
+proptest! {
+    #[test]
+    fn test_roundtrip(tree in arb_tree()) {
+        let json = serde_json::to_string(&tree).unwrap();
+        let roundtrip: SerdeErrorTree = serde_json::from_str(&json).unwrap();
+        prop_assert_eq!(&roundtrip, &tree);
+        prop_assert_eq!(serde_json::to_string(&roundtrip).unwrap(), json);
+    }
+
+    #[test]
+    fn test_roundtrip_dedupe(tree in arb_tree()) {
+        let json = serde_json::to_string(&Ser::new(&tree).dedupe_subtrees()).unwrap();
+        let mut deserializer = serde_json::Deserializer::from_str(&json);
+        let roundtrip = dedupe::deserialize(&mut deserializer).unwrap();
+        prop_assert_eq!(roundtrip, tree);
+    }
+
+    #[test]
+    fn test_roundtrip_ndjson(tree in arb_tree()) {
+        let roundtrip = ndjson::from_ndjson(&ndjson::to_ndjson(&tree)).unwrap();
+        prop_assert_eq!(roundtrip, tree);
+    }
+}
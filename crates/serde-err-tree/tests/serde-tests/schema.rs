@@ -0,0 +1,15 @@
+use serde_err_tree::SerdeErrorTree;
+
+#[test]
+fn test_schema() {
+    let schema = schemars::schema_for!(SerdeErrorTree);
+    let json = serde_json::to_string_pretty(&schema).unwrap();
+    expectorate::assert_contents("tests/outputs/serde-error-tree-schema.json", &json);
+
+    // The recursive sources field refers back to the root definition.
+    let value = serde_json::to_value(&schema).unwrap();
+    assert_eq!(
+        value["properties"]["sources"]["items"]["$ref"],
+        "#/definitions/SerdeErrorTree",
+    );
+}
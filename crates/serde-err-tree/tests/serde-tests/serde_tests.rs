@@ -1,6 +1,18 @@
+use err_tree::{ErrorTree, ErrorTreeExt, ErrorTreeSource, OwnedErrorTree};
 use mishap::Mishap;
 use pretty_assertions::assert_eq;
-use serde_err_tree::{Ser, SerdeErrorTree};
+use serde_err_tree::{
+    dedupe, ErrorTreeMetadata, ErrorTreeSerdeExt, Ser, SerStdError, SerdeErrorTree, StringInterner,
+};
+use std::{
+    collections::BTreeMap,
+    error, fmt, io,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, OnceLock,
+    },
+    time::{Duration, SystemTime},
+};
 
 #[test]
 fn test_complex() {
@@ -33,3 +45,603 @@ fn test_impl(mishap: Mishap, filename_prefix: &str) {
     let tree3 = SerdeErrorTree::new(&mishap);
     assert_eq!(tree, tree3, "trees match when constructed directly");
 }
+
+#[test]
+fn test_estimated_json_size() {
+    let mishap = mishap_testdata::complex();
+    let estimate = mishap.estimated_json_size();
+    let actual = serde_json::to_string(&Ser::new(&mishap)).unwrap().len();
+    // The estimate doesn't account for escaped newlines, but should otherwise be close.
+    assert!(
+        actual / 2 <= estimate && estimate <= actual * 2,
+        "estimate {estimate} is within a factor of 2 of actual size {actual}"
+    );
+}
+
+#[test]
+fn test_filter() {
+    let mishap = mishap_testdata::complex();
+    let ser = Ser::new(&mishap).filter(|msg| msg.contains("anyhow error3"));
+    let json = serde_json::to_string_pretty(&ser).unwrap();
+    expectorate::assert_contents("tests/outputs/complex-filter.json", &json);
+}
+
+#[test]
+fn test_dedupe_subtrees() {
+    let repeated = || Mishap::from_messages(&["failed to connect", "connection refused"]);
+    let mishap = Mishap::from_msg_and_error_trees(
+        "all requests failed",
+        [
+            repeated(),
+            Mishap::from_msg("timed out"),
+            repeated(),
+            Mishap::from_msg_and_error_tree("retry failed", repeated()),
+        ],
+    );
+
+    let ser = Ser::new(&mishap).dedupe_subtrees();
+    let json = serde_json::to_string_pretty(&ser).unwrap();
+    expectorate::assert_contents("tests/outputs/dedupe-serialize.json", &json);
+
+    let mut deserializer = serde_json::Deserializer::from_str(&json);
+    let tree = serde_err_tree::dedupe::deserialize(&mut deserializer).unwrap();
+    assert_eq!(tree, SerdeErrorTree::new(&mishap), "refs are expanded");
+
+    // Trees without any repeated subtrees are serialized as usual.
+    let mishap = mishap_testdata::complex();
+    let ser = Ser::new(&mishap).dedupe_subtrees();
+    assert_eq!(
+        serde_json::to_string(&ser).unwrap(),
+        serde_json::to_string(&Ser::new(&mishap)).unwrap(),
+    );
+
+    // Subtrees with the same messages in a different order aren't the same.
+    let mishap = Mishap::from_msg_and_error_trees(
+        "all requests failed",
+        [
+            Mishap::from_messages(&["a", "b", "c"]),
+            Mishap::from_messages(&["a", "c", "b"]),
+        ],
+    );
+    let ser = Ser::new(&mishap).dedupe_subtrees();
+    assert_eq!(
+        serde_json::to_string(&ser).unwrap(),
+        serde_json::to_string(&Ser::new(&mishap)).unwrap(),
+    );
+}
+
+#[test]
+fn test_type_tags() {
+    let mishap = mishap_testdata::complex();
+    let ser = Ser::new(&mishap).with_type_tags();
+    let json = serde_json::to_string_pretty(&ser).unwrap();
+    expectorate::assert_contents("tests/outputs/complex-type-tags.json", &json);
+
+    // Check that each type is computed from the number of sources.
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let mut stack = vec![&value];
+    let mut seen_types = Vec::new();
+    while let Some(node) = stack.pop() {
+        // Leaves don't have a sources field.
+        let sources = node
+            .get("sources")
+            .map_or(&[][..], |sources| sources.as_array().unwrap());
+        let expected = match sources.len() {
+            0 => "leaf",
+            1 => "chain",
+            _ => "tree",
+        };
+        assert_eq!(node["type"], expected, "type for {}", node["msg"]);
+        if !seen_types.contains(&expected) {
+            seen_types.push(expected);
+        }
+        stack.extend(sources);
+    }
+    seen_types.sort_unstable();
+    assert_eq!(
+        seen_types,
+        ["chain", "leaf", "tree"],
+        "all types are present"
+    );
+
+    // The type field is ignored while deserializing.
+    let tree: SerdeErrorTree = serde_json::from_str(&json).unwrap();
+    assert_eq!(tree, SerdeErrorTree::new(&mishap));
+}
+
+#[test]
+fn test_replace_subtrees() {
+    let mut tree = SerdeErrorTree::new(mishap_testdata::complex());
+    tree.replace_subtrees(
+        |node| node.msg.starts_with("mishap4"),
+        || SerdeErrorTree::from_msg_and_sources("internal error", vec![]),
+    );
+
+    assert_eq!(
+        tree.display_tree().to_string(),
+        "top-level line1\n\
+         top-level line2\n\
+         \n\
+         Caused by:\n\n  \
+           + mishap5 line1\n    \
+             mishap5 line2\n      \
+               - internal error\n  \
+           + mishap7 line1\n    \
+             mishap7 line2\n      \
+               - mishap6 line1\n        \
+                 mishap6 line2\n  \
+           + mishap8 line1\n    \
+             mishap8 line2\n    \
+             + anyhow error3\n    \
+             + anyhow error4\n",
+    );
+    // The descendants of the replaced subtree are gone.
+    let json = serde_json::to_string(&tree).unwrap();
+    assert!(!json.contains("mishap2"));
+    assert!(!json.contains("anyhow error\""));
+}
+
+#[test]
+fn test_with_timing() {
+    let failed_at = SystemTime::UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+    let mishap = Mishap::from_msg_and_error_trees(
+        "all requests failed",
+        [
+            Mishap::from_msg("request 1 failed")
+                .with_timestamp(failed_at)
+                .with_duration(Duration::from_millis(250)),
+            Mishap::from_msg("request 2 failed").with_duration(Duration::from_millis(30)),
+            Mishap::from_msg("request 3 failed"),
+        ],
+    );
+
+    let value = serde_json::to_value(Ser::new(&mishap).with_timing()).unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({
+            "msg": "all requests failed",
+            "sources": [
+                {
+                    "timestamp": 1_700_000_000_123_u64,
+                    "duration_ms": 250,
+                    "msg": "request 1 failed",
+                },
+                { "duration_ms": 30, "msg": "request 2 failed" },
+                { "msg": "request 3 failed" },
+            ],
+        }),
+    );
+
+    // Timing isn't serialized by default.
+    let json = serde_json::to_string(&Ser::new(&mishap)).unwrap();
+    assert!(!json.contains("duration_ms") && !json.contains("timestamp"));
+}
+
+#[test]
+fn test_intern() {
+    let tree1 = SerdeErrorTree::new(mishap_testdata::complex());
+    let tree2 = SerdeErrorTree::new(mishap_testdata::single_source());
+
+    let mut interner = StringInterner::new();
+    let interned1 = tree1.intern(&mut interner);
+    let interned2 = tree2.intern(&mut interner);
+
+    // "anyhow error" is a leaf in both trees.
+    let leaf1 = &interned1.sources[0].sources[0].sources[0].sources[0].sources[0].sources[0];
+    let leaf2 = &interned2.sources[0].sources[0].sources[0].sources[0];
+    assert_eq!(&*leaf1.msg, "anyhow error");
+    assert!(Arc::ptr_eq(&leaf1.msg, &leaf2.msg), "messages are shared");
+
+    // The serialized forms are unchanged.
+    assert_eq!(
+        serde_json::to_string(&interned1).unwrap(),
+        serde_json::to_string(&tree1).unwrap(),
+    );
+}
+
+#[test]
+fn test_with_rendered() {
+    let mishap = mishap_testdata::complex();
+    let value = serde_json::to_value(Ser::new(&mishap).with_rendered()).unwrap();
+    assert_eq!(
+        value["rendered"].as_str(),
+        Some(mishap.display_tree().to_string().as_str()),
+    );
+
+    // Only the root has the rendered field, and the structure is unchanged.
+    let tree: SerdeErrorTree = serde_json::from_value(value.clone()).unwrap();
+    assert_eq!(tree, SerdeErrorTree::new(&mishap));
+    assert!(value["sources"][0].get("rendered").is_none());
+
+    // The rendered form isn't serialized by default.
+    let value = serde_json::to_value(Ser::new(&mishap)).unwrap();
+    assert!(value.get("rendered").is_none());
+}
+
+#[test]
+fn test_render_both() {
+    let mishap = mishap_testdata::complex();
+    let (text, json) = mishap.render_both();
+    assert_eq!(text, mishap.display_tree().to_string());
+    assert_eq!(json, serde_json::to_string(&Ser::new(&mishap)).unwrap());
+}
+
+#[test]
+fn test_compat_normalize() {
+    let repeated = || Mishap::from_messages(&["failed to connect", "connection refused"]);
+    let mishap = Mishap::from_msg_and_error_trees(
+        "all requests failed",
+        [
+            repeated().with_duration(Duration::from_millis(30)),
+            repeated(),
+            mishap_testdata::complex(),
+        ],
+    )
+    .with_timestamp(SystemTime::UNIX_EPOCH + Duration::from_millis(1_700_000_000_000));
+
+    let ser = Ser::new(&mishap)
+        .dedupe_subtrees()
+        .with_type_tags()
+        .with_timing()
+        .with_rendered();
+    let mut value = serde_json::to_value(ser).unwrap();
+    assert!(value.get("rendered").is_some() && value.get("type").is_some());
+    assert!(value.to_string().contains(r#"{"ref":1}"#));
+
+    serde_err_tree::compat::normalize(&mut value);
+    assert_eq!(value, serde_json::to_value(Ser::new(&mishap)).unwrap());
+
+    let tree: SerdeErrorTree = serde_json::from_value(value).unwrap();
+    assert_eq!(tree, SerdeErrorTree::new(&mishap));
+}
+
+#[test]
+fn test_into_boxed() {
+    let json =
+        r#"{"msg":"outer","sources":[{"msg":"first","sources":[]},{"msg":"second","sources":[]}]}"#;
+    let tree: SerdeErrorTree = serde_json::from_str(json).unwrap();
+    let expected = "outer\n\nCaused by:\n\n  + first\n  + second\n";
+
+    let boxed = tree.clone().into_boxed();
+    assert_eq!(boxed.display_tree().to_string(), expected);
+
+    let boxed: Box<dyn ErrorTree> = tree.into();
+    assert_eq!(boxed.display_tree().to_string(), expected);
+    assert_eq!(boxed.sources().count(), 2);
+}
+
+#[test]
+fn test_extra_metadata_absent() {
+    // JSON without metadata deserializes as before, and serializes back unchanged.
+    let json = r#"{"msg":"outer","sources":[{"msg":"inner"}]}"#;
+    let tree: SerdeErrorTree = serde_json::from_str(json).unwrap();
+    assert!(tree.extra.is_empty());
+    assert!(tree.sources[0].extra.is_empty());
+    assert_eq!(serde_json::to_string(&tree).unwrap(), json);
+
+    // Trees that don't implement ErrorTreeMetadata are serialized as usual.
+    let mishap = mishap_testdata::complex();
+    assert_eq!(
+        serde_json::to_value(Ser::new(&mishap)).unwrap(),
+        serde_json::to_value(SerdeErrorTree::new(&mishap)).unwrap(),
+    );
+}
+
+#[test]
+fn test_extra_metadata() {
+    #[derive(Debug)]
+    struct CodedError {
+        msg: &'static str,
+        extra: BTreeMap<String, serde_json::Value>,
+        sources: Vec<CodedError>,
+    }
+
+    impl fmt::Display for CodedError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.msg)
+        }
+    }
+
+    impl ErrorTree for CodedError {
+        fn sources(&self) -> Box<dyn Iterator<Item = ErrorTreeSource<'_>> + '_> {
+            Box::new(
+                self.sources
+                    .iter()
+                    .map(|source| ErrorTreeSource::Tree(source)),
+            )
+        }
+    }
+
+    impl ErrorTreeMetadata for CodedError {
+        fn extra(&self) -> Option<&BTreeMap<String, serde_json::Value>> {
+            Some(&self.extra)
+        }
+
+        fn sources_with_metadata(
+            &self,
+        ) -> Box<dyn Iterator<Item = (ErrorTreeSource<'_>, Option<&dyn ErrorTreeMetadata>)> + '_>
+        {
+            Box::new(self.sources.iter().map(|source| {
+                (
+                    ErrorTreeSource::Tree(source),
+                    Some(source as &dyn ErrorTreeMetadata),
+                )
+            }))
+        }
+    }
+
+    let error = CodedError {
+        msg: "request failed",
+        extra: BTreeMap::from([("code".to_owned(), serde_json::json!("E_REQUEST"))]),
+        sources: vec![
+            CodedError {
+                msg: "timed out",
+                extra: BTreeMap::from([
+                    ("code".to_owned(), serde_json::json!("E_TIMEOUT")),
+                    ("severity".to_owned(), serde_json::json!("warning")),
+                ]),
+                sources: vec![],
+            },
+            CodedError {
+                msg: "no metadata",
+                extra: BTreeMap::new(),
+                sources: vec![],
+            },
+        ],
+    };
+
+    let value = serde_json::to_value(Ser::new(&error).with_metadata()).unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({
+            "msg": "request failed",
+            "sources": [
+                {
+                    "msg": "timed out",
+                    "extra": {"code": "E_TIMEOUT", "severity": "warning"},
+                },
+                {"msg": "no metadata"},
+            ],
+            "extra": {"code": "E_REQUEST"},
+        }),
+    );
+
+    // Without with_metadata, the metadata is skipped.
+    assert!(!serde_json::to_string(&Ser::new(&error))
+        .unwrap()
+        .contains("extra"));
+
+    // The metadata round-trips through SerdeErrorTree.
+    let tree: SerdeErrorTree = serde_json::from_value(value.clone()).unwrap();
+    assert_eq!(tree.extra["code"], "E_REQUEST");
+    assert_eq!(tree.sources[0].extra["severity"], "warning");
+    assert!(tree.sources[1].extra.is_empty());
+    assert_eq!(serde_json::to_value(&tree).unwrap(), value);
+}
+
+#[test]
+fn test_to_pruned() {
+    let mishap = mishap_testdata::complex();
+    let pruned = mishap.to_pruned(|source| !source.to_string().starts_with("anyhow error"));
+    let leaf = |msg: &str| SerdeErrorTree::from_msg_and_sources(msg, vec![]);
+    assert_eq!(
+        pruned,
+        SerdeErrorTree::from_msg_and_sources(
+            "top-level line1\ntop-level line2",
+            vec![
+                SerdeErrorTree::from_msg_and_sources(
+                    "mishap5 line1\nmishap5 line2",
+                    vec![SerdeErrorTree::from_msg_and_sources(
+                        "mishap4",
+                        vec![
+                            SerdeErrorTree::from_msg_and_sources(
+                                "mishap2 line1\n\nmishap2 line 2",
+                                vec![leaf("mishap1 line1\nmishap1 line2")],
+                            ),
+                            leaf("mishap3 line1\nmishap3 line2"),
+                        ],
+                    )],
+                ),
+                SerdeErrorTree::from_msg_and_sources(
+                    "mishap7 line1\nmishap7 line2",
+                    vec![leaf("mishap6 line1\nmishap6 line2")],
+                ),
+                leaf("mishap8 line1\nmishap8 line2"),
+            ],
+        ),
+    );
+
+    // Descendants of a pruned source are dropped, and keep isn't called for them.
+    let visited = std::cell::RefCell::new(Vec::new());
+    let pruned = mishap.to_pruned(|source| {
+        let msg = source.to_string();
+        visited
+            .borrow_mut()
+            .push(msg.lines().next().unwrap().to_owned());
+        !msg.starts_with("mishap5")
+    });
+    assert_eq!(pruned.sources.len(), 2);
+    assert_eq!(
+        visited.into_inner(),
+        [
+            "mishap5 line1",
+            "mishap7 line1",
+            "mishap6 line1",
+            "mishap8 line1",
+            "anyhow error3",
+            "anyhow error4",
+        ],
+    );
+}
+
+#[test]
+fn test_empty_sources_omitted() {
+    let tree = SerdeErrorTree::from_msg_and_sources(
+        "outer",
+        vec![
+            SerdeErrorTree::from_msg_and_sources("first", vec![]),
+            SerdeErrorTree::from_msg_and_sources(
+                "second",
+                vec![SerdeErrorTree::from_msg_and_sources("inner", vec![])],
+            ),
+        ],
+    );
+    let json = serde_json::to_string(&tree).unwrap();
+    assert_eq!(
+        json,
+        r#"{"msg":"outer","sources":[{"msg":"first"},{"msg":"second","sources":[{"msg":"inner"}]}]}"#,
+    );
+
+    // Payloads written with explicit empty sources arrays deserialize to the same tree.
+    let old = r#"{"msg":"outer","sources":[{"msg":"first","sources":[]},{"msg":"second","sources":[{"msg":"inner","sources":[]}]}]}"#;
+    let old_tree: SerdeErrorTree = serde_json::from_str(old).unwrap();
+    assert_eq!(old_tree, tree);
+    let new_tree: SerdeErrorTree = serde_json::from_str(&json).unwrap();
+    assert_eq!(new_tree, tree);
+
+    // The same applies to the dedupe format.
+    let mut deserializer = serde_json::Deserializer::from_str(old);
+    assert_eq!(dedupe::deserialize(&mut deserializer).unwrap(), tree);
+    let mut deserializer = serde_json::Deserializer::from_str(&json);
+    assert_eq!(dedupe::deserialize(&mut deserializer).unwrap(), tree);
+}
+
+#[test]
+fn test_with_max_depth() {
+    // A chain of 10 nodes, each with a single source.
+    let mut tree = SerdeErrorTree::from_msg_and_sources("level 9", vec![]);
+    for level in (0..9).rev() {
+        tree = SerdeErrorTree::from_msg_and_sources(format!("level {level}"), vec![tree]);
+    }
+
+    let value = serde_json::to_value(Ser::new(&tree).with_max_depth(2)).unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({
+            "msg": "level 0",
+            "sources": [{
+                "msg": "level 1",
+                "sources": [{
+                    "msg": "level 2",
+                    "sources": [{ "msg": "... (truncated)" }],
+                }],
+            }],
+        }),
+    );
+
+    // Sources below the maximum depth are never visited.
+    let traced = Traced::default();
+    serde_json::to_string(&Ser::new(&traced).with_max_depth(3)).unwrap();
+    assert_eq!(
+        traced.visited.load(Ordering::Relaxed),
+        4,
+        "sources visited for levels 0 to 3",
+    );
+
+    // Trees that fit within the maximum depth are unchanged, and type tags are still added.
+    let mishap = mishap_testdata::complex();
+    assert_eq!(
+        serde_json::to_value(Ser::new(&mishap).with_max_depth(mishap.depth())).unwrap(),
+        serde_json::to_value(Ser::new(&mishap)).unwrap(),
+    );
+    let value = serde_json::to_value(Ser::new(&mishap).with_max_depth(0).with_type_tags()).unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({
+            "type": "chain",
+            "msg": "top-level line1\ntop-level line2",
+            "sources": [{ "type": "leaf", "msg": "... (truncated)" }],
+        }),
+    );
+}
+
+/// An infinitely deep tree that counts how many times its sources are requested.
+#[derive(Debug, Default)]
+struct Traced {
+    depth: usize,
+    visited: Arc<AtomicUsize>,
+    source: OnceLock<Box<Traced>>,
+}
+
+impl fmt::Display for Traced {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "level {}", self.depth)
+    }
+}
+
+impl ErrorTree for Traced {
+    fn sources(&self) -> Box<dyn Iterator<Item = ErrorTreeSource<'_>> + '_> {
+        self.visited.fetch_add(1, Ordering::Relaxed);
+        let source = self.source.get_or_init(|| {
+            Box::new(Traced {
+                depth: self.depth + 1,
+                visited: self.visited.clone(),
+                source: OnceLock::new(),
+            })
+        });
+        Box::new(std::iter::once(ErrorTreeSource::Tree(&**source)))
+    }
+}
+
+#[test]
+fn test_ser_std_error() {
+    #[derive(Debug)]
+    struct ReadConfig(io::Error);
+
+    impl fmt::Display for ReadConfig {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("failed to read config")
+        }
+    }
+
+    impl error::Error for ReadConfig {
+        fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    let error = ReadConfig(io::Error::new(
+        io::ErrorKind::NotFound,
+        "config.toml not found",
+    ));
+    let json = serde_json::to_string(&SerStdError::new(&error)).unwrap();
+    assert_eq!(
+        json,
+        r#"{"msg":"failed to read config","sources":[{"msg":"config.toml not found"}]}"#,
+    );
+
+    // The output matches the format produced for error trees.
+    let tree: SerdeErrorTree = serde_json::from_str(&json).unwrap();
+    assert_eq!(serde_json::to_string(&Ser::new(&tree)).unwrap(), json);
+}
+
+#[test]
+fn test_with_debug() {
+    let tree = OwnedErrorTree::from_msg_and_sources(
+        "outer",
+        vec![OwnedErrorTree::from_msg_and_sources("inner", vec![])],
+    );
+
+    // Without the option, or with it disabled, the output is unchanged.
+    let plain = serde_json::to_value(Ser::new(&tree)).unwrap();
+    assert_eq!(
+        plain,
+        serde_json::json!({"msg": "outer", "sources": [{"msg": "inner"}]}),
+    );
+    let disabled = serde_json::to_value(Ser::new(&tree).with_debug(false)).unwrap();
+    assert_eq!(disabled, plain);
+
+    let value = serde_json::to_value(Ser::new(&tree).with_debug(true)).unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({
+            "msg": "outer",
+            "debug": format!("{tree:?}"),
+            "sources": [{"msg": "inner", "debug": format!("{:?}", tree.sources[0])}],
+        }),
+    );
+
+    // The debug field is ignored while deserializing.
+    let deserialized: SerdeErrorTree = serde_json::from_value(value).unwrap();
+    assert_eq!(deserialized, SerdeErrorTree::new(&tree));
+}
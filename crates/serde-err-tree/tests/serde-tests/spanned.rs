@@ -0,0 +1,69 @@
+use err_tree::ErrorTreeExt;
+use pretty_assertions::assert_eq;
+use serde_err_tree::SpannedErrorTree;
+
+const CONFIG: &str = "[server]\nport = \"eighty\"\nhots = \"localhost\"\n";
+
+fn span(text: &str) -> (usize, usize) {
+    let start = CONFIG.find(text).expect("text is in CONFIG");
+    (start, start + text.len())
+}
+
+#[test]
+fn test_spanned() {
+    let (port_start, port_end) = span("\"eighty\"");
+    let (server_start, server_end) = span("[server]");
+    let (hots_start, hots_end) = span("hots");
+    let mut tree = SpannedErrorTree::new("failed to parse config.toml")
+        .with_source(
+            SpannedErrorTree::new("invalid value for `port`").with_span(port_start, port_end),
+        )
+        .with_source(
+            SpannedErrorTree::new("invalid table `server`")
+                .with_span(server_start, server_end)
+                .with_source(
+                    SpannedErrorTree::new("unknown key `hots`").with_span(hots_start, hots_end),
+                ),
+        );
+
+    // Spans are serialized as byte ranges.
+    let json = serde_json::to_string(&tree).unwrap();
+    assert_eq!(
+        json,
+        r#"{"msg":"failed to parse config.toml","sources":["#.to_owned()
+            + r#"{"msg":"invalid value for `port`","span":[16,24]},"#
+            + r#"{"msg":"invalid table `server`","span":[0,8],"sources":["#
+            + r#"{"msg":"unknown key `hots`","span":[25,29]}]}]}"#,
+    );
+
+    let mut tree2: SpannedErrorTree = serde_json::from_str(&json).unwrap();
+    assert_eq!(tree, tree2, "spans are preserved through serialization");
+
+    // Positions are only shown once they've been computed from the source text.
+    let expected = "failed to parse config.toml\n\
+                    \n\
+                    Caused by:\n\n  \
+                      + invalid value for `port` (2:8)\n  \
+                      + invalid table `server` (1:1)\n      \
+                          - unknown key `hots` (3:1)\n";
+    assert!(!tree.display_tree().to_string().contains("(2:8)"));
+    tree.locate(CONFIG);
+    assert_eq!(tree.display_tree().to_string(), expected);
+    tree2.locate(CONFIG);
+    assert_eq!(tree2.display_tree().to_string(), expected);
+}
+
+#[test]
+fn test_locate() {
+    let text = "ab\nçd\n";
+    let mut tree = SpannedErrorTree::new("root")
+        .with_source(SpannedErrorTree::new("start").with_span(0, 1))
+        .with_source(SpannedErrorTree::new("after multibyte").with_span(5, 6))
+        .with_source(SpannedErrorTree::new("past end").with_span(100, 101))
+        .with_source(SpannedErrorTree::new("no span"));
+    tree.locate(text);
+
+    let positions: Vec<_> = tree.sources.iter().map(|source| source.position).collect();
+    assert_eq!(positions, [Some((1, 1)), Some((2, 2)), Some((3, 1)), None]);
+    assert_eq!(tree.position, None);
+}
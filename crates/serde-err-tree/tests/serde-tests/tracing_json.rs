@@ -0,0 +1,26 @@
+use serde_err_tree::tracing_json::TracingJson;
+use serde_json::json;
+
+#[test]
+fn test_single_source() {
+    let mishap = mishap_testdata::single_source();
+    let value = serde_json::to_value(TracingJson::new(&mishap)).unwrap();
+
+    let expected = json!({
+        "error": "mishap2 line1\nmishap2 line2",
+        "error.sources": [{
+            "error": "mishap1 line1\nmishap1 line2",
+            "error.sources": [{
+                "error": "anyhow error3",
+                "error.sources": [{
+                    "error": "anyhow error2",
+                    "error.sources": [{
+                        "error": "anyhow error",
+                        "error.sources": [],
+                    }],
+                }],
+            }],
+        }],
+    });
+    assert_eq!(value, expected);
+}